@@ -0,0 +1,664 @@
+//! A terminal without TTY, mainly to test games.
+//!
+//! A [`VirtualTerminal`] interprets everything written to it (text and ANSI escape sequences, as
+//! emitted by crossterm's commands) into a [`Screen`], a grid of styled [`Cell`]s, and gives the
+//! events that were scripted beforehand.
+//!
+//! ```
+//! use crossterm::{cursor::MoveTo, event::{KeyCode, KeyModifiers}, style::Stylize, QueueableCommand};
+//! use std::io::Write;
+//! use terminity::backend::{Terminal, VirtualTerminal};
+//!
+//! let mut term = VirtualTerminal::new((10, 3));
+//! term.queue(MoveTo(2, 1)).unwrap();
+//! write!(term, "{}", "Hi!".bold()).unwrap();
+//! assert_eq!(term.screen().line(1), "  Hi!     ");
+//! assert_eq!(term.screen().find("Hi"), Some((2, 1)));
+//!
+//! term.push_key(KeyCode::Enter, KeyModifiers::NONE);
+//! assert!(term.poll(std::time::Duration::ZERO).unwrap());
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+use crossterm::style::ContentStyle;
+use terminity_widgets::render::apply_sgr;
+use unicode_width::UnicodeWidthChar;
+
+use super::Terminal;
+
+/// A cell of a [`Screen`], the same as the ones widgets are rendered into.
+pub use terminity_widgets::render::Cell;
+
+/// The state of the escape sequences parser.
+#[derive(Debug)]
+enum Parser {
+	/// Printing text
+	Ground,
+	/// Just got an `ESC`
+	Escape,
+	/// In a Control Sequence Introducer, with the parameters gathered so far
+	Csi(String),
+	/// In an Operating System Command, ignored until its end
+	Osc,
+}
+
+/// A virtual screen, interpreting text and ANSI escape sequences into a grid of [`Cell`]s.
+///
+/// The screen handles what terminity and crossterm use: printing (with wide characters and
+/// automatic wrapping), `\r`, `\n`, cursor moves, save and restore, visibility, clearing,
+/// scrolling, colors and attributes, and the alternate screen. Unknown sequences are ignored.
+#[derive(Debug)]
+pub struct Screen {
+	size: (u16, u16),
+	cells: Vec<Cell>,
+	/// The content of the main screen while the alternate one is displayed.
+	main_cells: Option<Vec<Cell>>,
+	cursor: (u16, u16),
+	saved_cursor: (u16, u16),
+	cursor_visible: bool,
+	/// Whether the cursor is past the last column, the next printed character going on a new line.
+	wrap_pending: bool,
+	/// The current style, applied to the printed characters.
+	pen: ContentStyle,
+	parser: Parser,
+	/// Bytes of an incomplete UTF-8 character.
+	utf8_buffer: Vec<u8>,
+}
+
+impl Screen {
+	/// Creates an empty screen of the given size (columns, rows).
+	pub fn new(size: (u16, u16)) -> Self {
+		Self {
+			size,
+			cells: vec![Cell::default(); size.0 as usize * size.1 as usize],
+			main_cells: None,
+			cursor: (0, 0),
+			saved_cursor: (0, 0),
+			cursor_visible: true,
+			wrap_pending: false,
+			pen: ContentStyle::new(),
+			parser: Parser::Ground,
+			utf8_buffer: vec![],
+		}
+	}
+
+	/// The size (columns, rows) of the screen.
+	pub fn size(&self) -> (u16, u16) {
+		self.size
+	}
+
+	/// The position (column, row) of the cursor.
+	pub fn cursor(&self) -> (u16, u16) {
+		self.cursor
+	}
+
+	/// Whether the cursor is shown.
+	pub fn cursor_visible(&self) -> bool {
+		self.cursor_visible
+	}
+
+	/// The cell at the given position. Panics if the position is out of the screen.
+	pub fn cell(&self, x: u16, y: u16) -> &Cell {
+		assert!(x < self.size.0 && y < self.size.1, "Cell ({}, {}) out of the screen", x, y);
+		&self.cells[self.index(x, y)]
+	}
+
+	/// The text of the given row, without any style.
+	pub fn line(&self, y: u16) -> String {
+		(0..self.size.0).map(|x| self.cell(x, y).content.as_str()).collect()
+	}
+
+	/// The text of all the rows, without any style.
+	pub fn lines(&self) -> Vec<String> {
+		(0..self.size.1).map(|y| self.line(y)).collect()
+	}
+
+	/// Finds the position (column, row) of the first occurrence of `text` on the screen. The text
+	/// has to be on a single row.
+	pub fn find(&self, text: &str) -> Option<(u16, u16)> {
+		(0..self.size.1).find_map(|y| {
+			let mut line = String::new();
+			// Byte index in `line` at which each cell starts
+			let mut starts = vec![];
+			for x in 0..self.size.0 {
+				starts.push(line.len());
+				line.push_str(&self.cell(x, y).content);
+			}
+			let found = line.find(text)?;
+			Some((starts.iter().rposition(|s| *s <= found).unwrap_or(0) as u16, y))
+		})
+	}
+
+	/// Resizes the screen, keeping the content at the top-left.
+	pub fn resize(&mut self, size: (u16, u16)) {
+		let old_size = self.size;
+		let resize_cells = |cells: &[Cell]| {
+			let mut res = vec![Cell::default(); size.0 as usize * size.1 as usize];
+			for y in 0..size.1.min(old_size.1) as usize {
+				for x in 0..size.0.min(old_size.0) as usize {
+					res[x + y * size.0 as usize] = cells[x + y * old_size.0 as usize].clone();
+				}
+			}
+			res
+		};
+		self.main_cells = self.main_cells.as_deref().map(resize_cells);
+		self.cells = resize_cells(&self.cells);
+		self.size = size;
+		self.cursor = (
+			self.cursor.0.min(size.0.saturating_sub(1)),
+			self.cursor.1.min(size.1.saturating_sub(1)),
+		);
+		self.wrap_pending = false;
+	}
+
+	/// Interprets the given bytes.
+	pub fn process(&mut self, bytes: &[u8]) {
+		self.utf8_buffer.extend_from_slice(bytes);
+		let buffer = std::mem::take(&mut self.utf8_buffer);
+		let mut rest = &buffer[..];
+		loop {
+			match std::str::from_utf8(rest) {
+				Ok(s) => {
+					s.chars().for_each(|c| self.process_char(c));
+					break;
+				}
+				Err(e) => {
+					let (valid, after) = rest.split_at(e.valid_up_to());
+					// Safe: checked just above
+					std::str::from_utf8(valid).unwrap().chars().for_each(|c| self.process_char(c));
+					match e.error_len() {
+						// Incomplete character, waiting for the next bytes
+						None => {
+							self.utf8_buffer = after.to_vec();
+							break;
+						}
+						Some(len) => {
+							self.process_char(char::REPLACEMENT_CHARACTER);
+							rest = &after[len..];
+						}
+					}
+				}
+			}
+		}
+	}
+
+	fn index(&self, x: u16, y: u16) -> usize {
+		x as usize + y as usize * self.size.0 as usize
+	}
+
+	fn process_char(&mut self, c: char) {
+		match std::mem::replace(&mut self.parser, Parser::Ground) {
+			Parser::Ground => match c {
+				'\x1b' => self.parser = Parser::Escape,
+				'\r' => self.move_to(0, self.cursor.1),
+				'\n' => {
+					self.wrap_pending = false;
+					self.line_feed();
+				}
+				'\x08' => self.move_to(self.cursor.0.saturating_sub(1), self.cursor.1),
+				'\t' => self.move_to(
+					((self.cursor.0 / 8 + 1) * 8).min(self.size.0.saturating_sub(1)),
+					self.cursor.1,
+				),
+				c if c.is_control() => (),
+				// Nothing can be printed on an empty screen
+				_ if self.cells.is_empty() => (),
+				c => self.print(c),
+			},
+			Parser::Escape => match c {
+				'[' => self.parser = Parser::Csi(String::new()),
+				']' => self.parser = Parser::Osc,
+				'7' => self.saved_cursor = self.cursor,
+				'8' => self.move_to(self.saved_cursor.0, self.saved_cursor.1),
+				_ => (),
+			},
+			Parser::Csi(mut params) => {
+				if ('\x40'..='\x7e').contains(&c) {
+					self.csi(&params, c);
+				} else {
+					params.push(c);
+					self.parser = Parser::Csi(params);
+				}
+			}
+			Parser::Osc => match c {
+				'\x07' => (),
+				'\x1b' => self.parser = Parser::Escape,
+				_ => self.parser = Parser::Osc,
+			},
+		}
+	}
+
+	fn print(&mut self, c: char) {
+		let width = c.width().unwrap_or(0) as u16;
+		if width == 0 {
+			// Combining character: goes with the previous one
+			let (x, y) = self.cursor;
+			let x = if self.wrap_pending { x } else { x.saturating_sub(1) };
+			let i = self.index(x, y);
+			self.cells[i].content.push(c);
+			return;
+		}
+		// A wide character that can't fit in a line is replaced by a blank cell
+		let (content, width) =
+			if width > self.size.0 { (" ".to_owned(), 1) } else { (c.to_string(), width) };
+		if self.wrap_pending || self.cursor.0 + width > self.size.0 {
+			self.cursor.0 = 0;
+			self.line_feed();
+		}
+		self.wrap_pending = false;
+		let (x, y) = self.cursor;
+		self.put(x, y, Cell { content, style: self.pen });
+		if width == 2 {
+			self.put(x + 1, y, Cell { content: String::new(), style: self.pen });
+		}
+		if x + width >= self.size.0 {
+			self.cursor.0 = self.size.0.saturating_sub(1);
+			self.wrap_pending = true;
+		} else {
+			self.cursor.0 = x + width;
+		}
+	}
+
+	/// Puts a cell, erasing any wide character it overlaps.
+	fn put(&mut self, x: u16, y: u16, cell: Cell) {
+		let i = self.index(x, y);
+		if self.cells[i].content.is_empty() && x > 0 {
+			self.cells[i - 1] = Cell { content: " ".to_owned(), style: self.cells[i - 1].style };
+		}
+		if x + 1 < self.size.0 && self.cells[i + 1].content.is_empty() {
+			self.cells[i + 1] = Cell { content: " ".to_owned(), style: self.cells[i + 1].style };
+		}
+		self.cells[i] = cell;
+	}
+
+	fn move_to(&mut self, x: u16, y: u16) {
+		self.cursor = (x.min(self.size.0.saturating_sub(1)), y.min(self.size.1.saturating_sub(1)));
+		self.wrap_pending = false;
+	}
+
+	fn line_feed(&mut self) {
+		if self.cursor.1 + 1 < self.size.1 {
+			self.cursor.1 += 1;
+		} else {
+			self.scroll_up(1);
+		}
+	}
+
+	/// A blank cell, with the current background (as terminals usually do when erasing).
+	fn blank(&self) -> Cell {
+		let mut cell = Cell::default();
+		cell.style.background_color = self.pen.background_color;
+		cell
+	}
+
+	fn scroll_up(&mut self, n: u16) {
+		let n = (n.min(self.size.1) as usize) * self.size.0 as usize;
+		self.cells.drain(..n);
+		self.cells.extend(std::iter::repeat_n(self.blank(), n));
+	}
+
+	fn scroll_down(&mut self, n: u16) {
+		let n = (n.min(self.size.1) as usize) * self.size.0 as usize;
+		self.cells.truncate(self.cells.len() - n);
+		self.cells.splice(0..0, std::iter::repeat_n(self.blank(), n));
+	}
+
+	/// Erases the cells from `start` (included) to `end` (excluded), as indexes in `cells`.
+	fn erase(&mut self, start: usize, end: usize) {
+		let blank = self.blank();
+		let end = end.min(self.cells.len());
+		self.cells[start.min(end)..end].fill(blank);
+	}
+
+	fn csi(&mut self, params: &str, action: char) {
+		let private = params.starts_with('?');
+		let params_list: Vec<u16> =
+			params.trim_start_matches('?').split(';').map(|p| p.parse().unwrap_or(0)).collect();
+		// First parameter, with a default value of 1 (as for most sequences)
+		let n = params_list[0].max(1);
+		let (x, y) = self.cursor;
+		match action {
+			'H' | 'f' => {
+				let col = params_list.get(1).copied().unwrap_or(1).max(1);
+				self.move_to(col - 1, n - 1);
+			}
+			'A' => self.move_to(x, y.saturating_sub(n)),
+			'B' => self.move_to(x, y.saturating_add(n)),
+			'C' => self.move_to(x.saturating_add(n), y),
+			'D' => self.move_to(x.saturating_sub(n), y),
+			'E' => self.move_to(0, y.saturating_add(n)),
+			'F' => self.move_to(0, y.saturating_sub(n)),
+			'G' => self.move_to(n - 1, y),
+			'd' => self.move_to(x, n - 1),
+			'J' => {
+				let cursor = self.index(x, y);
+				match params_list[0] {
+					0 => self.erase(cursor, self.cells.len()),
+					1 => self.erase(0, cursor + 1),
+					_ => self.erase(0, self.cells.len()),
+				}
+			}
+			'K' => {
+				let (start, cursor) = (self.index(0, y), self.index(x, y));
+				let end = start + self.size.0 as usize;
+				match params_list[0] {
+					// If a wrap is pending, the cursor is after the last printed character
+					0 if self.wrap_pending => (),
+					0 => self.erase(cursor, end),
+					1 => self.erase(start, cursor + 1),
+					_ => self.erase(start, end),
+				}
+			}
+			'S' => self.scroll_up(n),
+			'T' => self.scroll_down(n),
+			'm' => self.sgr(params),
+			'h' | 'l' if private => {
+				let enable = action == 'h';
+				for mode in params_list {
+					match mode {
+						25 => self.cursor_visible = enable,
+						1049 => self.alternate_screen(enable),
+						_ => (),
+					}
+				}
+			}
+			_ => (),
+		}
+	}
+
+	fn alternate_screen(&mut self, enable: bool) {
+		if enable && self.main_cells.is_none() {
+			self.saved_cursor = self.cursor;
+			let blank = vec![Cell::default(); self.cells.len()];
+			self.main_cells = Some(std::mem::replace(&mut self.cells, blank));
+		} else if let (false, Some(main)) = (enable, self.main_cells.take()) {
+			self.cells = main;
+			self.move_to(self.saved_cursor.0, self.saved_cursor.1);
+		}
+	}
+
+	/// Select Graphic Rendition: changes the current style.
+	fn sgr(&mut self, params: &str) {
+		apply_sgr(&mut self.pen, params);
+	}
+}
+
+impl Display for Screen {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.lines().join("\n"))
+	}
+}
+
+/// A terminal without TTY: what's written to it is interpreted on a [`Screen`], and its events are
+/// scripted beforehand.
+///
+/// Once all the scripted events are consumed, polling or reading an event returns an error of kind
+/// [`UnexpectedEof`](io::ErrorKind::UnexpectedEof), so that a game waiting for more events stops
+/// instead of blocking forever.
+#[derive(Debug)]
+pub struct VirtualTerminal {
+	screen: Screen,
+	/// The scripted events. `None` stands for a poll that times out.
+	events: VecDeque<Option<Event>>,
+	/// The number of times the terminal was suspended.
+	suspensions: usize,
+}
+
+impl VirtualTerminal {
+	/// Creates a virtual terminal of the given size (columns, rows), with no scripted event.
+	pub fn new(size: (u16, u16)) -> Self {
+		Self { screen: Screen::new(size), events: VecDeque::new(), suspensions: 0 }
+	}
+
+	/// The screen, as currently displayed.
+	pub fn screen(&self) -> &Screen {
+		&self.screen
+	}
+
+	/// The number of scripted events (and timeouts) not consumed yet.
+	pub fn remaining_events(&self) -> usize {
+		self.events.len()
+	}
+
+	/// The number of times the program was [suspended](Terminal::suspend) on this terminal.
+	pub fn suspensions(&self) -> usize {
+		self.suspensions
+	}
+
+	/// Scripts an event.
+	pub fn push_event(&mut self, event: Event) -> &mut Self {
+		self.events.push_back(Some(event));
+		self
+	}
+
+	/// Scripts several events.
+	pub fn push_events(&mut self, events: impl IntoIterator<Item = Event>) -> &mut Self {
+		self.events.extend(events.into_iter().map(Some));
+		self
+	}
+
+	/// Scripts a key press.
+	pub fn push_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> &mut Self {
+		self.push_event(Event::Key(KeyEvent::new(code, modifiers)))
+	}
+
+	/// Scripts key presses, without modifiers.
+	pub fn push_keys(&mut self, codes: impl IntoIterator<Item = KeyCode>) -> &mut Self {
+		self.push_events(
+			codes.into_iter().map(|c| Event::Key(KeyEvent::new(c, KeyModifiers::NONE))),
+		)
+	}
+
+	/// Scripts a mouse event at the given position.
+	pub fn push_mouse(&mut self, kind: MouseEventKind, column: u16, row: u16) -> &mut Self {
+		self.push_event(Event::Mouse(MouseEvent {
+			kind,
+			column,
+			row,
+			modifiers: KeyModifiers::NONE,
+		}))
+	}
+
+	/// Scripts a resize of the terminal. The screen is resized when the event is read.
+	pub fn push_resize(&mut self, columns: u16, rows: u16) -> &mut Self {
+		self.push_event(Event::Resize(columns, rows))
+	}
+
+	/// Scripts a poll that times out, as if no event occurred for the requested duration.
+	pub fn push_timeout(&mut self) -> &mut Self {
+		self.events.push_back(None);
+		self
+	}
+}
+
+fn no_more_events() -> io::Error {
+	io::Error::new(io::ErrorKind::UnexpectedEof, "No more scripted events")
+}
+
+impl Write for VirtualTerminal {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.screen.process(buf);
+		Ok(buf.len())
+	}
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+impl Terminal for VirtualTerminal {
+	fn poll(&mut self, _timeout: Duration) -> io::Result<bool> {
+		match self.events.front() {
+			None => Err(no_more_events()),
+			Some(None) => {
+				self.events.pop_front();
+				Ok(false)
+			}
+			Some(Some(_)) => Ok(true),
+		}
+	}
+	fn read(&mut self) -> io::Result<Event> {
+		// Reading blocks until an event occurs: timeouts simply elapse
+		let event = self.events.iter().position(Option::is_some).and_then(|i| {
+			self.events.drain(..i);
+			self.events.pop_front().flatten()
+		});
+		let event = event.ok_or_else(|| {
+			self.events.clear();
+			no_more_events()
+		})?;
+		if let Event::Resize(w, h) = event {
+			self.screen.resize((w, h));
+		}
+		Ok(event)
+	}
+	fn size(&self) -> io::Result<(u16, u16)> {
+		Ok(self.screen.size())
+	}
+	fn suspend(&mut self) -> io::Result<()> {
+		self.suspensions += 1;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crossterm::cursor::{Hide, MoveTo, RestorePosition, SavePosition};
+	use crossterm::style::{Attribute, Color, Stylize};
+	use crossterm::terminal::{Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+	use crossterm::QueueableCommand;
+
+	#[test]
+	fn text_and_wrap() {
+		let mut term = VirtualTerminal::new((5, 3));
+		write!(term, "Hello\n\rWorld!").unwrap();
+		assert_eq!(term.screen().lines(), ["Hello", "World", "!    "]);
+		term.queue(MoveTo(1, 1)).unwrap();
+		write!(term, "\n\n").unwrap();
+		// Scrolled by one line
+		assert_eq!(term.screen().lines(), ["World", "!    ", "     "]);
+		assert_eq!(term.screen().cursor(), (1, 2));
+	}
+
+	#[test]
+	fn wide_chars() {
+		let mut term = VirtualTerminal::new((5, 2));
+		write!(term, "a漢字").unwrap();
+		assert_eq!(term.screen().line(0), "a漢字");
+		assert_eq!(term.screen().cell(2, 0).content, "");
+		assert_eq!(term.screen().line(1), "     ");
+		// Doesn't fit anymore: goes on the next line
+		term.queue(MoveTo(4, 0)).unwrap();
+		write!(term, "字").unwrap();
+		assert_eq!(term.screen().lines(), ["a漢字", "字   "]);
+		// Overwriting half of a wide character erases it
+		term.queue(MoveTo(2, 0)).unwrap();
+		write!(term, "b").unwrap();
+		assert_eq!(term.screen().lines(), ["a b字", "字   "]);
+		assert_eq!(term.screen().find("字"), Some((3, 0)));
+		assert_eq!(term.screen().find("字 "), Some((0, 1)));
+	}
+
+	#[test]
+	fn styles() {
+		let mut term = VirtualTerminal::new((6, 1));
+		write!(
+			term,
+			"{}{}",
+			"ab".red().on_dark_grey().bold(),
+			"c".with(Color::Rgb { r: 1, g: 2, b: 3 })
+		)
+		.unwrap();
+		let cell = term.screen().cell(1, 0);
+		assert_eq!(cell.style.foreground_color, Some(Color::Red));
+		assert_eq!(cell.style.background_color, Some(Color::DarkGrey));
+		assert!(cell.style.attributes.has(Attribute::Bold));
+		let cell = term.screen().cell(2, 0);
+		assert_eq!(cell.style.foreground_color, Some(Color::Rgb { r: 1, g: 2, b: 3 }));
+		assert_eq!(cell.style.background_color, None);
+		assert!(!cell.style.attributes.has(Attribute::Bold));
+	}
+
+	#[test]
+	fn clear_and_cursor() {
+		let mut term = VirtualTerminal::new((4, 3));
+		write!(term, "abcdefghijkl").unwrap();
+		term.queue(MoveTo(2, 1))
+			.unwrap()
+			.queue(Clear(ClearType::UntilNewLine))
+			.unwrap()
+			.queue(SavePosition)
+			.unwrap()
+			.queue(MoveTo(0, 0))
+			.unwrap()
+			.queue(Hide)
+			.unwrap()
+			.queue(RestorePosition)
+			.unwrap()
+			.queue(Clear(ClearType::FromCursorDown))
+			.unwrap();
+		assert_eq!(term.screen().lines(), ["abcd", "ef  ", "    "]);
+		assert_eq!(term.screen().cursor(), (2, 1));
+		assert!(!term.screen().cursor_visible());
+	}
+
+	#[test]
+	fn alternate_screen() {
+		let mut term = VirtualTerminal::new((3, 1));
+		write!(term, "abc").unwrap();
+		term.queue(EnterAlternateScreen).unwrap();
+		assert_eq!(term.screen().line(0), "   ");
+		write!(term, "\rd").unwrap();
+		assert_eq!(term.screen().line(0), "d  ");
+		term.queue(LeaveAlternateScreen).unwrap();
+		assert_eq!(term.screen().line(0), "abc");
+	}
+
+	#[test]
+	fn empty_screen() {
+		let mut term = VirtualTerminal::new((0, 0));
+		write!(term, "ab\u{301}\t世\r\n").unwrap();
+		term.queue(MoveTo(3, 2))
+			.unwrap()
+			.queue(Clear(ClearType::FromCursorUp))
+			.unwrap()
+			.queue(Clear(ClearType::UntilNewLine))
+			.unwrap();
+		assert_eq!(term.screen().cursor(), (0, 0));
+		assert!(term.screen().lines().is_empty());
+		term.push_resize(0, 2);
+		term.read().unwrap();
+		write!(term, "a\tb").unwrap();
+		assert_eq!(term.screen().lines(), ["", ""]);
+		term.push_resize(3, 1);
+		term.read().unwrap();
+		write!(term, "abcd").unwrap();
+		assert_eq!(term.screen().lines(), ["d  "]);
+	}
+
+	#[test]
+	fn narrow_screen() {
+		let mut term = VirtualTerminal::new((1, 3));
+		write!(term, "a世b").unwrap();
+		assert_eq!(term.screen().lines(), ["a", " ", "b"]);
+		assert_eq!(term.screen().cursor(), (0, 2));
+	}
+
+	#[test]
+	fn events() {
+		let mut term = VirtualTerminal::new((3, 1));
+		term.push_timeout().push_key(KeyCode::Enter, KeyModifiers::NONE).push_resize(5, 2);
+		assert!(!term.poll(Duration::ZERO).unwrap());
+		assert!(term.poll(Duration::ZERO).unwrap());
+		assert!(matches!(term.read().unwrap(), Event::Key(KeyEvent { code: KeyCode::Enter, .. })));
+		assert!(matches!(term.read().unwrap(), Event::Resize(5, 2)));
+		assert_eq!(term.size().unwrap(), (5, 2));
+		assert_eq!(term.read().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+		assert_eq!(term.poll(Duration::ZERO).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+	}
+}
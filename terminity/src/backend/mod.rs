@@ -0,0 +1,104 @@
+//! The terminals games run on.
+//!
+//! Games don't directly write to `stdout` nor read the events from crossterm: they are given a
+//! [`Terminal`], that both is the output sink and gives the events. This allows games to run on
+//! the actual terminal through [`Crossterm`], but also on a [`VirtualTerminal`](headless) that
+//! interprets what the game displays into a grid of cells and feeds scripted events, to test
+//! games without a TTY.
+
+use std::io::{self, Stdout, Write};
+use std::time::Duration;
+
+use crossterm::event::{
+	self, DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+	EnableFocusChange, EnableMouseCapture, Event,
+};
+use crossterm::{cursor, execute, terminal, QueueableCommand};
+
+pub mod headless;
+
+pub use headless::VirtualTerminal;
+
+/// A terminal a game can run on: it can be written to, and it gives the events that occur.
+///
+/// As any [`Write`], any crossterm command can be queued or executed on it.
+pub trait Terminal: Write {
+	/// Checks if an event is available, waiting at most `timeout` for one.
+	fn poll(&mut self, timeout: Duration) -> io::Result<bool>;
+	/// Reads the next event, blocking until one is available.
+	fn read(&mut self) -> io::Result<Event>;
+	/// The size (columns, rows) of the terminal.
+	fn size(&self) -> io::Result<(u16, u16)>;
+	/// Suspends the program (as `Ctrl+Z` usually does in a shell), giving the terminal back in its
+	/// original state until the program is resumed. Does nothing by default.
+	fn suspend(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+/// The actual terminal: writes to `stdout` and reads the events through crossterm.
+pub struct Crossterm(Stdout);
+
+impl Crossterm {
+	/// Gives the actual terminal.
+	pub fn new() -> Self {
+		Self(io::stdout())
+	}
+
+	/// Sets up the environment games run in: enables raw mode, paste, focus change and mouse
+	/// captures, enters the alternate screen and moves the cursor to (0, 0).
+	pub(crate) fn setup(&mut self) -> io::Result<()> {
+		terminal::enable_raw_mode()?;
+		execute!(self, EnableBracketedPaste, EnableFocusChange, EnableMouseCapture)?;
+		self.queue(cursor::SavePosition)?
+			.queue(terminal::EnterAlternateScreen)?
+			.queue(cursor::MoveTo(0, 0))?
+			.flush()
+	}
+
+	/// Restores the terminal state as it was before [`setup`](Self::setup).
+	pub(crate) fn restore(&mut self) -> io::Result<()> {
+		self.queue(terminal::LeaveAlternateScreen)?
+			.queue(cursor::RestorePosition)?
+			.queue(cursor::Show)?
+			.flush()?;
+		execute!(self, DisableBracketedPaste, DisableFocusChange, DisableMouseCapture)?;
+		terminal::disable_raw_mode()
+	}
+}
+
+impl Default for Crossterm {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Write for Crossterm {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.0.write(buf)
+	}
+	fn flush(&mut self) -> io::Result<()> {
+		self.0.flush()
+	}
+}
+
+impl Terminal for Crossterm {
+	fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+		event::poll(timeout)
+	}
+	fn read(&mut self) -> io::Result<Event> {
+		event::read()
+	}
+	fn size(&self) -> io::Result<(u16, u16)> {
+		terminal::size()
+	}
+	#[cfg(unix)]
+	fn suspend(&mut self) -> io::Result<()> {
+		self.restore()?;
+		// Raw mode disables the signal usually sent by Ctrl+Z: sending it ourselves. The call
+		// returns once the program is resumed.
+		// SAFETY: raising a signal has no precondition
+		unsafe { libc::raise(libc::SIGTSTP) };
+		self.setup()
+	}
+}
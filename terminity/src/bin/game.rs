@@ -1,15 +1,292 @@
-use terminity::games;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+use std::time::Duration;
 
 use structopt::StructOpt;
+use terminity::games::chess::ai::{Budget, MAX_LEVEL};
+use terminity::games::chess::clock::TimeControl;
+use terminity::games::chess::rules::{Color, History, Position};
+use terminity::games::chess::theme::{Config, PieceSet, BUILTIN_THEMES};
+use terminity::games::chess::uci::Limit;
+use terminity::games::chess::{Chess, Perspective};
+use terminity::games::stratego::rules::Player as Army;
+use terminity::games::stratego::Stratego;
+use terminity::games::sttt::ai::Level;
+use terminity::games::sttt::rules::{Player, Rules, SentToClosed, SIZES};
+use terminity::games::sttt::SuperTTT;
+use terminity::games::{self, GameWrapper};
+
 #[derive(StructOpt)]
 struct MasterOpt {
 	#[structopt(required = true)]
 	game: String,
+	/// Chess only: the position to start from, in FEN
+	#[structopt(long, conflicts_with = "pgn")]
+	fen: Option<String>,
+	/// Chess only: a PGN file of the game to resume
+	#[structopt(long, parse(from_os_str), conflicts_with = "chess960")]
+	pgn: Option<PathBuf>,
+	/// Chess only: plays Chess960 from the starting position of the given number (0 to 959), or
+	/// from a random one with "random"
+	#[structopt(long, parse(try_from_str = parse_chess960), conflicts_with = "fen")]
+	chess960: Option<Position>,
+	/// Chess only: sets up the starting position in an editor before playing
+	#[structopt(long)]
+	edit: bool,
+	/// Chess only: the file to save the game to as PGN when exiting
+	#[structopt(long, parse(from_os_str))]
+	save: Option<PathBuf>,
+	/// Who the computer plays: white or black for Chess, x or o for SuperTicTacToe, red or blue for
+	/// Stratego
+	#[structopt(long)]
+	computer: Option<String>,
+	/// Chess only: the strength of the computer, from 1 to 5 (3 by default). With --engine, the
+	/// seconds the engine thinks per move
+	#[structopt(long, requires = "computer")]
+	level: Option<u8>,
+	/// SuperTicTacToe only: how the computer plays, random, heuristic or mcts (the strongest, by
+	/// default)
+	#[structopt(long, requires = "computer")]
+	difficulty: Option<Level>,
+	/// SuperTicTacToe only: the number of zones on each side of the board, and of cells on each
+	/// side of a zone, from 2 to 5 (3 by default)
+	#[structopt(long)]
+	size: Option<u8>,
+	/// SuperTicTacToe only: the cells left in a won zone can still be played in
+	#[structopt(long)]
+	won_zones_open: bool,
+	/// SuperTicTacToe only: a player sent to a closed zone loses their turn, instead of choosing
+	/// the zone they play in
+	#[structopt(long)]
+	lose_turn: bool,
+	/// The seed the computer makes its random choices with, as between equal moves, random by
+	/// default
+	#[structopt(long, requires = "computer")]
+	seed: Option<u64>,
+	/// Chess only: a UCI engine program (as stockfish) playing for the computer instead of the
+	/// built-in one
+	#[structopt(long, parse(from_os_str), requires = "computer")]
+	engine: Option<PathBuf>,
+	/// Chess only: a UCI engine program analysing the game, showing its evaluation
+	#[structopt(long, parse(from_os_str))]
+	analyse: Option<PathBuf>,
+	/// Chess only: plays with a clock giving each player the given minutes
+	#[structopt(long)]
+	clock: Option<u64>,
+	/// Chess only: the seconds added to the clock of a player after each of their moves
+	#[structopt(long, requires = "clock", conflicts_with = "delay")]
+	increment: Option<u64>,
+	/// Chess only: the Bronstein delay in seconds, the time spent on a move being given back up
+	/// to it
+	#[structopt(long, requires = "clock")]
+	delay: Option<u64>,
+	/// Chess only: the side the board is seen from, white, black or auto to follow the player to
+	/// play
+	#[structopt(long, parse(try_from_str = parse_perspective))]
+	perspective: Option<Perspective>,
+	/// Chess only: the config file defining the themes, ~/.config/terminity/chess.conf by default
+	#[structopt(long, parse(from_os_str))]
+	config: Option<PathBuf>,
+	/// Chess only: the colours of the board, classic, wood (true colours), wood-256 or one of
+	/// the config file
+	#[structopt(long)]
+	theme: Option<String>,
+	/// Chess only: how the pieces are drawn, glyphs, filled or ascii
+	#[structopt(long)]
+	pieces: Option<PieceSet>,
+	/// Chess only: draws a larger board, each tile taking two lines
+	#[structopt(long)]
+	large: bool,
+}
+
+fn parse_color(color: &str) -> Result<Color, String> {
+	match color.to_ascii_lowercase().as_str() {
+		"white" | "w" => Ok(Color::White),
+		"black" | "b" => Ok(Color::Black),
+		_ => Err(format!("Unknown color {:?}, expected white or black", color)),
+	}
+}
+
+fn parse_player(player: &str) -> Result<Player, String> {
+	match player.to_ascii_lowercase().as_str() {
+		"x" => Ok(Player::X),
+		"o" => Ok(Player::O),
+		_ => Err(format!("Unknown player {:?}, expected x or o", player)),
+	}
+}
+
+fn parse_army(army: &str) -> Result<Army, String> {
+	match army.to_ascii_lowercase().as_str() {
+		"red" | "r" => Ok(Army::Red),
+		"blue" | "b" => Ok(Army::Blue),
+		_ => Err(format!("Unknown army {:?}, expected red or blue", army)),
+	}
+}
+
+fn parse_chess960(number: &str) -> Result<Position, String> {
+	if number == "random" {
+		return Ok(Position::random_chess960(&mut rand::thread_rng()));
+	}
+	number.parse().ok().and_then(Position::chess960).ok_or_else(|| {
+		format!("Invalid Chess960 position {:?}, expected 0 to 959 or random", number)
+	})
 }
+
+fn parse_perspective(perspective: &str) -> Result<Perspective, String> {
+	match perspective.to_ascii_lowercase().as_str() {
+		"auto" => Ok(Perspective::Auto),
+		color => parse_color(color).map(Perspective::Fixed).map_err(|_| {
+			format!("Unknown perspective {:?}, expected white, black or auto", perspective)
+		}),
+	}
+}
+
 fn main() -> std::io::Result<()> {
 	let opt: MasterOpt = MasterOpt::from_args();
+	let chess_options = [
+		opt.fen.is_some(),
+		opt.pgn.is_some(),
+		opt.chess960.is_some(),
+		opt.edit,
+		opt.save.is_some(),
+		opt.level.is_some(),
+		opt.engine.is_some(),
+		opt.analyse.is_some(),
+		opt.clock.is_some(),
+		opt.perspective.is_some(),
+		opt.config.is_some(),
+		opt.theme.is_some(),
+		opt.pieces.is_some(),
+		opt.large,
+	];
+	if chess_options.contains(&true) && opt.game != "Chess" {
+		let msg =
+			"--fen, --pgn, --chess960, --edit, --save, --level, --engine, --analyse, --clock, \
+			--perspective, --config, --theme, --pieces and --large are only available for Chess";
+		return Err(io::Error::new(ErrorKind::InvalidInput, msg));
+	}
+	let sttt_options =
+		[opt.difficulty.is_some(), opt.size.is_some(), opt.won_zones_open, opt.lose_turn];
+	if sttt_options.contains(&true) && opt.game != "SuperTicTacToe" {
+		let msg = "--difficulty, --size, --won-zones-open and --lose-turn are only available for \
+			SuperTicTacToe";
+		return Err(io::Error::new(ErrorKind::InvalidInput, msg));
+	}
+	if opt.computer.is_some()
+		&& !["Chess", "SuperTicTacToe", "Stratego"].contains(&opt.game.as_str())
+	{
+		let msg = "--computer is only available for Chess, SuperTicTacToe and Stratego";
+		return Err(io::Error::new(ErrorKind::InvalidInput, msg));
+	}
+	let invalid_input = |e| io::Error::new(ErrorKind::InvalidInput, e);
+	if opt.game == "SuperTicTacToe" {
+		let mut rules = Rules { won_zones_open: opt.won_zones_open, ..Rules::default() };
+		if opt.lose_turn {
+			rules.sent_to_closed = SentToClosed::LoseTurn;
+		}
+		if let Some(size) = opt.size {
+			if !SIZES.contains(&size) {
+				let msg = format!("The size must be between {} and {}", SIZES.start(), SIZES.end());
+				return Err(io::Error::new(ErrorKind::InvalidInput, msg));
+			}
+			rules.size = size;
+		}
+		let mut game = SuperTTT::new().with_rules(rules);
+		if let Some(player) = opt.computer {
+			let player = parse_player(&player).map_err(invalid_input)?;
+			let seed = opt.seed.unwrap_or_else(rand::random);
+			game = game.against_computer(player, opt.difficulty.unwrap_or_default(), seed);
+		}
+		return GameWrapper::new(game).run();
+	}
+	if opt.game == "Stratego" {
+		let mut game = Stratego::new();
+		if let Some(army) = opt.computer {
+			let army = parse_army(&army).map_err(invalid_input)?;
+			game = game.against_computer(army, opt.seed.unwrap_or_else(rand::random));
+		}
+		return GameWrapper::new(game).run();
+	}
+	if opt.game == "Chess" {
+		let invalid = |e| io::Error::new(ErrorKind::InvalidData, e);
+		let history = match (opt.fen, opt.pgn, opt.chess960) {
+			(Some(fen), _, _) => History::new(Position::from_fen(&fen).map_err(invalid)?),
+			(_, Some(path), _) => History::from_pgn(&fs::read_to_string(path)?).map_err(invalid)?,
+			(_, _, Some(position)) => History::new(position),
+			_ => History::default(),
+		};
+		let mut chess = Chess::new(history);
+		if opt.edit {
+			chess = chess.edit_position();
+		}
+		if let Some(path) = opt.save {
+			chess = chess.save_to(path);
+		}
+		if let Some(color) = opt.computer {
+			let color = parse_color(&color).map_err(invalid_input)?;
+			let level = opt.level.unwrap_or(3);
+			if !(1..=MAX_LEVEL).contains(&level) {
+				let msg = format!("The level must be between 1 and {}", MAX_LEVEL);
+				return Err(io::Error::new(ErrorKind::InvalidInput, msg));
+			}
+			chess = match opt.engine {
+				Some(engine) => {
+					let time = Duration::from_secs(level as u64);
+					chess.against_engine(color, engine, Limit::MoveTime(time))
+				}
+				None => {
+					let seed = opt.seed.unwrap_or_else(rand::random);
+					chess.against_computer(color, Budget::level(level), seed)
+				}
+			};
+		}
+		if let Some(engine) = opt.analyse {
+			chess = chess.analysed_by(engine);
+		}
+		if let Some(perspective) = opt.perspective {
+			chess = chess.seen_from(perspective);
+		}
+		if let Some(minutes) = opt.clock {
+			let base = Duration::from_secs(60 * minutes);
+			chess = chess.with_clock(match (opt.increment, opt.delay) {
+				(Some(increment), _) => {
+					TimeControl::Fischer { base, increment: Duration::from_secs(increment) }
+				}
+				(_, Some(delay)) => {
+					TimeControl::Bronstein { base, delay: Duration::from_secs(delay) }
+				}
+				_ => TimeControl::SuddenDeath(base),
+			});
+		}
+		// The config file is optional, unless given explicitly
+		let path = opt.config.or_else(|| Config::default_path().filter(|path| path.exists()));
+		let config = match path {
+			Some(path) => fs::read_to_string(path)?.parse().map_err(invalid)?,
+			None => Config::default(),
+		};
+		if let Some(name) = opt.theme.or(config.theme.clone()) {
+			let theme = config.theme(&name).ok_or_else(|| {
+				let mut themes: Vec<_> = config.themes.keys().map(String::as_str).collect();
+				themes.extend(BUILTIN_THEMES);
+				themes.sort_unstable();
+				themes.dedup();
+				let msg =
+					format!("Unknown theme {:?}, expected one of {}", name, themes.join(", "));
+				io::Error::new(ErrorKind::InvalidInput, msg)
+			})?;
+			chess = chess.with_theme(theme);
+		}
+		if let Some(pieces) = opt.pieces.or(config.pieces) {
+			chess = chess.with_pieces(pieces);
+		}
+		if opt.large || config.large == Some(true) {
+			chess = chess.large_board();
+		}
+		return GameWrapper::new(chess).run();
+	}
 	games::get(&opt.game)
-		.expect(&("Unable to find game named ".to_owned() + &opt.game))
+		.unwrap_or_else(|| panic!("Unable to find game named {}", opt.game))
 		.run()
 		.unwrap();
 	Ok(())
@@ -1,3 +1,5 @@
+use terminity::{games, hub};
 
-fn main() {
+fn main() -> std::io::Result<()> {
+	hub::run(&games::REGISTERY)
 }
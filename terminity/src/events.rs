@@ -0,0 +1,225 @@
+//! The event loop games run in.
+//!
+//! Instead of reading the events of the terminal themselves, games implement [`EventHandler`] and
+//! are given to [`run`], that dispatches the events (and regular ticks) to them, renders them after
+//! each event, and handles what should behave the same way in all games:
+//!
+//! * `Ctrl+C` exits the event loop,
+//! * `Ctrl+Z` suspends the program (see [`Terminal::suspend`]), the screen being cleared and
+//!   redrawn when it is resumed,
+//! * the screen is cleared when the terminal is resized, the handler only needs to adapt to the new
+//!   size (and to [invalidate](terminity_widgets::render::Renderer::invalidate) its renderer, if it
+//!   renders through one).
+//!
+//! ```
+//! use std::io::{self, Write};
+//! use crossterm::event::{KeyCode, KeyModifiers};
+//! use terminity::backend::VirtualTerminal;
+//! use terminity::events::{self, Event, EventHandler, Flow};
+//!
+//! /// Counts the key presses, until Enter is pressed.
+//! struct Counter(usize);
+//! impl EventHandler for Counter {
+//! 	fn update(&mut self, event: Event) -> Flow {
+//! 		match event {
+//! 			Event::Key(k) if k.code == KeyCode::Enter => return Flow::Exit,
+//! 			Event::Key(_) => self.0 += 1,
+//! 			_ => (),
+//! 		}
+//! 		Flow::Continue
+//! 	}
+//! 	fn render(&mut self, out: &mut dyn Write) -> io::Result<()> {
+//! 		write!(out, "\r{}", self.0)
+//! 	}
+//! }
+//!
+//! let mut term = VirtualTerminal::new((5, 1));
+//! term.push_keys([KeyCode::Char('a'), KeyCode::Char('b'), KeyCode::Enter]);
+//! let mut counter = Counter(0);
+//! events::run(&mut counter, &mut term).unwrap();
+//! assert_eq!(term.screen().line(0), "2    ");
+//! ```
+
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{
+	Event as TermEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEvent,
+};
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::QueueableCommand;
+
+use crate::backend::Terminal;
+
+/// An event given to an [`EventHandler`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+	/// A key was pressed, repeated or released. `Ctrl+C` and `Ctrl+Z` are handled by the event
+	/// loop and never given.
+	Key(KeyEvent),
+	/// Something happened with the mouse.
+	Mouse(MouseEvent),
+	/// The terminal has the given size (columns, rows). It is the first event given, and is given
+	/// again each time the terminal is resized or the program is resumed after a suspension, the
+	/// screen having been cleared.
+	Resize(u16, u16),
+	/// The terminal gained focus.
+	FocusGained,
+	/// The terminal lost focus.
+	FocusLost,
+	/// Some text was pasted.
+	Paste(String),
+	/// The [tick rate](EventHandler::tick_rate) elapsed without any other event.
+	Tick,
+}
+
+/// Whether the event loop should go on after an event was handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+	/// Go on, waiting for the next event.
+	Continue,
+	/// Stop the event loop.
+	Exit,
+}
+
+/// Something run by the event loop, as a game: it is updated on each event and then rendered.
+pub trait EventHandler {
+	/// Handles an event.
+	fn update(&mut self, event: Event) -> Flow;
+	/// Displays the handler. The output is flushed by the event loop afterwards.
+	fn render(&mut self, out: &mut dyn Write) -> io::Result<()>;
+	/// How long to wait for an event before giving an [`Event::Tick`]. It is queried before waiting
+	/// for each event, and is `None` by default, meaning that no tick is ever given.
+	fn tick_rate(&self) -> Option<Duration> {
+		None
+	}
+}
+
+/// Runs the event loop on the given terminal until the handler asks to exit or `Ctrl+C` is
+/// pressed. See the [module-level documentation](self) for more details.
+pub fn run(handler: &mut dyn EventHandler, term: &mut dyn Terminal) -> io::Result<()> {
+	let (width, height) = term.size()?;
+	if handler.update(Event::Resize(width, height)) == Flow::Exit {
+		return Ok(());
+	}
+	let mut last_tick = Instant::now();
+	loop {
+		handler.render(term)?;
+		term.flush()?;
+
+		let event = match handler.tick_rate() {
+			Some(rate) if !term.poll(rate.saturating_sub(last_tick.elapsed()))? => {
+				last_tick = Instant::now();
+				Event::Tick
+			}
+			_ => match term.read()? {
+				TermEvent::Key(KeyEvent {
+					code: KeyCode::Char(c @ ('c' | 'z')),
+					kind: KeyEventKind::Press,
+					modifiers,
+					..
+				}) if modifiers.contains(KeyModifiers::CONTROL) => {
+					if c == 'c' {
+						return Ok(());
+					}
+					term.suspend()?;
+					let (width, height) = term.size()?;
+					term.queue(Clear(ClearType::All))?;
+					Event::Resize(width, height)
+				}
+				TermEvent::Key(e) => Event::Key(e),
+				TermEvent::Mouse(e) => Event::Mouse(e),
+				TermEvent::Resize(width, height) => {
+					term.queue(Clear(ClearType::All))?;
+					Event::Resize(width, height)
+				}
+				TermEvent::FocusGained => Event::FocusGained,
+				TermEvent::FocusLost => Event::FocusLost,
+				TermEvent::Paste(text) => Event::Paste(text),
+			},
+		};
+		if handler.update(event) == Flow::Exit {
+			return Ok(());
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::backend::VirtualTerminal;
+
+	/// Records all the events it is given, and exits on Esc.
+	#[derive(Default)]
+	struct Recorder {
+		events: Vec<Event>,
+		renders: usize,
+	}
+
+	impl EventHandler for Recorder {
+		fn update(&mut self, event: Event) -> Flow {
+			let exit = matches!(event, Event::Key(KeyEvent { code: KeyCode::Esc, .. }));
+			self.events.push(event);
+			if exit {
+				Flow::Exit
+			} else {
+				Flow::Continue
+			}
+		}
+		fn render(&mut self, _: &mut dyn Write) -> io::Result<()> {
+			self.renders += 1;
+			Ok(())
+		}
+		fn tick_rate(&self) -> Option<Duration> {
+			Some(Duration::from_secs(60))
+		}
+	}
+
+	#[test]
+	fn dispatch() {
+		let mut term = VirtualTerminal::new((10, 5));
+		term.push_timeout()
+			.push_event(TermEvent::Paste("hi".to_owned()))
+			.push_event(TermEvent::FocusLost)
+			.push_resize(20, 8)
+			.push_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+			.push_keys([KeyCode::Esc, KeyCode::Enter]);
+		let mut recorder = Recorder::default();
+		run(&mut recorder, &mut term).unwrap();
+		assert_eq!(
+			recorder.events,
+			[
+				Event::Resize(10, 5),
+				Event::Tick,
+				Event::Paste("hi".to_owned()),
+				Event::FocusLost,
+				Event::Resize(20, 8),
+				// Suspended and resumed
+				Event::Resize(20, 8),
+				Event::Key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)),
+			]
+		);
+		assert_eq!(recorder.renders, 6);
+		assert_eq!(term.suspensions(), 1);
+		// Enter is never read
+		assert_eq!(term.remaining_events(), 1);
+	}
+
+	#[test]
+	fn interrupt() {
+		let mut term = VirtualTerminal::new((10, 5));
+		term.push_key(KeyCode::Char('a'), KeyModifiers::NONE)
+			.push_key(KeyCode::Char('c'), KeyModifiers::CONTROL)
+			.push_keys([KeyCode::Esc]);
+		let mut recorder = Recorder::default();
+		run(&mut recorder, &mut term).unwrap();
+		assert_eq!(
+			recorder.events,
+			[
+				Event::Resize(10, 5),
+				Event::Key(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE))
+			]
+		);
+		assert_eq!(term.remaining_events(), 1);
+	}
+}
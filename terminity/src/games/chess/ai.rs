@@ -0,0 +1,390 @@
+//! A computer opponent: a negamax search with alpha-beta pruning over the legal moves, limited by
+//! a depth and a time budget.
+//!
+//! The search is deterministic for a given seed as long as it isn't cut short by its time
+//! budget: the seed only decides between moves that evaluate the same.
+//!
+//! ```
+//! use terminity::games::chess::ai::{Ai, Budget};
+//! use terminity::games::chess::rules::{History, Move};
+//!
+//! // Black can take the queen on h5 with the knight
+//! let mut history = History::default();
+//! for (from, to) in [((4, 1), (4, 3)), ((6, 7), (5, 5)), ((3, 0), (7, 4))] {
+//! 	history.play(Move::new(from, to)).unwrap();
+//! }
+//! let mut ai = Ai::new(Budget { depth: 2, time: None }, 42);
+//! assert_eq!(ai.best_move(&history), Some(Move::new((5, 5), (7, 4))));
+//! ```
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use super::rules::{Color, History, Move, Piece, Position};
+
+/// The strongest level of [`Budget::level`].
+pub const MAX_LEVEL: u8 = 5;
+
+/// The score of a checkmate, from which the number of half-moves to reach it is subtracted.
+const MATE: i32 = 1_000_000;
+
+/// How deep the search goes on with captures only, once the depth budget is exhausted.
+const QUIESCENCE_DEPTH: u32 = 4;
+
+/// How much the search can go on before picking a move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Budget {
+	/// The number of half-moves to look ahead, not counting the captures looked at to avoid
+	/// stopping in the middle of an exchange.
+	pub depth: u32,
+	/// How long to search at most. The best move of the deepest complete search is then played.
+	pub time: Option<Duration>,
+}
+
+impl Budget {
+	/// The budget of the given strength level, from 1 to [`MAX_LEVEL`]: the level is the depth,
+	/// with a second to think per level.
+	pub fn level(level: u8) -> Self {
+		let level = level.clamp(1, MAX_LEVEL);
+		Self { depth: level as u32, time: Some(Duration::from_secs(level as u64)) }
+	}
+}
+
+/// A computer player.
+#[derive(Debug, Clone)]
+pub struct Ai {
+	budget: Budget,
+	rng: StdRng,
+}
+
+/// A search running in the background, cancelled when dropped.
+#[derive(Debug)]
+pub struct Thinking {
+	result: Receiver<Move>,
+	stop: Arc<AtomicBool>,
+}
+
+impl Ai {
+	/// A computer player searching within the given budget, choosing between equal moves using
+	/// the given seed.
+	pub fn new(budget: Budget, seed: u64) -> Self {
+		Self { budget, rng: StdRng::seed_from_u64(seed) }
+	}
+
+	/// The search budget.
+	pub fn budget(&self) -> Budget {
+		self.budget
+	}
+
+	/// Searches the best move in the current position of the game, if the game isn't over.
+	pub fn best_move(&mut self, history: &History) -> Option<Move> {
+		self.search(history).run()
+	}
+
+	/// Searches the best move in the current position of the game on a background thread.
+	pub fn think(&mut self, history: &History) -> Thinking {
+		let search = self.search(history);
+		let stop = search.stop.clone();
+		let (sender, result) = mpsc::channel();
+		thread::spawn(move || {
+			if let Some(mv) = search.run() {
+				// The receiver is gone if the search is cancelled, nothing to do then
+				let _ = sender.send(mv);
+			}
+		});
+		Thinking { result, stop }
+	}
+
+	/// Prepares the search of the current position of the game.
+	fn search(&mut self, history: &History) -> Search {
+		// Only the positions since the last capture or pawn move can be repeated
+		let positions = history.positions();
+		let reversible = history.position().halfmove_clock() as usize + 1;
+		Search {
+			deadline: self.budget.time.map(|time| Instant::now() + time),
+			depth: self.budget.depth.max(1),
+			seed: self.rng.gen(),
+			path: positions[positions.len().saturating_sub(reversible)..].to_vec(),
+			stop: Arc::new(AtomicBool::new(false)),
+			aborted: false,
+		}
+	}
+}
+
+impl Thinking {
+	/// The move found, if the search is over.
+	pub fn poll(&self) -> Option<Move> {
+		self.result.try_recv().ok()
+	}
+
+	/// Waits for the search to be over and returns the move found, if any.
+	pub fn wait(&self) -> Option<Move> {
+		self.result.recv().ok()
+	}
+}
+
+impl Drop for Thinking {
+	fn drop(&mut self) {
+		self.stop.store(true, Ordering::Relaxed);
+	}
+}
+
+/// A search in progress.
+struct Search {
+	deadline: Option<Instant>,
+	depth: u32,
+	seed: u64,
+	/// The positions leading to the one being searched, the last one included, to detect
+	/// repetitions
+	path: Vec<Position>,
+	/// Set to cancel the search
+	stop: Arc<AtomicBool>,
+	/// Whether the search ran out of time or was cancelled
+	aborted: bool,
+}
+
+impl Search {
+	/// Searches with an increasing depth until the budget is exhausted, and returns the best move
+	/// of the deepest complete search.
+	fn run(mut self) -> Option<Move> {
+		let position = self.path.last()?.clone();
+		let mut moves = position.legal_moves();
+		moves.shuffle(&mut StdRng::seed_from_u64(self.seed));
+		let mut best = *moves.first()?;
+		for depth in 1..=self.depth {
+			// Searching the best move of the previous depth first helps pruning
+			moves.sort_by_key(|mv| (*mv != best, -order(&position, mv)));
+			let mut alpha = -MATE - 1;
+			let mut best_at_depth = None;
+			for mv in &moves {
+				let score = -self.child(&position, mv, depth - 1, -MATE - 1, -alpha, 1);
+				if self.aborted {
+					break;
+				}
+				if score > alpha {
+					alpha = score;
+					best_at_depth = Some(*mv);
+				}
+			}
+			if self.aborted {
+				// A partial search still tells if a better move than the previous best was found
+				if let Some(mv) = best_at_depth.filter(|_| depth > 1) {
+					best = mv;
+				}
+				break;
+			}
+			best = best_at_depth.unwrap_or(best);
+			// No need to search deeper once a forced mate is found
+			if alpha.abs() >= MATE - depth as i32 {
+				break;
+			}
+		}
+		Some(best)
+	}
+
+	/// Plays the move and searches the resulting position.
+	fn child(
+		&mut self,
+		position: &Position,
+		mv: &Move,
+		depth: u32,
+		alpha: i32,
+		beta: i32,
+		ply: i32,
+	) -> i32 {
+		self.path.push(position.after(mv));
+		let score = self.negamax(depth, alpha, beta, ply);
+		self.path.pop();
+		score
+	}
+
+	/// The score of the last position of the path for the player to play.
+	fn negamax(&mut self, depth: u32, mut alpha: i32, beta: i32, ply: i32) -> i32 {
+		if self.stop.load(Ordering::Relaxed) || self.deadline.is_some_and(|d| Instant::now() >= d) {
+			self.aborted = true;
+			return 0;
+		}
+		let (position, previous) = self.path.split_last().unwrap();
+		let position = position.clone();
+		let mut moves = position.legal_moves();
+		if moves.is_empty() {
+			return if position.in_check() { ply - MATE } else { 0 };
+		}
+		if position.halfmove_clock() >= 100
+			|| position.insufficient_material()
+			|| previous.iter().any(|p| p.repeats(&position))
+		{
+			return 0;
+		}
+		if depth == 0 {
+			return quiescence(&position, QUIESCENCE_DEPTH, alpha, beta);
+		}
+		moves.sort_by_key(|mv| -order(&position, mv));
+		for mv in &moves {
+			let score = -self.child(&position, mv, depth - 1, -beta, -alpha, ply + 1);
+			if self.aborted {
+				return 0;
+			}
+			if score >= beta {
+				return beta;
+			}
+			alpha = alpha.max(score);
+		}
+		alpha
+	}
+}
+
+/// The score of the position for the player to play, only looking at captures and
+/// promotions so that the evaluation isn't made in the middle of an exchange.
+fn quiescence(position: &Position, depth: u32, mut alpha: i32, beta: i32) -> i32 {
+	let standing = evaluate(position);
+	if depth == 0 || standing >= beta {
+		return standing.min(beta);
+	}
+	alpha = alpha.max(standing);
+	let mut moves = position.legal_moves();
+	moves.retain(|mv| order(position, mv) > 0);
+	moves.sort_by_key(|mv| -order(position, mv));
+	for mv in &moves {
+		let after = position.after(mv);
+		let score = -quiescence(&after, depth - 1, -beta, -alpha);
+		if score >= beta {
+			return beta;
+		}
+		alpha = alpha.max(score);
+	}
+	alpha
+}
+
+/// The value of a piece, in hundredths of pawns.
+fn value(piece: Piece) -> i32 {
+	match piece {
+		Piece::Pawn => 100,
+		Piece::Knight => 320,
+		Piece::Bishop => 330,
+		Piece::Rook => 500,
+		Piece::Queen => 900,
+		Piece::King => 0,
+	}
+}
+
+/// How promising a move looks: captures of valuable pieces by cheap ones and promotions first.
+/// Quiet moves are scored 0.
+fn order(position: &Position, mv: &Move) -> i32 {
+	let promotion = mv.promotion.map_or(0, value);
+	let capture = match (position[mv.from], position[mv.to]) {
+		(Some(piece), Some(taken)) if piece.1 != taken.1 => 10 * value(taken.0) - value(piece.0),
+		// En passant
+		(Some(piece), None) if piece.0 == Piece::Pawn && mv.from.0 != mv.to.0 => 9 * value(piece.0),
+		_ => 0,
+	};
+	promotion + capture
+}
+
+/// A static evaluation of the position for the player to play: the material, with bonuses for
+/// advanced pawns and centralized knights, bishops and queens.
+pub fn evaluate(position: &Position) -> i32 {
+	position
+		.pieces()
+		.map(|((x, y), tile)| {
+			// Distance to the center, from 0 to 6
+			let distance = (2 * x as i32 - 7).abs() / 2 + (2 * y as i32 - 7).abs() / 2;
+			let bonus = match tile.0 {
+				Piece::Pawn => {
+					let advance = match tile.1 {
+						Color::White => y as i32 - 1,
+						Color::Black => 6 - y as i32,
+					};
+					5 * advance + if (3..=4).contains(&x) { 10 } else { 0 }
+				}
+				Piece::Knight | Piece::Bishop => 20 - 5 * distance,
+				Piece::Queen => 6 - distance,
+				_ => 0,
+			};
+			let score = value(tile.0) + bonus;
+			if tile.1 == position.player() {
+				score
+			} else {
+				-score
+			}
+		})
+		.sum()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn history(fen: &str) -> History {
+		History::new(fen.parse().unwrap())
+	}
+
+	#[test]
+	fn mate_in_one() {
+		let mut ai = Ai::new(Budget { depth: 2, time: None }, 0);
+		let mate = ai.best_move(&history("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1"));
+		assert_eq!(mate, Some(Move::new((0, 0), (0, 7))));
+		// Black defends against it
+		let mut game = history("6k1/5ppp/8/8/8/8/5PPP/R5K1 b - - 0 1");
+		let defense = ai.best_move(&game).unwrap();
+		game.play(defense).unwrap();
+		let after = game.position().after(&Move::new((0, 0), (0, 7)));
+		assert!(!after.legal_moves().is_empty(), "{:?} doesn't defend", defense);
+	}
+
+	#[test]
+	fn mate_in_two() {
+		let mut ai = Ai::new(Budget { depth: 3, time: None }, 0);
+		let mut game = history("k7/8/2K5/8/8/8/8/7R w - - 0 1");
+		game.play(ai.best_move(&game).unwrap()).unwrap();
+		// Whatever black answers, white mates
+		for reply in game.position().legal_moves() {
+			let mut game = game.clone();
+			game.play(reply).unwrap();
+			game.play(ai.best_move(&game).unwrap()).unwrap();
+			assert!(game.outcome().is_some(), "No mate after {:?}", reply);
+		}
+	}
+
+	#[test]
+	fn seeded() {
+		let play = |seed| {
+			let mut ai = Ai::new(Budget { depth: 1, time: None }, seed);
+			let mut game = History::default();
+			for _ in 0..6 {
+				game.play(ai.best_move(&game).unwrap()).unwrap();
+			}
+			game.moves().to_vec()
+		};
+		assert_eq!(play(7), play(7));
+		assert!((0..10).any(|seed| play(seed) != play(7)));
+	}
+
+	#[test]
+	fn time_budget() {
+		let budget = Budget { depth: 20, time: Some(Duration::from_millis(200)) };
+		let game = History::default();
+		let start = Instant::now();
+		let mv = Ai::new(budget, 0).best_move(&game).unwrap();
+		assert!(start.elapsed() < Duration::from_secs(2));
+		assert!(game.position().is_legal(&mv));
+	}
+
+	#[test]
+	fn background() {
+		let mut ai = Ai::new(Budget { depth: 2, time: None }, 0);
+		let game = history("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1");
+		let thinking = ai.think(&game);
+		assert_eq!(thinking.wait(), Some(Move::new((0, 0), (0, 7))));
+		// Nothing to search once the game is over
+		let thinking = ai.think(&history("R5k1/5ppp/8/8/8/8/5PPP/6K1 b - - 1 1"));
+		assert_eq!(thinking.wait(), None);
+	}
+}
@@ -0,0 +1,187 @@
+//! Chess clocks: each player has some time for the whole game, that only runs during their turn,
+//! and may get some time back after each move depending on the time control.
+//!
+//! The clock is given the time of every action, so that it doesn't depend on when it is looked at.
+//!
+//! ```
+//! use std::time::{Duration, Instant};
+//! use terminity::games::chess::clock::{Clock, TimeControl};
+//! use terminity::games::chess::rules::Color;
+//!
+//! // Three minutes each, and two seconds more after each move
+//! let minutes = |m: u64| Duration::from_secs(60 * m);
+//! let control = TimeControl::Fischer { base: minutes(3), increment: Duration::from_secs(2) };
+//! let mut clock = Clock::new(control);
+//! let start = Instant::now();
+//! clock.start(Color::White, start);
+//! clock.press(start + Duration::from_secs(5));
+//! assert_eq!(clock.remaining(Color::White, start), minutes(3) - Duration::from_secs(3));
+//! assert_eq!(clock.running(), Some(Color::Black));
+//! ```
+
+use std::time::{Duration, Instant};
+
+use super::rules::Color;
+
+/// How much time the players have.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TimeControl {
+	/// The given time for the whole game.
+	SuddenDeath(Duration),
+	/// The base time for the whole game, and the increment added after each move.
+	Fischer { base: Duration, increment: Duration },
+	/// The base time for the whole game, and the delay: after each move, the time spent on it is
+	/// given back, up to the delay.
+	Bronstein { base: Duration, delay: Duration },
+}
+
+impl TimeControl {
+	/// The time each player starts with.
+	pub fn base(&self) -> Duration {
+		match *self {
+			Self::SuddenDeath(base) => base,
+			Self::Fischer { base, .. } | Self::Bronstein { base, .. } => base,
+		}
+	}
+
+	/// The time given back after a move that took the given time.
+	fn bonus(&self, spent: Duration) -> Duration {
+		match *self {
+			Self::SuddenDeath(_) => Duration::ZERO,
+			Self::Fischer { increment, .. } => increment,
+			Self::Bronstein { delay, .. } => spent.min(delay),
+		}
+	}
+}
+
+/// The clock of a game, running for at most one player at a time.
+#[derive(Debug, Clone)]
+pub struct Clock {
+	control: TimeControl,
+	/// The time left to each player (white then black) when their clock was last stopped
+	remaining: [Duration; 2],
+	/// The player whose clock is running, and since when
+	running: Option<(Color, Instant)>,
+}
+
+impl Clock {
+	/// A stopped clock giving both players the base time of the given control.
+	pub fn new(control: TimeControl) -> Self {
+		Self { control, remaining: [control.base(); 2], running: None }
+	}
+
+	/// The time control of the clock.
+	pub fn control(&self) -> TimeControl {
+		self.control
+	}
+
+	/// The player whose clock is running, if any.
+	pub fn running(&self) -> Option<Color> {
+		self.running.map(|(color, _)| color)
+	}
+
+	/// The time left to the given player at the given time.
+	pub fn remaining(&self, color: Color, now: Instant) -> Duration {
+		let remaining = self.remaining[color.index()];
+		match self.running {
+			Some((running, since)) if running == color => {
+				remaining.saturating_sub(now.saturating_duration_since(since))
+			}
+			_ => remaining,
+		}
+	}
+
+	/// The player whose time ran out at the given time, if any. Only the running clock can run
+	/// out.
+	pub fn flagged(&self, now: Instant) -> Option<Color> {
+		self.running().filter(|&color| self.remaining(color, now).is_zero())
+	}
+
+	/// Starts the clock of the given player from the given time, stopping the other one if it was
+	/// running (without giving it any time back).
+	pub fn start(&mut self, color: Color, now: Instant) {
+		self.stop(now);
+		self.running = Some((color, now));
+	}
+
+	/// Stops the clock at the given time.
+	pub fn stop(&mut self, now: Instant) {
+		if let Some((color, _)) = self.running {
+			self.remaining[color.index()] = self.remaining(color, now);
+			self.running = None;
+		}
+	}
+
+	/// The running player ended their turn at the given time: stops their clock, gives them the
+	/// time of the control back and starts the clock of the other player. Nothing is given back to
+	/// a player whose time ran out.
+	pub fn press(&mut self, now: Instant) {
+		let Some((color, since)) = self.running else {
+			return;
+		};
+		self.stop(now);
+		let remaining = &mut self.remaining[color.index()];
+		if !remaining.is_zero() {
+			*remaining += self.control.bonus(now.saturating_duration_since(since));
+		}
+		self.running = Some((color.opponent(), now));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn secs(secs: u64) -> Duration {
+		Duration::from_secs(secs)
+	}
+
+	#[test]
+	fn sudden_death() {
+		let mut clock = Clock::new(TimeControl::SuddenDeath(secs(60)));
+		let start = Instant::now();
+		assert_eq!(clock.remaining(Color::White, start + secs(10)), secs(60));
+		clock.start(Color::White, start);
+		assert_eq!(clock.remaining(Color::White, start + secs(10)), secs(50));
+		assert_eq!(clock.remaining(Color::Black, start + secs(10)), secs(60));
+		clock.press(start + secs(10));
+		assert_eq!(clock.remaining(Color::White, start + secs(100)), secs(50));
+		assert_eq!(clock.remaining(Color::Black, start + secs(30)), secs(40));
+		assert_eq!(clock.flagged(start + secs(69)), None);
+		assert_eq!(clock.flagged(start + secs(70)), Some(Color::Black));
+		assert_eq!(clock.remaining(Color::Black, start + secs(100)), Duration::ZERO);
+		clock.stop(start + secs(30));
+		assert_eq!(clock.running(), None);
+		assert_eq!(clock.flagged(start + secs(100)), None);
+		assert_eq!(clock.remaining(Color::Black, start + secs(100)), secs(40));
+	}
+
+	#[test]
+	fn fischer() {
+		let control = TimeControl::Fischer { base: secs(60), increment: secs(5) };
+		let mut clock = Clock::new(control);
+		let start = Instant::now();
+		clock.start(Color::White, start);
+		clock.press(start + secs(2));
+		assert_eq!(clock.remaining(Color::White, start + secs(2)), secs(63));
+		clock.press(start + secs(22));
+		assert_eq!(clock.remaining(Color::Black, start + secs(22)), secs(45));
+		// No increment once the time ran out
+		clock.press(start + secs(100));
+		assert_eq!(clock.remaining(Color::White, start + secs(100)), Duration::ZERO);
+	}
+
+	#[test]
+	fn bronstein() {
+		let control = TimeControl::Bronstein { base: secs(60), delay: secs(5) };
+		let mut clock = Clock::new(control);
+		let start = Instant::now();
+		clock.start(Color::White, start);
+		// A quick move costs nothing, a long one loses the time beyond the delay
+		clock.press(start + secs(2));
+		assert_eq!(clock.remaining(Color::White, start + secs(2)), secs(60));
+		assert_eq!(clock.remaining(Color::Black, start + secs(4)), secs(58));
+		clock.press(start + secs(22));
+		assert_eq!(clock.remaining(Color::Black, start + secs(22)), secs(45));
+	}
+}
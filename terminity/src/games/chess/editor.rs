@@ -0,0 +1,366 @@
+//! The position editor, where the players set up the position the game starts from by placing the
+//! pieces with the mouse or the keyboard.
+
+use std::io;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::style::ContentStyle;
+use crossterm::{cursor, QueueableCommand};
+use terminity_widgets::widgets::auto_padder::AutoPadder;
+use terminity_widgets::{MouseEventWidget, ResizableWisget, Widget};
+
+use super::rules::{CastlingSide, Color, History, Piece, Position, Tile};
+use super::theme::{PieceSet, Theme};
+use super::{highlight, Board, ChessScreen, MovesPanel};
+use crate::events::{Event, EventHandler, Flow};
+
+/// The pieces to choose from, in the order they are shown.
+const PALETTE: [Piece; 6] =
+	[Piece::King, Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight, Piece::Pawn];
+
+/// The editor of the starting position, run before the game.
+pub(super) struct Editor {
+	/// The board and the palette, wrapped in an auto-padder to center them on the screen
+	screen: AutoPadder<EditorScreen>,
+	/// The position chosen to start the game from, once it is valid
+	pub(super) done: Option<Position>,
+}
+
+/// The board being set up, and next to it the pieces to place.
+struct EditorScreen {
+	board: Board,
+	palette: Palette,
+}
+
+/// The pieces that can be placed and the keys of the editor.
+struct Palette {
+	/// The piece placed when clicking on the board, `None` erasing the tiles
+	hand: Option<Tile>,
+	/// Whether the kings can castle with their outermost rooks when on their back rank
+	castling: bool,
+	/// Why the position can't be played from, if starting it failed
+	error: Option<&'static str>,
+	/// How the pieces are drawn
+	pieces: PieceSet,
+	/// The lines taken by the palette
+	height: usize,
+	/// The style of the piece in hand
+	hand_style: ContentStyle,
+	/// The style of the error
+	error_style: ContentStyle,
+}
+
+impl Palette {
+	/// The column of the eraser, after the pieces
+	const ERASER_COLUMN: usize = 2 * PALETTE.len() + 2;
+	/// The line the error starts on
+	const ERROR_LINE: usize = 6;
+
+	/// The piece or the eraser shown at the given column and line, if any.
+	fn item_at(&self, column: usize, line: usize) -> Option<Option<Tile>> {
+		let color = match line {
+			0 if (Self::ERASER_COLUMN..Self::ERASER_COLUMN + 3).contains(&column) => {
+				return Some(None)
+			}
+			0 => Color::White,
+			1 => Color::Black,
+			_ => return None,
+		};
+		PALETTE.get(column / 2).map(|piece| Some(Tile(*piece, color)))
+	}
+}
+
+impl Widget for Palette {
+	fn size(&self) -> (usize, usize) {
+		(MovesPanel::WIDTH, self.height)
+	}
+	fn displ_line(&self, f: &mut std::fmt::Formatter<'_>, line: usize) -> std::fmt::Result {
+		let width = MovesPanel::WIDTH;
+		match line {
+			0 | 1 => {
+				let color = if line == 0 { Color::White } else { Color::Black };
+				for piece in PALETTE {
+					let item = self.pieces.symbol(piece, color).to_string() + " ";
+					if self.hand == Some(Tile(piece, color)) {
+						write!(f, "{}", self.hand_style.apply(item))?;
+					} else {
+						f.write_str(&item)?;
+					}
+				}
+				f.write_str("  ")?;
+				match line {
+					0 if self.hand.is_none() => write!(f, "{}", self.hand_style.apply("Del"))?,
+					0 => f.write_str("Del")?,
+					_ => f.write_str("   ")?,
+				}
+				write!(f, "{:1$}", "", width - Self::ERASER_COLUMN - 3)
+			}
+			2 => write!(f, "{:1$}", "Tab: change player", width),
+			3 => {
+				let castling = if self.castling { "on" } else { "off" };
+				write!(f, "{:1$}", format!("c: castling {}", castling), width)
+			}
+			4 => write!(f, "{:1$}", "Enter: start", width),
+			_ => {
+				// The error, cut into lines at the spaces
+				let mut lines: Vec<String> = vec![];
+				for word in self.error.into_iter().flat_map(|error| error.split(' ')) {
+					match lines.last_mut() {
+						Some(last) if last.len() + 1 + word.len() <= width => {
+							*last += " ";
+							*last += word;
+						}
+						_ => lines.push(word.to_owned()),
+					}
+				}
+				let text = line.checked_sub(Self::ERROR_LINE).and_then(|i| lines.get(i));
+				match text {
+					Some(text) => {
+						write!(f, "{}", self.error_style.apply(format!("{:1$}", text, width)))
+					}
+					None => write!(f, "{:1$}", "", width),
+				}
+			}
+		}
+	}
+}
+
+impl MouseEventWidget for Palette {
+	type MouseHandlingResult = bool;
+	fn mouse_event(&mut self, event: MouseEvent) -> Self::MouseHandlingResult {
+		let MouseEvent { kind, column, row, .. } = event;
+		match (kind, self.item_at(column as usize, row as usize)) {
+			(MouseEventKind::Down(MouseButton::Left), Some(item)) => {
+				self.hand = item;
+				true
+			}
+			_ => false,
+		}
+	}
+}
+
+impl Widget for EditorScreen {
+	fn size(&self) -> (usize, usize) {
+		ChessScreen::size_with(self.board.tile)
+	}
+	fn displ_line(&self, f: &mut std::fmt::Formatter<'_>, line: usize) -> std::fmt::Result {
+		self.board.displ_line(f, line)?;
+		write!(f, "{:1$}", "", ChessScreen::GAP)?;
+		self.palette.displ_line(f, line)
+	}
+}
+
+impl MouseEventWidget for EditorScreen {
+	type MouseHandlingResult = bool;
+	fn mouse_event(&mut self, mut event: MouseEvent) -> Self::MouseHandlingResult {
+		let board_width = self.board.size().0;
+		if (event.column as usize) >= board_width {
+			let Some(column) = (event.column as usize).checked_sub(board_width + ChessScreen::GAP)
+			else {
+				return false;
+			};
+			event.column = column as u16;
+			return self.palette.mouse_event(event);
+		}
+		let Some(pos) = self.board.tile_at(event.column, event.row) else {
+			return false;
+		};
+		self.board.cursor_pos = pos;
+		match event.kind {
+			// The pieces are placed while dragging too
+			MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Drag(MouseButton::Left) => {
+				self.place(self.palette.hand)
+			}
+			MouseEventKind::Down(MouseButton::Right) => self.place(None),
+			_ => (),
+		}
+		true
+	}
+}
+
+impl EditorScreen {
+	/// Puts the given piece (or nothing) on the cursor's tile.
+	fn place(&mut self, tile: Option<Tile>) {
+		self.board.editing.as_mut().unwrap().set(self.board.cursor_pos, tile);
+		self.palette.error = None;
+	}
+
+	/// The position set up, if it can be played from. The kings can castle with their outermost
+	/// rooks if castling is on, and no pawn can be taken en passant.
+	fn position(&self) -> Result<Position, &'static str> {
+		let mut position = self.board.editing.clone().unwrap();
+		position.set_en_passant(None);
+		for color in [Color::White, Color::Black] {
+			for side in [CastlingSide::King, CastlingSide::Queen] {
+				position.set_castling(color, side, None);
+			}
+		}
+		// Checking the kings before looking for their rooks
+		position.validate()?;
+		for color in [Color::White, Color::Black].into_iter().filter(|_| self.palette.castling) {
+			if position.king(color).is_some_and(|(_, y)| y == color.back_rank()) {
+				for side in [CastlingSide::King, CastlingSide::Queen] {
+					position.set_castling(color, side, position.outermost_rook(color, side));
+				}
+			}
+		}
+		position.validate()?;
+		Ok(position)
+	}
+}
+
+impl Editor {
+	/// An editor starting from the given position.
+	pub(super) fn new(position: Position) -> Self {
+		let castling = [Color::White, Color::Black].into_iter().any(|color| {
+			[CastlingSide::King, CastlingSide::Queen]
+				.into_iter()
+				.any(|side| position.castling(color, side).is_some())
+		});
+		let theme = Theme::default();
+		let mut board = Board::new(History::default());
+		board.cursor_pos = (4, position.player().back_rank());
+		board.editing = Some(position);
+		let palette = Palette {
+			hand: Some(Tile(Piece::Pawn, Color::White)),
+			castling,
+			error: None,
+			pieces: PieceSet::default(),
+			height: board.size().1,
+			hand_style: highlight(theme.cursor),
+			error_style: highlight(theme.checked),
+		};
+		Self { screen: AutoPadder(EditorScreen { board, palette }, (0, 0)), done: None }
+	}
+
+	/// Draws the editor with the given theme and piece set, on a large board or not.
+	pub(super) fn with_looks(mut self, theme: Theme, pieces: PieceSet, large: bool) -> Self {
+		let palette = &mut self.screen.palette;
+		palette.pieces = pieces;
+		palette.hand_style = highlight(theme.cursor);
+		palette.error_style = highlight(theme.checked);
+		self.screen.board.set_looks(theme, pieces, large);
+		self.screen.palette.height = self.screen.board.size().1;
+		self
+	}
+}
+
+impl EventHandler for Editor {
+	fn update(&mut self, event: Event) -> Flow {
+		use KeyCode::*;
+		let screen = &mut *self.screen;
+		match event {
+			Event::Key(KeyEvent { code, kind: KeyEventKind::Press, .. }) => match code {
+				Left => screen.board.move_cursor(-1, 0),
+				Right => screen.board.move_cursor(1, 0),
+				Up => screen.board.move_cursor(0, 1),
+				Down => screen.board.move_cursor(0, -1),
+				Char('f') => screen.board.rotated = !screen.board.rotated,
+				Char(' ') => screen.place(screen.palette.hand),
+				Delete | Backspace => screen.place(None),
+				Char('c') => screen.palette.castling = !screen.palette.castling,
+				Tab => {
+					let position = screen.board.editing.as_mut().unwrap();
+					position.set_player(position.player().opponent());
+				}
+				Enter => match screen.position() {
+					Ok(position) => {
+						self.done = Some(position);
+						return Flow::Exit;
+					}
+					Err(error) => screen.palette.error = Some(error),
+				},
+				// Typing a piece's letter places it, white in uppercase and black in lowercase
+				Char(c) => {
+					let Some(piece) = Piece::from_letter(c) else {
+						return Flow::Continue;
+					};
+					let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+					screen.palette.hand = Some(Tile(piece, color));
+					screen.place(screen.palette.hand);
+				}
+				_ => (),
+			},
+			Event::Mouse(e) => {
+				self.screen.mouse_event(e);
+			}
+			Event::Resize(w, h) => self.screen.resize((w as usize, h as usize)),
+			_ => (),
+		}
+		Flow::Continue
+	}
+
+	fn render(&mut self, out: &mut dyn io::Write) -> io::Result<()> {
+		out.queue(cursor::Hide)?.queue(cursor::MoveTo(0, 0))?;
+		write!(out, "{}", self.screen)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::backend::VirtualTerminal;
+	use crate::events;
+	use crate::games::chess::Chess;
+	use crate::games::Game;
+	use crossterm::event::KeyModifiers;
+	use crossterm::style::Color as TermColor;
+
+	#[test]
+	fn edit_and_start() {
+		let mut editor = Editor::new(Position::empty());
+		let mut term = VirtualTerminal::new((40, 10));
+		// The black king with the keyboard on e8, then the white king and a rook with the mouse
+		term.push_keys([KeyCode::Up; 7]);
+		term.push_keys([KeyCode::Char('k')]);
+		term.push_mouse(MouseEventKind::Down(MouseButton::Left), 20, 0);
+		term.push_mouse(MouseEventKind::Down(MouseButton::Left), 10, 7);
+		term.push_mouse(MouseEventKind::Down(MouseButton::Left), 24, 0);
+		term.push_mouse(MouseEventKind::Down(MouseButton::Left), 16, 7);
+		// A black rook checking the white king, with black to play
+		term.push_mouse(MouseEventKind::Down(MouseButton::Left), 24, 1);
+		term.push_mouse(MouseEventKind::Down(MouseButton::Left), 10, 4);
+		term.push_keys([KeyCode::Tab, KeyCode::Enter]);
+		term.push_key(KeyCode::Char('c'), KeyModifiers::CONTROL);
+		events::run(&mut editor, &mut term).unwrap();
+		assert_eq!(editor.done, None);
+		let screen = term.screen();
+		let text = |y, columns: std::ops::Range<u16>| {
+			let text: String = columns.map(|x| screen.cell(x, y).content.as_str()).collect();
+			text.trim_end().to_owned()
+		};
+		assert_eq!(text(0, 0..40), "8         ♔         ♚ ♛ ♜ ♝ ♞ ♟   Del");
+		assert_eq!(text(9, 0..18).trim(), "Black to play");
+		let error: Vec<_> = (6..10).map(|y| text(y, 20..40)).collect();
+		assert_eq!(error, ["The player that just", "played can't be in", "check", ""]);
+		assert_eq!(screen.cell(20, 6).style.background_color, Some(TermColor::DarkRed));
+
+		// Removing the rook with a right click, white can castle with the other one
+		let mut term = VirtualTerminal::new((40, 10));
+		term.push_mouse(MouseEventKind::Down(MouseButton::Right), 10, 4);
+		term.push_mouse(MouseEventKind::Moved, 2, 7);
+		term.push_keys([KeyCode::Tab, KeyCode::Char('R'), KeyCode::Char('c'), KeyCode::Enter]);
+		events::run(&mut editor, &mut term).unwrap();
+		assert_eq!(term.screen().cell(20, 6).style.background_color, None);
+		let position = editor.done.unwrap();
+		assert_eq!(position.to_fen(), "4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1");
+
+		// The castling rights only go to the kings on their back rank
+		let mut editor = Editor::new("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1".parse().unwrap());
+		editor.screen.board.cursor_pos = (4, 0);
+		editor.update(Event::Key(KeyEvent::new(KeyCode::Delete, KeyModifiers::NONE)));
+		editor.screen.board.cursor_pos = (4, 1);
+		editor.update(Event::Key(KeyEvent::new(KeyCode::Char('K'), KeyModifiers::SHIFT)));
+		assert_eq!(editor.screen.position().unwrap().to_fen(), "4k3/8/8/8/8/8/4K3/R6R w - - 0 1");
+		editor.update(Event::Key(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE)));
+		assert_eq!(editor.screen.position(), Err("Each player must have exactly one king"));
+
+		// The game is then played from the position set up
+		let mut term = VirtualTerminal::new((40, 10));
+		term.push_keys([KeyCode::Enter]);
+		term.push_key(KeyCode::Char('c'), KeyModifiers::CONTROL);
+		Game::run(&Chess::default().edit_position(), &mut term).unwrap();
+		assert_eq!(term.remaining_events(), 0);
+		assert!(term.screen().line(0).ends_with(" ♔ Black            "));
+	}
+}
@@ -0,0 +1,2013 @@
+//! A chess game, following all the rules: castling, en passant, promotion, and the game ends on
+//! checkmate, stalemate, fifty moves without capture nor pawn move, threefold repetition or
+//! insufficient material.
+//!
+//! Lemme know if the chessboard doesn't display as expected on your console and what
+//! is your configuration. It isn't intended for IDE consoles, but might work on them.
+
+#![allow(missing_docs)]
+
+use std::fmt::Write;
+use std::ops::{Index, RangeInclusive};
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, Instant};
+use std::{fs, io};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
+//use crossterm::{Style, Color as TermColor};
+use crossterm::style::{Color as TermColor, ContentStyle};
+use crossterm::{cursor, QueueableCommand};
+use terminity_widgets::render::{Buffer, Renderer};
+use terminity_widgets::widgets::auto_padder::AutoPadder;
+use terminity_widgets::{MouseEventWidget, ResizableWisget, Widget, WidgetDisplay};
+
+use crate::backend::Terminal;
+use crate::events::{self, Event, EventHandler, Flow};
+use crate::games::Game;
+
+pub mod ai;
+pub mod clock;
+mod editor;
+pub mod notation;
+pub mod rules;
+pub mod theme;
+pub mod uci;
+
+use ai::{Ai, Budget, Thinking};
+use clock::{Clock, TimeControl};
+use editor::Editor;
+use rules::{
+	Color, DrawReason, History, IllegalMove, Move, Outcome, Piece, Pos, Position, Tile, PROMOTIONS,
+};
+use theme::{PieceSet, Theme};
+use uci::{Engine, Limit, Score};
+
+/// Chess, played from the standard starting position unless told otherwise.
+#[derive(Default)]
+pub struct Chess {
+	/// The game to resume
+	history: History,
+	/// Where to save the game as PGN when exiting, if anywhere
+	save_to: Option<PathBuf>,
+	/// The color played by the computer and who it is, if playing against it
+	computer: Option<(Color, Opponent)>,
+	/// The program of the UCI engine analysing the game, if any
+	analyst: Option<PathBuf>,
+	/// The time control of the game, if played with a clock
+	clock: Option<TimeControl>,
+	/// The side the board is seen from, if not the default one: white's side, or the side of the
+	/// player against the computer
+	perspective: Option<Perspective>,
+	/// The colours of the board
+	theme: Theme,
+	/// How the pieces are drawn
+	pieces: PieceSet,
+	/// Whether the tiles are drawn larger
+	large: bool,
+	/// Whether the players set up the starting position before playing
+	edit: bool,
+}
+
+/// Who plays for the computer.
+#[derive(Debug, Clone)]
+enum Opponent {
+	/// The built-in AI, with its budget and seed
+	Builtin(Budget, u64),
+	/// A UCI engine, with its program and how long it searches
+	Engine(PathBuf, Limit),
+}
+
+/// The side the board is seen from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Perspective {
+	/// From the side of the given color, unless flipped by hand.
+	Fixed(Color),
+	/// From the side of the player to play, the board being flipped after each move.
+	Auto,
+}
+
+impl Chess {
+	/// Resumes the given game, which may start from any position.
+	pub fn new(history: History) -> Self {
+		Self {
+			history,
+			save_to: None,
+			computer: None,
+			analyst: None,
+			clock: None,
+			perspective: None,
+			theme: Theme::default(),
+			pieces: PieceSet::default(),
+			large: false,
+			edit: false,
+		}
+	}
+	/// Saves the game as PGN in the given file when exiting.
+	pub fn save_to(mut self, path: impl Into<PathBuf>) -> Self {
+		self.save_to = Some(path.into());
+		self
+	}
+	/// Plays against the computer, that plays the given color within the given budget. The seed
+	/// decides between the moves the computer finds equally good.
+	pub fn against_computer(mut self, color: Color, budget: Budget, seed: u64) -> Self {
+		self.computer = Some((color, Opponent::Builtin(budget, seed)));
+		self
+	}
+	/// Plays against the UCI engine of the given program, that plays the given color and stops
+	/// searching at the given limit, which shouldn't be [`Limit::Infinite`].
+	pub fn against_engine(
+		mut self,
+		color: Color,
+		program: impl Into<PathBuf>,
+		limit: Limit,
+	) -> Self {
+		self.computer = Some((color, Opponent::Engine(program.into(), limit)));
+		self
+	}
+	/// Analyses the game with the UCI engine of the given program, showing its evaluation and the
+	/// best move it found above the moves played.
+	pub fn analysed_by(mut self, program: impl Into<PathBuf>) -> Self {
+		self.analyst = Some(program.into());
+		self
+	}
+	/// Plays with a clock following the given time control. A player whose time runs out loses,
+	/// and moves can't be taken back.
+	pub fn with_clock(mut self, control: TimeControl) -> Self {
+		self.clock = Some(control);
+		self
+	}
+	/// Shows the board from the given side. It can still be flipped by hand during the game.
+	pub fn seen_from(mut self, perspective: Perspective) -> Self {
+		self.perspective = Some(perspective);
+		self
+	}
+	/// Draws the board with the colours of the given theme.
+	pub fn with_theme(mut self, theme: Theme) -> Self {
+		self.theme = theme;
+		self
+	}
+	/// Draws the pieces with the given set.
+	pub fn with_pieces(mut self, pieces: PieceSet) -> Self {
+		self.pieces = pieces;
+		self
+	}
+	/// Draws each tile on two lines instead of one, for the terminals with large enough screens.
+	pub fn large_board(mut self) -> Self {
+		self.large = true;
+		self
+	}
+	/// Lets the players set up the position to start from in an editor before playing, from the
+	/// current position of the game. The game is then played from the position set up.
+	pub fn edit_position(mut self) -> Self {
+		self.edit = true;
+		self
+	}
+}
+
+impl Game for Chess {
+	fn name(&self) -> &str {
+		"Chess"
+	}
+	fn description(&self) -> &str {
+		"The classic chess, against a friend on the same keyboard or against the computer."
+	}
+	fn players(&self) -> RangeInclusive<u8> {
+		2..=2
+	}
+	fn min_size(&self) -> (u16, u16) {
+		let tile = if self.large { Board::LARGE_TILE } else { Board::TILE };
+		let (width, height) = ChessScreen::size_with(tile);
+		(width as u16, height as u16)
+	}
+	fn run(&self, term: &mut dyn Terminal) -> io::Result<()> {
+		let mut history = self.history.clone();
+		if self.edit {
+			let mut editor = Editor::new(history.position().clone()).with_looks(
+				self.theme.clone(),
+				self.pieces,
+				self.large,
+			);
+			events::run(&mut editor, term)?;
+			// The players left without starting a game
+			let Some(position) = editor.done else {
+				return Ok(());
+			};
+			history = History::new(position);
+		}
+		let start = history.start().clone();
+		let mut state =
+			GameState::new(history).with_looks(self.theme.clone(), self.pieces, self.large);
+		match &self.computer {
+			None => (),
+			Some((color, Opponent::Builtin(budget, seed))) => {
+				let ai = Ai::new(*budget, *seed);
+				state = state.with_computer(*color, ComputerPlayer::Builtin(ai, None));
+			}
+			Some((color, Opponent::Engine(program, limit))) => {
+				let engine = Engine::start(&mut Command::new(program), &start)?;
+				state = state.with_computer(*color, ComputerPlayer::Engine(engine, *limit));
+			}
+		}
+		if let Some(program) = &self.analyst {
+			state = state.with_analyst(Engine::start(&mut Command::new(program), &start)?);
+		}
+		if let Some(control) = self.clock {
+			state = state.with_clock(Clock::new(control));
+		}
+		let human = self.computer.as_ref().map_or(Color::White, |(color, _)| color.opponent());
+		state = state.seen_from(self.perspective.unwrap_or(Perspective::Fixed(human)));
+		events::run(&mut state, term)?;
+		if let Some(path) = &self.save_to {
+			fs::write(path, state.screen.board.history.to_pgn())?;
+		}
+		state.error.map_or(Ok(()), Err)
+	}
+}
+
+/// The state of a running game.
+struct GameState {
+	/// The board and the moves played, wrapped in an auto-padder to center them on the screen
+	screen: AutoPadder<ChessScreen>,
+	/// The computer opponent, if any
+	computer: Option<Computer>,
+	/// The engine analysing the game, if any
+	analyst: Option<Engine>,
+	/// The position the analyst was last asked to analyse
+	analysed: Option<Position>,
+	/// The clock of the game, if played with one
+	clock: Option<Clock>,
+	/// The search of the move to suggest to the player, and the position it is searched in
+	hinting: Option<(Position, Thinking)>,
+	/// When the cursor last blinked, or stopped blinking
+	blink: Instant,
+	/// The error that made the game stop, if any
+	error: Option<io::Error>,
+	renderer: Renderer,
+}
+
+/// A computer playing one of the colors.
+struct Computer {
+	color: Color,
+	player: ComputerPlayer,
+}
+
+/// What plays for the computer.
+enum ComputerPlayer {
+	/// The built-in AI, and its search of the next move while it is its turn
+	Builtin(Ai, Option<Thinking>),
+	/// A UCI engine, and how long it searches
+	Engine(Engine, Limit),
+}
+
+impl GameState {
+	/// How long to wait at most for the computer's move when blocking.
+	const WAIT_TIMEOUT: Duration = Duration::from_secs(60);
+	/// How much the built-in AI searches the move to suggest to the player.
+	const HINT_BUDGET: Budget = Budget { depth: 3, time: Some(Duration::from_secs(1)) };
+
+	fn new(history: History) -> Self {
+		Self {
+			screen: AutoPadder(ChessScreen::new(history), (0, 0)),
+			computer: None,
+			analyst: None,
+			analysed: None,
+			clock: None,
+			hinting: None,
+			blink: Instant::now(),
+			error: None,
+			renderer: Renderer::new(),
+		}
+	}
+
+	/// Draws the game with the given theme and piece set, on a large board or not.
+	fn with_looks(mut self, theme: Theme, pieces: PieceSet, large: bool) -> Self {
+		self.screen.set_looks(theme, pieces, large);
+		self
+	}
+
+	/// Lets the computer play the given color.
+	fn with_computer(mut self, color: Color, player: ComputerPlayer) -> Self {
+		self.screen.board.computer = Some(color);
+		self.computer = Some(Computer { color, player });
+		self
+	}
+
+	/// Lets the given engine analyse the game.
+	fn with_analyst(mut self, engine: Engine) -> Self {
+		self.screen.panel.evaluation = Some(Evaluation::default());
+		self.analyst = Some(engine);
+		self
+	}
+
+	/// Shows the board from the given side.
+	fn seen_from(mut self, perspective: Perspective) -> Self {
+		self.screen.board.perspective = perspective;
+		self.screen.board.follow_perspective();
+		self.update_clock(Instant::now());
+		self
+	}
+
+	/// Plays with the given clock, started for the player to play.
+	fn with_clock(mut self, clock: Clock) -> Self {
+		self.clock = Some(clock);
+		self.update_clock(Instant::now());
+		self
+	}
+
+	/// Runs the clock of the player to play at the given time, and ends the game if their time
+	/// ran out. Shows who is to play in any case.
+	fn update_clock(&mut self, now: Instant) {
+		if let Some(clock) = &mut self.clock {
+			let board = &mut self.screen.board;
+			let player = board.history.position().player();
+			if board.history.outcome().is_none() && clock.running() != Some(player) {
+				match clock.running() {
+					Some(_) => clock.press(now),
+					None => clock.start(player, now),
+				}
+			}
+			if let Some(color) = clock.flagged(now) {
+				board.history.time_out(color);
+				board.selected = None;
+				board.promotion = None;
+			}
+			if board.history.outcome().is_some() {
+				clock.stop(now);
+			}
+		}
+		self.screen.update_clocks(self.clock.as_ref(), now);
+	}
+
+	/// How long the cursor stays in the same style when blinking.
+	fn blink_period(&self) -> Duration {
+		// The cursor blinks faster when a piece is selected
+		Duration::from_millis(if self.screen.board.selected.is_none() { 400 } else { 100 })
+	}
+
+	/// Starts the search of the computer's move if it is its turn, and plays the move once it is
+	/// found. If `wait` is set, blocks until then.
+	fn computer_turn(&mut self, wait: bool) -> io::Result<()> {
+		let Some(computer) = &mut self.computer else {
+			return Ok(());
+		};
+		let board = &mut self.screen.board;
+		let history = &board.history;
+		if history.outcome().is_some() || history.position().player() != computer.color {
+			return Ok(());
+		}
+		let found = match &mut computer.player {
+			ComputerPlayer::Builtin(ai, thinking) => {
+				let search = thinking.get_or_insert_with(|| ai.think(history));
+				let found = if wait { search.wait() } else { search.poll() };
+				if found.is_some() {
+					*thinking = None;
+				}
+				found
+			}
+			ComputerPlayer::Engine(engine, limit) => {
+				if !engine.searching() {
+					engine.go(history, *limit)?;
+				}
+				if wait {
+					engine.wait(Self::WAIT_TIMEOUT)?
+				} else {
+					engine.poll()?
+				}
+			}
+		};
+		if let Some(mv) = found {
+			board.play_move(mv).map_err(|_| {
+				io::Error::new(io::ErrorKind::InvalidData, "Illegal move from the computer")
+			})?;
+			board.checked_by = board.history.position().checkers();
+		}
+		Ok(())
+	}
+
+	/// Whether the computer is searching its next move.
+	fn thinking(&self) -> bool {
+		match self.computer.as_ref().map(|c| &c.player) {
+			Some(ComputerPlayer::Builtin(_, thinking)) => thinking.is_some(),
+			Some(ComputerPlayer::Engine(engine, _)) => engine.searching(),
+			None => false,
+		}
+	}
+
+	/// Makes the analyst analyse the current position if it changed, and shows what it found.
+	fn analyse(&mut self) -> io::Result<()> {
+		let Some(analyst) = &mut self.analyst else {
+			return Ok(());
+		};
+		let screen = &mut *self.screen;
+		let history = &screen.board.history;
+		let position = history.position();
+		if self.analysed.as_ref() != Some(position) {
+			if history.outcome().is_none() {
+				analyst.go(history, Limit::Infinite)?;
+			} else if analyst.searching() {
+				analyst.stop()?;
+			}
+			self.analysed = Some(position.clone());
+		}
+		// The best move only comes once the search is stopped, nothing to do with it
+		analyst.poll()?;
+		screen.panel.evaluation = Some(if history.outcome().is_none() {
+			let info = analyst.info();
+			Evaluation {
+				score: info.score.map(|score| score.for_white(position.player())),
+				best: info.pv.first().map(|mv| position.san(mv)),
+			}
+		} else {
+			Evaluation::default()
+		});
+		Ok(())
+	}
+
+	/// Suggests a move to the player to play: the best move found by the analyst if the game is
+	/// analysed, or else searches one with the built-in AI.
+	fn ask_hint(&mut self) {
+		let computer = self.computer.as_ref().map(|c| c.color);
+		let board = &mut self.screen.board;
+		let history = &board.history;
+		let position = history.position();
+		if board.preview.is_some()
+			|| history.outcome().is_some()
+			|| computer == Some(position.player())
+		{
+			return;
+		}
+		let analysed = self.analyst.as_ref().filter(|_| self.analysed.as_ref() == Some(position));
+		if let Some(best) = analysed.and_then(|analyst| analyst.info().pv.first()) {
+			board.hint = Some(*best);
+		} else if board.hint.is_none() {
+			let thinking = Ai::new(Self::HINT_BUDGET, 0).think(history);
+			self.hinting = Some((position.clone(), thinking));
+		}
+	}
+
+	/// Shows the suggested move once it is found, unless a move was played since it was asked
+	/// for. If `wait` is set, blocks until then.
+	fn poll_hint(&mut self, wait: bool) {
+		let Some((position, thinking)) = &self.hinting else {
+			return;
+		};
+		let board = &mut self.screen.board;
+		if position == board.history.position() {
+			let found = if wait { thinking.wait() } else { thinking.poll() };
+			if found.is_none() && !wait {
+				return;
+			}
+			board.hint = found;
+		}
+		self.hinting = None;
+	}
+
+	/// Stops the search of the computer's move, that will be searched again from scratch.
+	fn cancel_thinking(&mut self) -> io::Result<()> {
+		match self.computer.as_mut().map(|c| &mut c.player) {
+			Some(ComputerPlayer::Builtin(_, thinking)) => *thinking = None,
+			Some(ComputerPlayer::Engine(engine, _)) if engine.searching() => {
+				engine.stop()?;
+				engine.wait(Self::WAIT_TIMEOUT)?;
+			}
+			_ => (),
+		}
+		Ok(())
+	}
+
+	/// Takes back the last move, and the computer's answer to it when playing against it.
+	fn undo(&mut self) -> io::Result<()> {
+		// No take back against the clock
+		if self.clock.is_some() {
+			return Ok(());
+		}
+		self.cancel_thinking()?;
+		let computer = self.computer.as_ref().map(|c| c.color);
+		let board = &mut self.screen.board;
+		if board.undo() {
+			while computer == Some(board.history.position().player()) && board.undo() {}
+		}
+		Ok(())
+	}
+
+	/// Replays the last move taken back, and the computer's answer to it when playing against it.
+	fn redo(&mut self) -> io::Result<()> {
+		if self.clock.is_some() {
+			return Ok(());
+		}
+		self.cancel_thinking()?;
+		let computer = self.computer.as_ref().map(|c| c.color);
+		let board = &mut self.screen.board;
+		if board.redo() {
+			while computer == Some(board.history.position().player()) && board.redo() {}
+		}
+		Ok(())
+	}
+
+	fn handle_event(&mut self, event: Event) -> io::Result<Flow> {
+		use KeyCode::*;
+		let thinking = self.thinking();
+		let blink_period = self.blink_period();
+		let board = &mut self.screen.board;
+		match event {
+			// No event for a while: blinking, unless waiting for the computer
+			Event::Tick if thinking => return Ok(Flow::Continue),
+			Event::Tick => {
+				if self.blink.elapsed() >= blink_period {
+					board.cursor_style_alt = !board.cursor_style_alt;
+					self.blink = Instant::now();
+				}
+				return Ok(Flow::Continue);
+			}
+			Event::Mouse(e) => {
+				// Using the terminity_widget mouse api.
+				// The wrapping auto-padder filters out the events out of the screen
+				// and changes the column and line values to correspond to the position
+				// on the screen, that gives them to the board or the moves.
+				if self.screen.mouse_event(e) != Some(true) {
+					return Ok(Flow::Continue);
+				}
+			}
+			// Choosing the piece to promote to
+			Event::Key(KeyEvent { code, kind: KeyEventKind::Press, .. })
+				if board.promotion.is_some() =>
+			{
+				let promotion = board.promotion.as_mut().unwrap();
+				match code {
+					Left => promotion.choice = promotion.choice.saturating_sub(1),
+					Right => promotion.choice = (promotion.choice + 1).min(PROMOTIONS.len() - 1),
+					Enter => board.promote(),
+					Esc => board.promotion = None,
+					Char(c) => {
+						let Some(choice) = "qrbn".find(c.to_ascii_lowercase()) else {
+							return Ok(Flow::Continue);
+						};
+						promotion.choice = choice;
+						board.promote();
+					}
+					_ => return Ok(Flow::Continue),
+				}
+			}
+			Event::Key(KeyEvent { code, kind: KeyEventKind::Press, .. }) => match code {
+				Enter => {
+					if board.selected.is_none() {
+						board.select();
+					} else {
+						board.play();
+					}
+				}
+				Left => board.move_cursor(-1, 0),
+				Right => board.move_cursor(1, 0),
+				Up => board.move_cursor(0, 1),
+				Down => board.move_cursor(0, -1),
+				Char('f') => board.rotated = !board.rotated,
+				// Back to the game being played
+				Esc => {
+					board.selected = None;
+					board.preview = None;
+				}
+				Char('u') | Backspace => self.undo()?,
+				Char('r') => self.redo()?,
+				Char('h') => self.ask_hint(),
+				_ => return Ok(Flow::Continue),
+			},
+			// Use the auto-padder to handle resize
+			Event::Resize(w, h) => self.screen.resize((w as usize, h as usize)),
+			_ => return Ok(Flow::Continue),
+		}
+		// An event was handled: reset blinking
+		self.screen.board.cursor_style_alt = false;
+		self.blink = Instant::now();
+		Ok(Flow::Continue)
+	}
+}
+
+impl EventHandler for GameState {
+	fn update(&mut self, event: Event) -> Flow {
+		if let Event::Resize(..) = event {
+			self.renderer.invalidate();
+		}
+		// The time may have run out before the event
+		self.update_clock(Instant::now());
+		let flow = self.handle_event(event).and_then(|flow| {
+			self.computer_turn(false)?;
+			self.analyse()?;
+			self.poll_hint(false);
+			Ok(flow)
+		});
+		self.update_clock(Instant::now());
+		self.screen.update_panel();
+		flow.unwrap_or_else(|e| {
+			self.error = Some(e);
+			Flow::Exit
+		})
+	}
+
+	fn render(&mut self, out: &mut dyn io::Write) -> io::Result<()> {
+		out.queue(cursor::Hide)?;
+		self.renderer.render(out, Buffer::from_widget(&self.screen))
+	}
+
+	fn tick_rate(&self) -> Option<Duration> {
+		// Checking often for the computer's move, and showing the tenths of seconds of the clocks
+		Some(if self.thinking() || self.hinting.is_some() {
+			Duration::from_millis(50)
+		} else if self.clock.as_ref().is_some_and(|c| c.running().is_some()) {
+			Duration::from_millis(100)
+		} else {
+			self.blink_period()
+		})
+	}
+}
+
+/// The style of a text highlighted with the given background colour.
+fn highlight(background: Option<TermColor>) -> ContentStyle {
+	ContentStyle {
+		foreground_color: Some(TermColor::White),
+		background_color: background,
+		..Default::default()
+	}
+}
+
+/// A pawn move waiting for the player to choose what the pawn is promoted to
+#[derive(Debug, Clone, Copy)]
+struct Promotion {
+	from: Pos,
+	to: Pos,
+	/// The index of the chosen piece in `PROMOTIONS`
+	choice: usize,
+}
+
+#[derive(WidgetDisplay)]
+/// The chess board and its metadata (mainly for display purpose)
+struct Board {
+	/// The game being played
+	history: History,
+	/// The colours of the board
+	theme: Theme,
+	/// How the pieces are drawn
+	pieces: PieceSet,
+	/// The width and height of a tile
+	tile: (usize, usize),
+	/// Whether the board is seen from black's side: rank 1 on top and the H file on the left
+	rotated: bool,
+	/// The side the board is seen from
+	perspective: Perspective,
+	/// Current cursor position
+	cursor_pos: Pos,
+	/// The position of the selected piece to move
+	selected: Option<Pos>,
+	/// The move suggested to the player, if they asked for one
+	hint: Option<Move>,
+	/// Determinates if the cursor is in alternative style (i.e. blinking) or not
+	cursor_style_alt: bool,
+	/// List of all pieces that are checking the king (or will check if requested move was made)
+	checked_by: Vec<Pos>,
+	/// Positions of the invalid move that was tried to be made
+	invalid: Option<(Pos, Pos)>,
+	/// The promotion being chosen, if any
+	promotion: Option<Promotion>,
+	/// The color played by the computer, that can't be moved by hand
+	computer: Option<Color>,
+	/// The moves taken back, the last one first
+	undone: Vec<Move>,
+	/// The number of moves of the past position shown instead of the current one, if any
+	preview: Option<usize>,
+	/// The position being set up in the editor, shown instead of the game
+	editing: Option<Position>,
+}
+
+/// What an engine thinks of a position, shown above the moves played.
+#[derive(Debug, Clone, Default)]
+struct Evaluation {
+	/// The evaluation of the position for white
+	score: Option<Score>,
+	/// The best move found, in SAN
+	best: Option<String>,
+}
+
+impl Evaluation {
+	/// The lines taken by the evaluation, a blank one included
+	const HEIGHT: usize = 3;
+
+	/// Displays a line of the evaluation: the score and the best move, then a bar showing white's
+	/// advantage by how much of it is white.
+	fn displ_line(&self, f: &mut std::fmt::Formatter<'_>, line: usize) -> std::fmt::Result {
+		let width = MovesPanel::WIDTH;
+		match line {
+			0 => {
+				let score = self.score.map(|s| s.to_string()).unwrap_or_default();
+				let best = self.best.as_ref().map(|mv| format!("best {}", mv)).unwrap_or_default();
+				write!(f, "{:<6} {:<2$}", score, best, width - 7)
+			}
+			1 => {
+				let white = match self.score {
+					None => width / 2,
+					Some(Score::Mate(moves)) if moves > 0 => width,
+					Some(Score::Mate(_)) => 0,
+					// Each tile of the bar is half a pawn
+					Some(Score::Centipawns(cp)) => (width as i32 / 2 + cp.clamp(-500, 500) / 50)
+						.clamp(0, width as i32) as usize,
+				};
+				let bar =
+					|color| ContentStyle { background_color: Some(color), ..Default::default() };
+				write!(f, "{}", bar(TermColor::White).apply(" ".repeat(white)))?;
+				write!(f, "{}", bar(TermColor::Black).apply(" ".repeat(width - white)))
+			}
+			_ => write!(f, "{:1$}", "", width),
+		}
+	}
+}
+
+impl Board {
+	/// The size of a tile, as wide as two characters to look square
+	const TILE: (usize, usize) = (2, 1);
+	/// The size of a tile on a large board
+	const LARGE_TILE: (usize, usize) = (4, 2);
+	/// The width of the rank numbers on the left of the tiles
+	const RANK_WIDTH: usize = 2;
+	/// The column of the first piece to choose from when promoting
+	const PROMOTION_COLUMN: usize = 9;
+
+	/// The size of the board with its coordinates and status line, with tiles of the given size
+	fn size_with((width, height): (usize, usize)) -> (usize, usize) {
+		(Self::RANK_WIDTH + 8 * width, 8 * height + 2)
+	}
+	/// The line displaying the state of the game under the board
+	fn status_line(&self) -> usize {
+		self.size().1 - 1
+	}
+	/// Draws the board with the given theme and piece set, with large tiles or not
+	fn set_looks(&mut self, theme: Theme, pieces: PieceSet, large: bool) {
+		self.tile = if large { Self::LARGE_TILE } else { Self::TILE };
+		self.theme = theme;
+		self.pieces = pieces;
+	}
+
+	/// Mark the cursor's position as selected (selects the piece to move)
+	fn select(&mut self) {
+		let player = self.history.position().player();
+		if self.history.outcome().is_none()
+			&& self.preview.is_none()
+			&& self.computer != Some(player)
+			&& Some(player) == self[self.cursor_pos].map(|t| t.1)
+		{
+			self.selected = Some(self.cursor_pos);
+		}
+	}
+	/// Tries to move the piece at the position of self.selected to the current cursor's position
+	fn play(&mut self) {
+		// Reset the checking pieces list (will be populated later)
+		self.checked_by = Vec::with_capacity(5);
+		self.invalid = None;
+		let to = self.cursor_pos;
+
+		// Whether the move was sucessful or not, we want to stop trying to move.
+		if let Some(from) = self.selected.take() {
+			// No move was actually asked, do as if nothing happened
+			if from != to {
+				let position = self.history.position();
+				match self.moves_to(from, to)[..] {
+					[] => {
+						self.invalid = Some((from, to));
+						// Signal the pieces that would check the king if the move was made
+						let attempts = [
+							Move::new(from, to),
+							Move { promotion: Some(Piece::Queen), ..Move::new(from, to) },
+						];
+						if let Some(mv) = attempts.iter().find(|m| position.pseudo_legal(m)) {
+							let after = position.after(mv);
+							if let Some(king) = after.king(position.player()) {
+								self.checked_by = after.attackers(king, after.player());
+							}
+						}
+					}
+					[mv] => self.play_move(mv).expect("Legal move refused"),
+					// Several legal moves: the pawn is promoted, the player has to choose to what
+					_ => self.promotion = Some(Promotion { from, to, choice: 0 }),
+				}
+			}
+		}
+		// Signal any currently checking pieces
+		self.checked_by.append(&mut self.history.position().checkers());
+	}
+	/// The legal moves of the piece at `from` when dropped at `to`: the moves to `to`, or castling
+	/// by moving the king to the tile it ends on
+	fn moves_to(&self, from: Pos, to: Pos) -> Vec<Move> {
+		let position = self.history.position();
+		let mut moves = position.legal_moves();
+		moves.retain(|m| m.from == from);
+		if moves.iter().any(|m| m.to == to) {
+			moves.retain(|m| m.to == to);
+		} else {
+			moves.retain(|m| {
+				position.castling_side(m).is_some_and(|side| (side.king_file(), from.1) == to)
+			});
+		}
+		moves
+	}
+	/// The tiles the piece at `from` can be dropped at to play a legal move, as in `moves_to`
+	fn destinations(&self, from: Pos) -> Vec<Pos> {
+		let position = self.history.position();
+		let moves = position.legal_moves().into_iter().filter(|m| m.from == from);
+		moves
+			.flat_map(|m| {
+				let king_tile = position.castling_side(&m).map(|side| (side.king_file(), from.1));
+				[Some(m.to), king_tile]
+			})
+			.flatten()
+			.collect()
+	}
+	/// The last move played before the shown position, if any
+	fn last_move(&self) -> Option<Move> {
+		if self.editing.is_some() {
+			return None;
+		}
+		let played = self.preview.unwrap_or(self.history.moves().len());
+		played.checked_sub(1).map(|index| self.history.moves()[index])
+	}
+	/// Plays the promotion being chosen
+	fn promote(&mut self) {
+		if let Some(Promotion { from, to, choice }) = self.promotion.take() {
+			let mv = Move { from, to, promotion: Some(PROMOTIONS[choice]) };
+			self.play_move(mv).expect("Legal move refused");
+			self.checked_by = self.history.position().checkers();
+		}
+	}
+	/// Plays the given move. The moves taken back can only be replayed if it is the next one.
+	fn play_move(&mut self, mv: Move) -> Result<(), IllegalMove> {
+		self.history.play(mv)?;
+		self.hint = None;
+		if self.undone.last() == Some(&mv) {
+			self.undone.pop();
+		} else {
+			self.undone.clear();
+		}
+		self.follow_perspective();
+		Ok(())
+	}
+	/// Turns the board to the side it should be seen from, forgetting about any flip by hand
+	fn follow_perspective(&mut self) {
+		self.rotated = match self.perspective {
+			Perspective::Fixed(color) => color == Color::Black,
+			Perspective::Auto => self.history.position().player() == Color::Black,
+		};
+	}
+	/// Moves the cursor by the given number of tiles to the right and to the top of the screen
+	fn move_cursor(&mut self, right: isize, up: isize) {
+		let (right, up) = if self.rotated { (-right, -up) } else { (right, up) };
+		let (x, y) = self.cursor_pos;
+		self.cursor_pos =
+			(x.saturating_add_signed(right).min(7), y.saturating_add_signed(up).min(7));
+	}
+	/// The tile displayed at the given column and line of the board, if any
+	fn tile_at(&self, column: u16, line: u16) -> Option<Pos> {
+		// The tiles are after the rank number and a space
+		let column = (column as usize).checked_sub(Self::RANK_WIDTH)?;
+		let x = Some(column / self.tile.0).filter(|&x| x < 8)?;
+		let y = Some(line as usize / self.tile.1).filter(|&y| y < 8)?;
+		Some(if self.rotated { (7 - x, y) } else { (x, 7 - y) })
+	}
+	/// Takes back the last move, returning whether there was one
+	fn undo(&mut self) -> bool {
+		let Some(mv) = self.history.undo() else {
+			return false;
+		};
+		self.undone.push(mv);
+		self.back_to_game();
+		self.follow_perspective();
+		true
+	}
+	/// Replays the last move taken back, returning whether there was one
+	fn redo(&mut self) -> bool {
+		let Some(mv) = self.undone.pop() else {
+			return false;
+		};
+		self.history.play(mv).expect("Move taken back refused");
+		self.back_to_game();
+		self.follow_perspective();
+		true
+	}
+	/// Forgets about what was being done and shows the current position
+	fn back_to_game(&mut self) {
+		self.selected = None;
+		self.hint = None;
+		self.promotion = None;
+		self.invalid = None;
+		self.preview = None;
+		self.checked_by = self.history.position().checkers();
+	}
+	/// Shows the position after the given number of moves instead of the current one, until a key
+	/// is pressed. Shows the current position if all the moves are asked for.
+	fn preview(&mut self, moves: usize) {
+		self.back_to_game();
+		self.preview = (moves < self.history.moves().len()).then_some(moves);
+	}
+	/// The position shown on the board
+	fn shown(&self) -> &Position {
+		if let Some(position) = &self.editing {
+			return position;
+		}
+		self.preview.map_or(self.history.position(), |moves| &self.history.positions()[moves])
+	}
+	/// A short text describing the state of the game
+	fn status(&self) -> String {
+		if self.preview.is_some() {
+			return "Esc: back to game".to_owned();
+		}
+		let position = self.history.position();
+		if let Some(hint) = self.hint {
+			return format!("Hint: {}", position.san(&hint));
+		}
+		let player = match self.shown().player() {
+			Color::White => "White",
+			Color::Black => "Black",
+		};
+		if self.editing.is_some() {
+			return format!("{} to play", player);
+		}
+		match self.history.outcome() {
+			None if position.in_check() => format!("{} is in check", player),
+			None if self.computer == Some(position.player()) => format!("{} is thinking", player),
+			None => format!("{} to play", player),
+			Some(outcome) => {
+				let reason = match outcome {
+					Outcome::Checkmate(_) => "Checkmate!",
+					Outcome::Timeout(_) => "Time out!",
+					Outcome::Draw(DrawReason::Stalemate) => "Stalemate",
+					Outcome::Draw(DrawReason::FiftyMoves) => "50 moves rule",
+					Outcome::Draw(DrawReason::Repetition) => "Repetition",
+					Outcome::Draw(DrawReason::InsufficientMaterial) => "Dead position",
+					Outcome::Draw(DrawReason::TimeoutVsInsufficientMaterial) => "Flag, no mate",
+				};
+				format!("{} {}", reason, outcome.score())
+			}
+		}
+	}
+}
+
+impl Index<Pos> for Board {
+	type Output = Option<Tile>;
+	fn index(&self, pos: Pos) -> &Self::Output {
+		&self.shown()[pos]
+	}
+}
+
+impl Board {
+	/// A board showing the given game, the cursor on the king's tile of the next player
+	fn new(history: History) -> Self {
+		let cursor_pos = (4, history.position().player().back_rank());
+		Board {
+			theme: Theme::default(),
+			pieces: PieceSet::default(),
+			tile: Self::TILE,
+			history,
+			rotated: false,
+			perspective: Perspective::Fixed(Color::White),
+			cursor_pos,
+			cursor_style_alt: false,
+			selected: None,
+			hint: None,
+			checked_by: vec![],
+			invalid: None,
+			promotion: None,
+			computer: None,
+			undone: vec![],
+			preview: None,
+			editing: None,
+		}
+	}
+}
+
+impl Widget for Board {
+	fn size(&self) -> (usize, usize) {
+		Self::size_with(self.tile)
+	}
+	fn displ_line(&self, f: &mut std::fmt::Formatter<'_>, line_nb: usize) -> std::fmt::Result {
+		let width = self.size().0;
+		let (tile_width, tile_height) = self.tile;
+		// The pieces and the coordinates are in the middle of the tiles, rounded to the top left
+		let (piece_column, piece_line) = ((tile_width - 1) / 2, (tile_height - 1) / 2);
+		if line_nb == self.status_line() {
+			if let Some(promotion) = self.promotion {
+				f.write_str("Promote: ")?;
+				let player = self.history.position().player();
+				for (i, piece) in PROMOTIONS.iter().enumerate() {
+					let piece = self.pieces.symbol(*piece, player).to_string() + " ";
+					if i == promotion.choice {
+						write!(f, "{}", highlight(self.theme.cursor).apply(piece))?;
+					} else {
+						f.write_str(&piece)?;
+					}
+				}
+				let len = Self::PROMOTION_COLUMN + 2 * PROMOTIONS.len();
+				write!(f, "{:1$}", "", width - len)?;
+			} else {
+				write!(f, "{:^1$}", self.status(), width)?;
+			}
+		} else if line_nb == 8 * tile_height {
+			write!(f, "{:1$}", "", Self::RANK_WIDTH)?;
+			let write_column = |letter| {
+				write!(f, "{:1$}{2:3$}", "", piece_column, letter, tile_width - piece_column)
+			};
+			let mut col_names = 'A'..='H';
+			if self.rotated {
+				col_names.rev().try_for_each(write_column)?
+			} else {
+				col_names.try_for_each(write_column)?
+			};
+		} else {
+			// The bord begins at bottom left
+			let rank = if self.rotated { line_nb / tile_height } else { 7 - line_nb / tile_height };
+			let middle = line_nb % tile_height == piece_line;
+			if middle {
+				write!(f, "{:<1$}", rank + 1, Self::RANK_WIDTH)?;
+			} else {
+				write!(f, "{:1$}", "", Self::RANK_WIDTH)?;
+			}
+
+			let player = self.history.position().player();
+			// What happened in the game doesn't concern the past or edited positions
+			let live = self.preview.is_none() && self.editing.is_none();
+			let theme = &self.theme;
+			let destinations =
+				self.selected.map(|from| self.destinations(from)).unwrap_or_default();
+			let hint = self.hint.filter(|_| live);
+			let last_move = self.last_move();
+			let write_tile = |i: usize| {
+				let pos = (i, rank);
+				let tile = self[pos];
+				let background = if pos == self.cursor_pos && !self.cursor_style_alt {
+					theme.cursor
+				} else if live
+					&& (!self.checked_by.is_empty() && tile == Some(Tile(Piece::King, player))
+						|| self.checked_by.contains(&pos))
+				{
+					theme.checked
+				} else if self.selected == Some(pos) {
+					theme.selected
+				} else if self.invalid.is_some_and(|(p0, p1)| p0 == pos || p1 == pos) {
+					theme.invalid
+				} else if hint.is_some_and(|mv| mv.from == pos || mv.to == pos) {
+					theme.hint
+				} else if destinations.contains(&pos) {
+					theme.legal
+				} else if last_move.is_some_and(|mv| mv.from == pos || mv.to == pos) {
+					theme.last_move
+				} else if (rank + i).is_multiple_of(2) {
+					theme.light_tile
+				} else {
+					theme.dark_tile
+				};
+				let mut style = highlight(background);
+				let content = match tile {
+					Some(Tile(piece, color)) if middle => {
+						style.foreground_color = Some(theme.pieces(color, self.pieces));
+						let symbol = self.pieces.symbol(piece, color);
+						format!("{:1$}{2:3$}", "", piece_column, symbol, tile_width - piece_column)
+					}
+					_ => " ".repeat(tile_width),
+				};
+				write!(f, "{}", style.apply(content))
+			};
+			if self.rotated {
+				(0..8).rev().try_for_each(write_tile)?
+			} else {
+				(0..8).try_for_each(write_tile)?
+			};
+		}
+		Ok(())
+	}
+}
+
+impl MouseEventWidget for Board {
+	type MouseHandlingResult = bool;
+	fn mouse_event(&mut self, event: crossterm::event::MouseEvent) -> Self::MouseHandlingResult {
+		// NB: the event will be filtered and re-indexed by the wrapping Auto-Padder
+		let MouseEvent { kind, column, row, .. } = event;
+		let status_line = self.status_line();
+		if let Some(promotion) = &mut self.promotion {
+			// Only the pieces to promote to can be chosen
+			let column = (column as usize).wrapping_sub(Self::PROMOTION_COLUMN) / 2;
+			if row as usize != status_line || column >= PROMOTIONS.len() {
+				return false;
+			}
+			match kind {
+				MouseEventKind::Moved if promotion.choice != column => promotion.choice = column,
+				MouseEventKind::Down(MouseButton::Left) => {
+					promotion.choice = column;
+					self.promote();
+				}
+				_ => return false,
+			}
+			return true;
+		}
+		let Some(new_pos) = self.tile_at(column, row) else {
+			return false;
+		};
+		match kind {
+			MouseEventKind::Moved | MouseEventKind::Drag(_) => {
+				if new_pos == self.cursor_pos {
+					return false;
+				} else {
+					self.cursor_pos = new_pos;
+				}
+			}
+			MouseEventKind::Down(MouseButton::Left) => {
+				self.cursor_pos = new_pos;
+				self.select();
+			}
+			MouseEventKind::Up(MouseButton::Left) => {
+				self.cursor_pos = new_pos;
+				self.play();
+			}
+			_ => (),
+		}
+		true
+	}
+}
+
+/// The moves played so far in SAN, and what the analysing engine thinks of the position if the
+/// game is analysed.
+struct MovesPanel {
+	/// The moves played when the panel was last updated
+	played: Vec<Move>,
+	/// The moves played, in SAN
+	sans: Vec<String>,
+	/// The number of the first move
+	first_number: usize,
+	/// Whether black played the first move, leaving white's first slot empty
+	black_first: bool,
+	/// The number of moves of the position shown on the board
+	shown: usize,
+	/// The first row of moves displayed
+	scroll: usize,
+	/// The lines taken by the panel
+	height: usize,
+	/// The style of the move leading to the position shown on the board
+	shown_style: ContentStyle,
+	/// What the analysing engine thinks of the position, if the game is analysed
+	evaluation: Option<Evaluation>,
+}
+
+impl MovesPanel {
+	const WIDTH: usize = 20;
+	/// The width of the number starting each row
+	const NUMBER_WIDTH: usize = 5;
+	/// The width of a move in a row, the space separating it from the next one included
+	const MOVE_WIDTH: usize = 8;
+
+	fn new(history: &History) -> Self {
+		let start = history.start();
+		let mut panel = Self {
+			played: vec![],
+			sans: vec![],
+			first_number: start.fullmove_number() as usize,
+			black_first: start.player() == Color::Black,
+			shown: 0,
+			scroll: 0,
+			height: 8,
+			shown_style: highlight(Theme::default().cursor),
+			evaluation: None,
+		};
+		panel.update(history, history.moves().len());
+		panel
+	}
+
+	/// Shows the moves of the given game, scrolling to the one leading to the shown position if it
+	/// changed.
+	fn update(&mut self, history: &History, shown: usize) {
+		if self.played == history.moves() && self.shown == shown {
+			return;
+		}
+		if self.played != history.moves() {
+			self.played = history.moves().to_vec();
+			self.sans =
+				history.positions().iter().zip(history.moves()).map(|(p, mv)| p.san(mv)).collect();
+		}
+		self.shown = shown;
+		self.scroll_to_shown();
+	}
+
+	/// Takes the given number of lines, scrolling to keep the move leading to the shown position
+	/// visible.
+	fn set_height(&mut self, height: usize) {
+		self.height = height;
+		self.scroll_to_shown();
+	}
+
+	/// Scrolls to the move leading to the shown position, if it isn't visible.
+	fn scroll_to_shown(&mut self) {
+		if let Some(row) = self.shown.checked_sub(1).map(|index| self.slot(index) / 2) {
+			if row < self.scroll {
+				self.scroll = row;
+			} else if row >= self.scroll + self.rows() {
+				self.scroll = row + 1 - self.rows();
+			}
+		}
+	}
+
+	/// The lines above the moves
+	fn header(&self) -> usize {
+		self.evaluation.as_ref().map_or(0, |_| Evaluation::HEIGHT)
+	}
+
+	/// How many rows of moves are displayed
+	fn rows(&self) -> usize {
+		self.height - self.header()
+	}
+
+	/// The slot of the move of the given index in the list, two slots per row
+	fn slot(&self, index: usize) -> usize {
+		index + self.black_first as usize
+	}
+
+	fn scroll_by(&mut self, rows: isize) {
+		let total = self.slot(self.sans.len()).div_ceil(2);
+		let max = total.saturating_sub(self.rows());
+		self.scroll = self.scroll.saturating_add_signed(rows).min(max);
+	}
+}
+
+impl Widget for MovesPanel {
+	fn size(&self) -> (usize, usize) {
+		(Self::WIDTH, self.height)
+	}
+	fn displ_line(&self, f: &mut std::fmt::Formatter<'_>, line: usize) -> std::fmt::Result {
+		if let Some(evaluation) = self.evaluation.as_ref().filter(|_| line < self.header()) {
+			return evaluation.displ_line(f, line);
+		}
+		let row = self.scroll + line - self.header();
+		if row * 2 >= self.slot(self.sans.len()) {
+			return write!(f, "{:1$}", "", Self::WIDTH);
+		}
+		write!(f, "{:>1$}. ", self.first_number + row, Self::NUMBER_WIDTH - 2)?;
+		for slot in [row * 2, row * 2 + 1] {
+			let index = slot.checked_sub(self.black_first as usize);
+			let san = match index {
+				None => "...",
+				Some(index) => self.sans.get(index).map_or("", String::as_str),
+			};
+			let san = format!("{:1$}", san, Self::MOVE_WIDTH - 1);
+			if index.is_some_and(|index| index + 1 == self.shown) {
+				write!(f, "{}", self.shown_style.apply(san))?;
+			} else {
+				f.write_str(&san)?;
+			}
+			if slot == row * 2 {
+				f.write_char(' ')?;
+			}
+		}
+		Ok(())
+	}
+}
+
+impl MouseEventWidget for MovesPanel {
+	/// The number of moves of the position to show, if a move was clicked
+	type MouseHandlingResult = Option<usize>;
+	fn mouse_event(&mut self, event: crossterm::event::MouseEvent) -> Self::MouseHandlingResult {
+		let MouseEvent { kind, column, row, .. } = event;
+		match kind {
+			MouseEventKind::ScrollUp => self.scroll_by(-1),
+			MouseEventKind::ScrollDown => self.scroll_by(1),
+			MouseEventKind::Down(MouseButton::Left) => {
+				let line =
+					(row as usize).checked_sub(self.header()).filter(|&l| l < self.rows())?;
+				let column = (column as usize).checked_sub(Self::NUMBER_WIDTH)?;
+				let slot = (self.scroll + line) * 2 + column / Self::MOVE_WIDTH;
+				let index = slot.checked_sub(self.black_first as usize)?;
+				return (index < self.sans.len()).then_some(index + 1);
+			}
+			_ => (),
+		}
+		None
+	}
+}
+
+/// A player and their time left, if the game is played with a clock.
+struct ClockFace {
+	color: Color,
+	/// How the king of the player is drawn
+	pieces: PieceSet,
+	/// The time left to the player, if the game is played with a clock
+	time: Option<Duration>,
+	/// Whether it is the turn of the player
+	active: bool,
+	active_style: ContentStyle,
+	flagged_style: ContentStyle,
+}
+
+impl ClockFace {
+	fn new(color: Color) -> Self {
+		let theme = Theme::default();
+		Self {
+			color,
+			pieces: PieceSet::default(),
+			time: None,
+			active: false,
+			active_style: highlight(theme.cursor),
+			flagged_style: highlight(theme.checked),
+		}
+	}
+}
+
+/// Writes the given time as a clock does: hours only when needed, and tenths of seconds under
+/// ten seconds. The time is rounded up, so that it only shows zero once it ran out.
+fn clock_time(time: Duration) -> String {
+	let secs = (time + Duration::from_nanos(999_999_999)).as_secs();
+	match secs {
+		3601.. => format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60),
+		11.. => format!("{}:{:02}", secs / 60, secs % 60),
+		_ => {
+			let tenths = (time + Duration::from_nanos(99_999_999)).as_millis() / 100;
+			format!("0:{:02}.{}", tenths / 10, tenths % 10)
+		}
+	}
+}
+
+impl Widget for ClockFace {
+	fn size(&self) -> (usize, usize) {
+		(MovesPanel::WIDTH, 1)
+	}
+	fn displ_line(&self, f: &mut std::fmt::Formatter<'_>, _: usize) -> std::fmt::Result {
+		let name = match self.color {
+			Color::White => "White",
+			Color::Black => "Black",
+		};
+		let king = self.pieces.symbol(Piece::King, self.color);
+		let time = self.time.map(clock_time).unwrap_or_default();
+		let face = format!(" {} {:<6}{:>10} ", king, name, time);
+		if self.time.is_some_and(|time| time.is_zero()) {
+			write!(f, "{}", self.flagged_style.apply(face))
+		} else if self.active {
+			write!(f, "{}", self.active_style.apply(face))
+		} else {
+			f.write_str(&face)
+		}
+	}
+}
+
+/// The board, and next to it the players with the moves played between them.
+struct ChessScreen {
+	board: Board,
+	/// The player on the top of the board
+	top: ClockFace,
+	panel: MovesPanel,
+	/// The player on the bottom of the board
+	bottom: ClockFace,
+}
+
+impl ChessScreen {
+	/// The columns between the board and the moves
+	const GAP: usize = 2;
+
+	/// The size of the screen, with tiles of the given size. The players and the moves are as
+	/// high as the board.
+	fn size_with(tile: (usize, usize)) -> (usize, usize) {
+		let (width, height) = Board::size_with(tile);
+		(width + Self::GAP + MovesPanel::WIDTH, height)
+	}
+
+	/// The column of the moves and the players
+	fn panel_column(&self) -> usize {
+		self.board.size().0 + Self::GAP
+	}
+
+	fn new(history: History) -> Self {
+		let mut screen = Self {
+			top: ClockFace::new(Color::Black),
+			panel: MovesPanel::new(&history),
+			bottom: ClockFace::new(Color::White),
+			board: Board::new(history),
+		};
+		screen.update_clocks(None, Instant::now());
+		screen
+	}
+
+	/// Draws the game with the given theme and piece set, on a large board or not.
+	fn set_looks(&mut self, theme: Theme, pieces: PieceSet, large: bool) {
+		for face in [&mut self.top, &mut self.bottom] {
+			face.pieces = pieces;
+			face.active_style = highlight(theme.cursor);
+			face.flagged_style = highlight(theme.checked);
+		}
+		self.panel.shown_style = highlight(theme.cursor);
+		self.board.set_looks(theme, pieces, large);
+		// The players are above and under the moves
+		self.panel.set_height(self.board.size().1 - 2);
+	}
+
+	/// Shows the moves of the game in the panel, once the board changed.
+	fn update_panel(&mut self) {
+		let board = &self.board;
+		let shown = board.preview.unwrap_or(board.history.moves().len());
+		self.panel.update(&board.history, shown);
+	}
+
+	/// Shows the time left on the given clock at the given time, and who is to play, the players
+	/// being on the side of the board they are seen from.
+	fn update_clocks(&mut self, clock: Option<&Clock>, now: Instant) {
+		let history = &self.board.history;
+		let (top, bottom) = if self.board.rotated {
+			(Color::White, Color::Black)
+		} else {
+			(Color::Black, Color::White)
+		};
+		self.top.color = top;
+		self.bottom.color = bottom;
+		for face in [&mut self.top, &mut self.bottom] {
+			face.time = clock.map(|clock| clock.remaining(face.color, now));
+			face.active = history.outcome().is_none() && history.position().player() == face.color;
+		}
+	}
+}
+
+impl Widget for ChessScreen {
+	fn size(&self) -> (usize, usize) {
+		Self::size_with(self.board.tile)
+	}
+	fn displ_line(&self, f: &mut std::fmt::Formatter<'_>, line: usize) -> std::fmt::Result {
+		self.board.displ_line(f, line)?;
+		write!(f, "{:1$}", "", Self::GAP)?;
+		match line.checked_sub(1) {
+			None => self.top.displ_line(f, 0),
+			Some(line) if line < self.panel.height => self.panel.displ_line(f, line),
+			Some(_) => self.bottom.displ_line(f, 0),
+		}
+	}
+}
+
+impl MouseEventWidget for ChessScreen {
+	type MouseHandlingResult = bool;
+	fn mouse_event(
+		&mut self,
+		mut event: crossterm::event::MouseEvent,
+	) -> Self::MouseHandlingResult {
+		if (event.column as usize) < self.board.size().0 {
+			return self.board.mouse_event(event);
+		}
+		let Some(column) = (event.column as usize).checked_sub(self.panel_column()) else {
+			return false;
+		};
+		// The moves are between the players
+		let Some(row) = event.row.checked_sub(1) else {
+			return false;
+		};
+		event.column = column as u16;
+		event.row = row;
+		match self.panel.mouse_event(event) {
+			Some(moves) => {
+				self.board.preview(moves);
+				self.update_panel();
+				true
+			}
+			None => false,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::backend::headless::Screen;
+	use crate::backend::VirtualTerminal;
+	use crossterm::event::KeyModifiers;
+	use std::io::Write as _;
+	use std::ops::Range;
+	use terminity_widgets::render;
+
+	/// The text in the given columns of a line of the screen, without the trailing spaces.
+	fn text(screen: &Screen, y: u16, columns: Range<u16>) -> String {
+		let text: String = columns.map(|x| screen.cell(x, y).content.as_str()).collect();
+		text.trim_end().to_owned()
+	}
+
+	/// The number of white cells of the bar of an evaluation of the given score.
+	fn evaluation_bar(score: Score) -> usize {
+		struct Bar(Evaluation);
+		impl std::fmt::Display for Bar {
+			fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+				self.0.displ_line(f, 1)
+			}
+		}
+		let bar = Bar(Evaluation { score: Some(score), best: None }).to_string();
+		let mut cells = vec![render::Cell::default(); MovesPanel::WIDTH];
+		assert_eq!(render::print_ansi(&mut cells, 0, &bar), MovesPanel::WIDTH);
+		cells.iter().filter(|c| c.style.background_color == Some(TermColor::White)).count()
+	}
+
+	#[test]
+	fn evaluation() {
+		assert_eq!(evaluation_bar(Score::Centipawns(0)), 10);
+		assert_eq!(evaluation_bar(Score::Centipawns(-31)), 10);
+		assert_eq!(evaluation_bar(Score::Centipawns(120)), 12);
+		assert_eq!(evaluation_bar(Score::Centipawns(-120)), 8);
+		assert_eq!(evaluation_bar(Score::Centipawns(-900)), 0);
+		assert_eq!(evaluation_bar(Score::Centipawns(900)), 20);
+		assert_eq!(evaluation_bar(Score::Mate(-3)), 0);
+		assert_eq!(evaluation_bar(Score::Mate(2)), 20);
+	}
+
+	/// Scripts the key presses moving the cursor from `from` to `to`.
+	fn goto(term: &mut VirtualTerminal, from: Pos, to: Pos) {
+		let horizontal = if to.0 > from.0 { KeyCode::Right } else { KeyCode::Left };
+		let vertical = if to.1 > from.1 { KeyCode::Up } else { KeyCode::Down };
+		term.push_keys(std::iter::repeat_n(horizontal, to.0.abs_diff(from.0)));
+		term.push_keys(std::iter::repeat_n(vertical, to.1.abs_diff(from.1)));
+	}
+
+	/// Scripts a move made with the keyboard, the cursor being at `cursor`.
+	fn play(term: &mut VirtualTerminal, cursor: &mut Pos, from: Pos, to: Pos) {
+		goto(term, *cursor, from);
+		term.push_keys([KeyCode::Enter]);
+		goto(term, from, to);
+		term.push_keys([KeyCode::Enter]);
+		*cursor = to;
+	}
+
+	#[test]
+	fn scholars_mate() {
+		let mut term = VirtualTerminal::new((18, 9));
+		let mut cursor = (4, 0);
+		play(&mut term, &mut cursor, (4, 1), (4, 3));
+		play(&mut term, &mut cursor, (4, 6), (4, 4));
+		play(&mut term, &mut cursor, (3, 0), (7, 4));
+		// The board is then centered, and the knight is moved with the mouse
+		term.push_resize(50, 15);
+		let screen_pos = |(x, y): Pos| (5 + 2 + 2 * x as u16, 2 + 7 - y as u16);
+		for (kind, pos) in [
+			(MouseEventKind::Moved, (1, 7)),
+			(MouseEventKind::Down(MouseButton::Left), (1, 7)),
+			(MouseEventKind::Drag(MouseButton::Left), (2, 5)),
+			(MouseEventKind::Up(MouseButton::Left), (2, 5)),
+		] {
+			let (column, row) = screen_pos(pos);
+			term.push_mouse(kind, column, row);
+		}
+		cursor = (2, 5);
+		play(&mut term, &mut cursor, (5, 0), (2, 3));
+		play(&mut term, &mut cursor, (6, 7), (5, 5));
+		play(&mut term, &mut cursor, (7, 4), (5, 6));
+		term.push_key(KeyCode::Char('c'), KeyModifiers::CONTROL);
+
+		Chess::default().run(&mut term).unwrap();
+		assert_eq!(term.remaining_events(), 0);
+		let screen = term.screen();
+		let board: Vec<_> = (2..12).map(|y| text(screen, y, 0..23)).collect();
+		assert_eq!(
+			board,
+			[
+				"     8 ♖   ♗ ♕ ♔ ♗   ♖",
+				"     7 ♙ ♙ ♙ ♙   ♛ ♙ ♙",
+				"     6     ♘     ♘",
+				"     5         ♙",
+				"     4     ♝   ♟",
+				"     3",
+				"     2 ♟ ♟ ♟ ♟   ♟ ♟ ♟",
+				"     1 ♜ ♞ ♝   ♚   ♞ ♜",
+				"       A B C D E F G H",
+				"       Checkmate! 1-0",
+			]
+		);
+		let moves: Vec<_> = (3..7).map(|y| text(screen, y, 25..45)).collect();
+		assert_eq!(
+			moves,
+			["  1. e4      e5", "  2. Qh5     Nc6", "  3. Bc4     Nf6", "  4. Qxf7#"]
+		);
+		let background = |pos| {
+			let (column, row) = screen_pos(pos);
+			screen.cell(column, row).style.background_color
+		};
+		// The cursor is on the queen, that checks the king
+		assert_eq!(background((5, 6)), Some(TermColor::DarkBlue));
+		assert_eq!(background((4, 7)), Some(TermColor::DarkRed));
+		assert_eq!(background((0, 0)), Some(TermColor::DarkGrey));
+		assert_eq!(background((1, 0)), None);
+	}
+
+	#[test]
+	fn castling_and_promotion() {
+		let mut term = VirtualTerminal::new((40, 10));
+		let mut cursor = (4, 0);
+		for (from, to) in [
+			((6, 0), (5, 2)),
+			((7, 6), (7, 4)),
+			((4, 1), (4, 2)),
+			((7, 4), (7, 3)),
+			((5, 0), (4, 1)),
+			((7, 3), (7, 2)),
+			// Castling by moving the king two tiles away
+			((4, 0), (6, 0)),
+			((7, 2), (6, 1)),
+			((0, 1), (0, 2)),
+		] {
+			play(&mut term, &mut cursor, from, to);
+		}
+		// Taking the rook, choosing to promote to a knight
+		play(&mut term, &mut cursor, (6, 1), (5, 0));
+		term.push_keys([KeyCode::Right, KeyCode::Right, KeyCode::Left]);
+		term.push_keys([KeyCode::Right, KeyCode::Right, KeyCode::Enter]);
+		term.push_key(KeyCode::Char('c'), KeyModifiers::CONTROL);
+
+		let mut state = GameState::new(History::default());
+		events::run(&mut state, &mut term).unwrap();
+		let position = state.screen.board.history.position();
+		assert_eq!(position[(6, 0)], Some(Tile(Piece::King, Color::White)));
+		assert_eq!(position[(5, 0)], Some(Tile(Piece::Knight, Color::Black)));
+		assert_eq!(state.screen.board.history.moves().len(), 10);
+		let screen = term.screen();
+		assert_eq!(text(screen, 0, 0..18), "8 ♖ ♘ ♗ ♕ ♔ ♗ ♘ ♖");
+		assert_eq!(text(screen, 7, 0..18), "1 ♜ ♞ ♝ ♛   ♘ ♚");
+		assert_eq!(text(screen, 9, 0..18).trim(), "White to play");
+		assert_eq!(text(screen, 5, 20..40), "  5. a3      gxf1=N");
+	}
+
+	#[test]
+	fn promotion_prompt() {
+		let mut board = Board::new(History::default());
+		for (from, to) in [
+			((1, 1), (1, 3)),
+			((0, 6), (0, 4)),
+			((1, 3), (0, 4)),
+			((1, 6), (1, 5)),
+			((0, 4), (1, 5)),
+			((2, 7), (0, 5)),
+			((1, 5), (1, 6)),
+			((0, 5), (2, 3)),
+		] {
+			board.selected = Some(from);
+			board.cursor_pos = to;
+			board.play();
+		}
+		// Taking the rook
+		board.selected = Some((1, 6));
+		board.cursor_pos = (0, 7);
+		board.play();
+		assert!(board.promotion.is_some());
+		let mut term = VirtualTerminal::new((18, 10));
+		write!(term, "{}", board).unwrap();
+		assert_eq!(term.screen().line(9).trim_end(), "Promote: ♛ ♜ ♝ ♞");
+		assert_eq!(term.screen().cell(9, 9).style.background_color, Some(TermColor::DarkBlue));
+		// Clicking the rook
+		let kind = MouseEventKind::Down(MouseButton::Left);
+		board.mouse_event(MouseEvent { kind, column: 11, row: 9, modifiers: KeyModifiers::NONE });
+		assert_eq!(board[(0, 7)], Some(Tile(Piece::Rook, Color::White)));
+		assert!(board.promotion.is_none());
+	}
+
+	#[test]
+	fn resume_and_save() {
+		let fen = "4k3/8/8/8/8/8/5PPP/3rR1K1 b - - 0 30";
+		let path = std::env::temp_dir().join(format!("terminity-chess-{}.pgn", std::process::id()));
+		let chess = Chess::new(History::new(fen.parse().unwrap())).save_to(&path);
+		let mut term = VirtualTerminal::new((40, 10));
+		// The cursor starts on the black king's tile
+		let mut cursor = (4, 7);
+		play(&mut term, &mut cursor, (3, 0), (4, 0));
+		term.push_key(KeyCode::Char('c'), KeyModifiers::CONTROL);
+
+		chess.run(&mut term).unwrap();
+		assert_eq!(text(term.screen(), 9, 0..18).trim(), "Checkmate! 0-1");
+		assert_eq!(text(term.screen(), 1, 20..40), " 30. ...     Rxe1#");
+		let pgn = fs::read_to_string(&path).unwrap();
+		fs::remove_file(&path).unwrap();
+		assert!(pgn.ends_with("\n\n30... Rxe1# 0-1\n"), "{}", pgn);
+		let history = History::from_pgn(&pgn).unwrap();
+		assert_eq!(history.start().to_fen(), fen);
+		assert_eq!(history.moves(), [Move::new((3, 0), (4, 0))]);
+	}
+
+	#[test]
+	fn computer_opponent() {
+		let ai = || ComputerPlayer::Builtin(Ai::new(Budget { depth: 1, time: None }, 0), None);
+		let mut state = GameState::new(History::default()).with_computer(Color::Black, ai());
+		let mut term = VirtualTerminal::new((40, 10));
+		play(&mut term, &mut (4, 0), (4, 1), (4, 3));
+		term.push_key(KeyCode::Char('c'), KeyModifiers::CONTROL);
+		events::run(&mut state, &mut term).unwrap();
+		// The computer started thinking once the move was played, and may not be done yet
+		state.computer_turn(true).unwrap();
+		assert!(!state.thinking());
+		assert_eq!(state.screen.board.history.moves().len(), 2);
+		assert_eq!(state.screen.board.status(), "White to play");
+		// Taking back the move takes back the computer's answer too
+		state.undo().unwrap();
+		assert!(state.screen.board.history.moves().is_empty());
+		state.redo().unwrap();
+		assert_eq!(state.screen.board.history.moves().len(), 2);
+
+		// The black pieces can't be moved by hand on the computer's turn
+		let mut history = History::default();
+		history.play(Move::new((4, 1), (4, 3))).unwrap();
+		let mut state = GameState::new(history).with_computer(Color::Black, ai());
+		assert_eq!(state.screen.board.status(), "Black is thinking");
+		state.screen.board.cursor_pos = (4, 6);
+		state.screen.board.select();
+		assert_eq!(state.screen.board.selected, None);
+
+		// The computer plays first as white
+		let mut state = GameState::new(History::default()).with_computer(Color::White, ai());
+		state.update(Event::Resize(18, 10));
+		state.computer_turn(true).unwrap();
+		assert_eq!(state.screen.board.history.position().player(), Color::Black);
+	}
+
+	#[test]
+	fn undo_and_redo() {
+		let mut term = VirtualTerminal::new((40, 10));
+		let mut cursor = (4, 0);
+		play(&mut term, &mut cursor, (4, 1), (4, 3));
+		play(&mut term, &mut cursor, (4, 6), (4, 4));
+		play(&mut term, &mut cursor, (6, 0), (5, 2));
+		term.push_keys([KeyCode::Char('u'), KeyCode::Backspace, KeyCode::Char('r')]);
+		term.push_key(KeyCode::Char('c'), KeyModifiers::CONTROL);
+
+		let mut state = GameState::new(History::default());
+		events::run(&mut state, &mut term).unwrap();
+		let board = &mut state.screen.board;
+		assert_eq!(board.history.moves(), [Move::new((4, 1), (4, 3)), Move::new((4, 6), (4, 4))]);
+		assert_eq!(board.undone, [Move::new((6, 0), (5, 2))]);
+		let screen = term.screen();
+		assert_eq!(text(screen, 0, 20..40), " ♔ Black");
+		assert_eq!(text(screen, 1, 20..40), "  1. e4      e5");
+		assert_eq!(screen.cell(33, 1).style.background_color, Some(TermColor::DarkBlue));
+		assert_eq!(text(screen, 2, 20..40), "");
+		// White is to play
+		assert_eq!(text(screen, 9, 20..40), " ♚ White");
+		assert_eq!(screen.cell(20, 9).style.background_color, Some(TermColor::DarkBlue));
+		assert_eq!(screen.cell(20, 0).style.background_color, None);
+
+		// Playing another move forgets about the moves taken back
+		board.play_move(Move::new((3, 1), (3, 3))).unwrap();
+		assert!(!board.redo());
+		assert!(board.undo());
+		assert!(board.redo());
+		assert_eq!(board.history.moves().len(), 3);
+	}
+
+	#[test]
+	fn moves_preview() {
+		let pgn =
+			"1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 8. c3 O-O \
+			9. h3 Nb8 10. d4 Nbd7 11. c4 c6 12. cxb5 axb5";
+		let mut state = GameState::new(History::from_pgn(pgn).unwrap());
+		// The list is scrolled down to the last move
+		assert_eq!(state.screen.panel.scroll, 4);
+		let mut term = VirtualTerminal::new((40, 10));
+		// Scrolling up to click on 3... a6
+		term.push_mouse(MouseEventKind::ScrollUp, 25, 1);
+		term.push_mouse(MouseEventKind::ScrollUp, 25, 1);
+		term.push_mouse(MouseEventKind::Down(MouseButton::Left), 34, 1);
+		term.push_key(KeyCode::Char('c'), KeyModifiers::CONTROL);
+		events::run(&mut state, &mut term).unwrap();
+
+		let board = &state.screen.board;
+		assert_eq!(board.preview, Some(6));
+		assert_eq!(board[(0, 5)], Some(Tile(Piece::Pawn, Color::Black)));
+		assert_eq!(board[(1, 4)], Some(Tile(Piece::Bishop, Color::White)));
+		let screen = term.screen();
+		assert_eq!(text(screen, 1, 20..40), "  3. Bb5     a6");
+		assert_eq!(screen.cell(33, 1).style.background_color, Some(TermColor::DarkBlue));
+		assert_eq!(text(screen, 9, 0..18).trim(), "Esc: back to game");
+
+		// No piece can be moved while looking at a past position
+		state.screen.board.cursor_pos = (4, 1);
+		state.screen.board.select();
+		assert_eq!(state.screen.board.selected, None);
+		state.update(Event::Key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)));
+		assert_eq!(state.screen.board.preview, None);
+		assert_eq!(state.screen.panel.scroll, 4);
+		assert_eq!(state.screen.board[(1, 4)], Some(Tile(Piece::Pawn, Color::Black)));
+	}
+
+	/// Scripts a move made with the mouse, the board being rotated or not.
+	fn click_move(term: &mut VirtualTerminal, from: Pos, to: Pos, rotated: bool) {
+		let screen_pos = |(x, y): Pos| {
+			let (x, y) = if rotated { (7 - x, y) } else { (x, 7 - y) };
+			(2 + 2 * x as u16, y as u16)
+		};
+		let (column, row) = screen_pos(from);
+		term.push_mouse(MouseEventKind::Down(MouseButton::Left), column, row);
+		let (column, row) = screen_pos(to);
+		term.push_mouse(MouseEventKind::Up(MouseButton::Left), column, row);
+	}
+
+	#[test]
+	fn perspectives() {
+		let mut state = GameState::new(History::default()).seen_from(Perspective::Auto);
+		let mut term = VirtualTerminal::new((40, 10));
+		click_move(&mut term, (4, 1), (4, 3), false);
+		// The board is flipped for black
+		click_move(&mut term, (4, 6), (4, 4), true);
+		click_move(&mut term, (6, 0), (5, 2), false);
+		term.push_key(KeyCode::Char('c'), KeyModifiers::CONTROL);
+		events::run(&mut state, &mut term).unwrap();
+		let board = &state.screen.board;
+		assert_eq!(board.history.moves().len(), 3);
+		assert!(board.rotated);
+		let screen = term.screen();
+		assert_eq!(text(screen, 0, 0..18), "1 ♜   ♝ ♚ ♛ ♝ ♞ ♜");
+		assert_eq!(text(screen, 2, 0..18), "3     ♞");
+		assert_eq!(text(screen, 4, 0..18), "5       ♙");
+		assert_eq!(text(screen, 7, 0..18), "8 ♖ ♘ ♗ ♔ ♕ ♗ ♘ ♖");
+		assert_eq!(text(screen, 8, 0..18), "  H G F E D C B A");
+		// Black is at the bottom, about to play
+		assert_eq!(text(screen, 0, 20..40), " ♚ White");
+		assert_eq!(text(screen, 9, 20..40), " ♔ Black");
+		assert_eq!(screen.cell(20, 9).style.background_color, Some(TermColor::DarkBlue));
+
+		// The arrows move the cursor on the screen whatever the side, and the board can be flipped
+		// back by hand until the next move
+		let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+		state.screen.board.cursor_pos = (3, 6);
+		state.update(key(KeyCode::Up));
+		state.update(key(KeyCode::Left));
+		assert_eq!(state.screen.board.cursor_pos, (4, 5));
+		state.update(key(KeyCode::Char('f')));
+		assert!(!state.screen.board.rotated);
+		state.update(key(KeyCode::Up));
+		state.update(key(KeyCode::Left));
+		assert_eq!(state.screen.board.cursor_pos, (3, 6));
+		assert_eq!(state.screen.top.color, Color::Black);
+		state.screen.board.selected = Some((1, 7));
+		state.screen.board.cursor_pos = (2, 5);
+		state.screen.board.play();
+		assert!(!state.screen.board.rotated);
+		assert_eq!(state.screen.board.history.moves().len(), 4);
+
+		// Against the computer, the board is seen from the player's side
+		let ai = ComputerPlayer::Builtin(Ai::new(Budget { depth: 1, time: None }, 0), None);
+		let state = GameState::new(History::default())
+			.with_computer(Color::White, ai)
+			.seen_from(Perspective::Fixed(Color::Black));
+		assert!(state.screen.board.rotated);
+	}
+
+	#[test]
+	fn clock() {
+		let control =
+			TimeControl::Fischer { base: Duration::from_secs(60), increment: Duration::ZERO };
+		let mut state = GameState::new(History::default()).with_clock(Clock::new(control));
+		let start = Instant::now();
+		let mut term = VirtualTerminal::new((40, 10));
+		play(&mut term, &mut (4, 0), (4, 1), (4, 3));
+		term.push_key(KeyCode::Char('u'), KeyModifiers::NONE);
+		term.push_key(KeyCode::Char('c'), KeyModifiers::CONTROL);
+		events::run(&mut state, &mut term).unwrap();
+		// The move can't be taken back, and it is now black's time that runs
+		assert_eq!(state.screen.board.history.moves().len(), 1);
+		let clock = state.clock.as_ref().unwrap();
+		assert_eq!(clock.running(), Some(Color::Black));
+		assert!(clock.remaining(Color::White, start) <= Duration::from_secs(60));
+		let screen = term.screen();
+		assert_eq!(text(screen, 0, 20..40), " ♔ Black       1:00");
+		assert_eq!(text(screen, 9, 20..40), " ♚ White       1:00");
+
+		// Black's flag falls
+		let later = start + Duration::from_secs(61);
+		state.update_clock(later);
+		assert_eq!(state.screen.board.history.outcome(), Some(Outcome::Timeout(Color::White)));
+		assert_eq!(state.screen.board.status(), "Time out! 1-0");
+		assert_eq!(state.clock.as_ref().unwrap().running(), None);
+		assert_eq!(state.screen.top.time, Some(Duration::ZERO));
+		assert!(!state.screen.bottom.active);
+		assert_eq!(clock_time(Duration::from_millis(10_420)), "0:11");
+		assert_eq!(clock_time(Duration::from_millis(9_420)), "0:09.5");
+		assert_eq!(clock_time(Duration::ZERO), "0:00.0");
+		assert_eq!(clock_time(Duration::from_millis(3_724_500)), "1:02:05");
+	}
+
+	#[test]
+	fn legal_moves_and_hint() {
+		let mut state = GameState::new(History::default());
+		let mut term = VirtualTerminal::new((40, 10));
+		goto(&mut term, (4, 0), (4, 1));
+		term.push_keys([KeyCode::Enter]);
+		term.push_key(KeyCode::Char('c'), KeyModifiers::CONTROL);
+		events::run(&mut state, &mut term).unwrap();
+		// The tiles the pawn can go to are highlighted
+		let screen = term.screen();
+		let background =
+			|(x, y): Pos| screen.cell(2 + 2 * x as u16, 7 - y as u16).style.background_color;
+		assert_eq!(background((4, 1)), Some(TermColor::DarkBlue));
+		assert_eq!(background((4, 2)), Some(TermColor::DarkCyan));
+		assert_eq!(background((4, 3)), Some(TermColor::DarkCyan));
+		assert_eq!(background((4, 4)), Some(TermColor::DarkGrey));
+		assert_eq!(background((3, 2)), None);
+		// Castling by moving the king to its tile or on the rook
+		let castling = "4k3/8/8/8/8/8/8/4K2R w K - 0 1".parse().unwrap();
+		let board = Board::new(History::new(castling));
+		let mut destinations = board.destinations((4, 0));
+		destinations.sort();
+		assert_eq!(destinations, [(3, 0), (3, 1), (4, 1), (5, 0), (5, 1), (6, 0), (7, 0)]);
+
+		// The last move is highlighted once played
+		let board = &mut state.screen.board;
+		board.cursor_pos = (4, 3);
+		board.play();
+		let mut term = VirtualTerminal::new((40, 10));
+		write!(term, "{}", state.screen.board).unwrap();
+		let screen = term.screen();
+		let background =
+			|(x, y): Pos| screen.cell(2 + 2 * x as u16, 7 - y as u16).style.background_color;
+		assert_eq!(background((4, 1)), Some(TermColor::DarkMagenta));
+		assert_eq!(background((4, 2)), Some(TermColor::DarkGrey));
+
+		// Asking for a hint in a mate in one
+		let mate_in_one = "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1".parse().unwrap();
+		let mut state = GameState::new(History::new(mate_in_one));
+		state.update(Event::Key(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE)));
+		state.poll_hint(true);
+		assert_eq!(state.screen.board.hint, Some(Move::new((0, 0), (0, 7))));
+		assert_eq!(state.screen.board.status(), "Hint: Ra8#");
+		let mut term = VirtualTerminal::new((40, 10));
+		write!(term, "{}", state.screen.board).unwrap();
+		assert_eq!(term.screen().cell(2, 0).style.background_color, Some(TermColor::Magenta));
+		state.screen.board.play_move(Move::new((0, 0), (0, 7))).unwrap();
+		assert_eq!(state.screen.board.hint, None);
+	}
+
+	#[test]
+	fn themes_and_large_board() {
+		let chess = Chess::default().large_board();
+		assert_eq!(chess.min_size(), (56, 18));
+		let wood = Theme::builtin("wood").unwrap();
+		let mut state =
+			GameState::new(History::default()).with_looks(wood.clone(), PieceSet::Ascii, true);
+		let mut term = VirtualTerminal::new((56, 18));
+		// The tiles are four columns wide and two lines high, any part of them can be clicked
+		term.push_mouse(MouseEventKind::Down(MouseButton::Left), 21, 13);
+		term.push_mouse(MouseEventKind::Up(MouseButton::Left), 19, 9);
+		term.push_key(KeyCode::Char('c'), KeyModifiers::CONTROL);
+		events::run(&mut state, &mut term).unwrap();
+		assert_eq!(state.screen.board.history.moves(), [Move::new((4, 1), (4, 3))]);
+		let screen = term.screen();
+		assert_eq!(text(screen, 0, 0..34), "8  r   n   b   q   k   b   n   r");
+		assert_eq!(text(screen, 1, 0..34), "");
+		assert_eq!(text(screen, 8, 0..34), format!("4{:18}P", ""));
+		assert_eq!(text(screen, 16, 0..34), "   A   B   C   D   E   F   G   H");
+		assert_eq!(text(screen, 17, 0..34).trim(), "Black to play");
+		assert_eq!(text(screen, 0, 36..56), " k Black");
+		assert_eq!(text(screen, 1, 36..56), "  1. e4");
+		assert_eq!(text(screen, 17, 36..56), " K White");
+		assert_eq!(screen.cell(2, 15).style.background_color, wood.light_tile);
+		assert_eq!(screen.cell(6, 15).style.background_color, wood.dark_tile);
+		assert_eq!(screen.cell(19, 8).style.foreground_color, wood.white_pieces);
+		assert_eq!(screen.cell(19, 2).style.foreground_color, wood.black_pieces);
+		assert_eq!(screen.cell(36, 0).style.background_color, wood.cursor);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn engine_opponent() {
+		let (mut command, _) = uci::tests::fake_engine("opponent");
+		let engine = Engine::start(&mut command, &Position::default()).unwrap();
+		let player = ComputerPlayer::Engine(engine, Limit::Depth(1));
+		let mut state = GameState::new(History::default()).with_computer(Color::Black, player);
+		let mut term = VirtualTerminal::new((40, 10));
+		play(&mut term, &mut (4, 0), (4, 1), (4, 3));
+		term.push_key(KeyCode::Char('c'), KeyModifiers::CONTROL);
+		events::run(&mut state, &mut term).unwrap();
+		state.computer_turn(true).unwrap();
+		assert_eq!(
+			state.screen.board.history.moves(),
+			[Move::new((4, 1), (4, 3)), Move::new((4, 6), (4, 4))]
+		);
+		assert_eq!(state.screen.board.status(), "White to play");
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn analysis() {
+		let (mut command, log) = uci::tests::fake_engine("analysis");
+		let engine = Engine::start(&mut command, &Position::default()).unwrap();
+		let mut state = GameState::new(History::default()).with_analyst(engine);
+		let mut term = VirtualTerminal::new((40, 10));
+		play(&mut term, &mut (4, 0), (4, 1), (4, 3));
+		term.push_key(KeyCode::Char('c'), KeyModifiers::CONTROL);
+		events::run(&mut state, &mut term).unwrap();
+		// Waiting for the analysis of the position after 1. e4
+		let start = std::time::Instant::now();
+		while state.screen.panel.evaluation.as_ref().unwrap().best.is_none() {
+			assert!(start.elapsed() < Duration::from_secs(5), "No analysis");
+			std::thread::sleep(Duration::from_millis(10));
+			state.analyse().unwrap();
+		}
+		state.render(&mut term).unwrap();
+		term.flush().unwrap();
+		let screen = term.screen();
+		// The engine gives its score for black
+		assert_eq!(text(screen, 1, 20..40), "-0.35  best e5");
+		assert_eq!(text(screen, 4, 20..40), "  1. e4");
+		// The evaluation bar is white on white's half, as no one has half a pawn of advantage
+		let bar: Vec<_> = (20..40).map(|x| screen.cell(x, 2).style.background_color).collect();
+		let (white, black) = (Some(TermColor::White), Some(TermColor::Black));
+		assert_eq!(bar, [[white; 10], [black; 10]].concat());
+		drop(state);
+		let log = fs::read_to_string(log).unwrap();
+		assert!(
+			log.contains("position startpos\ngo infinite\nstop\nposition startpos moves e2e4\n")
+		);
+	}
+}
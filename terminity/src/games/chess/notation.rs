@@ -0,0 +1,634 @@
+//! Chess notations: FEN for positions, SAN and UCI's long algebraic notation for moves, and PGN
+//! for whole games.
+//!
+//! ```
+//! use terminity::games::chess::rules::{History, Position};
+//!
+//! let mut history = History::default();
+//! for san in ["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6", "Qxf7#"] {
+//! 	let mv = history.position().parse_san(san).unwrap();
+//! 	history.play(mv).unwrap();
+//! }
+//! assert!(history.to_pgn().ends_with("1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0\n"));
+//!
+//! let fen = "r1bqkb1r/pppp1Qpp/2n2n2/4p3/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 0 4";
+//! assert_eq!(history.position().to_fen(), fen);
+//! assert_eq!(fen.parse::<Position>().unwrap(), *history.position());
+//! ```
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use super::rules::{CastlingSide, Color, History, Move, Outcome, Piece, Pos, Position, Tile};
+
+/// An error while reading a notation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError(pub String);
+
+impl Display for ParseError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.0)
+	}
+}
+
+impl Error for ParseError {}
+
+/// Shorthand to build an error.
+fn error<T>(message: String) -> Result<T, ParseError> {
+	Err(ParseError(message))
+}
+
+impl Piece {
+	/// The letter of the piece in english notations, uppercase.
+	pub fn letter(self) -> char {
+		match self {
+			Piece::King => 'K',
+			Piece::Queen => 'Q',
+			Piece::Rook => 'R',
+			Piece::Bishop => 'B',
+			Piece::Knight => 'N',
+			Piece::Pawn => 'P',
+		}
+	}
+	/// The piece of the given letter in english notations, whatever its case.
+	pub fn from_letter(letter: char) -> Option<Self> {
+		[Piece::King, Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight, Piece::Pawn]
+			.into_iter()
+			.find(|p| p.letter() == letter.to_ascii_uppercase())
+	}
+}
+
+/// The name of the tile, as `e4`.
+pub fn tile_name((x, y): Pos) -> String {
+	format!("{}{}", (b'a' + x as u8) as char, y + 1)
+}
+
+/// The position of the tile of the given name, as `e4`.
+pub fn parse_tile(name: &str) -> Option<Pos> {
+	match name.as_bytes() {
+		[file @ b'a'..=b'h', rank @ b'1'..=b'8'] => {
+			Some(((file - b'a') as usize, (rank - b'1') as usize))
+		}
+		_ => None,
+	}
+}
+
+impl Position {
+	/// The position in Forsyth–Edwards Notation.
+	///
+	/// Castling rights are written as `KQkq` when the rooks are the outermost ones, and with the
+	/// file of the rook otherwise (as in Shredder-FEN), which only happens in Chess960.
+	pub fn to_fen(&self) -> String {
+		let mut fen = String::new();
+		for y in (0..8).rev() {
+			let mut empty = 0;
+			for x in 0..8 {
+				match self[(x, y)] {
+					None => empty += 1,
+					Some(Tile(piece, color)) => {
+						if empty > 0 {
+							fen += &empty.to_string();
+							empty = 0;
+						}
+						fen.push(match color {
+							Color::White => piece.letter(),
+							Color::Black => piece.letter().to_ascii_lowercase(),
+						});
+					}
+				}
+			}
+			if empty > 0 {
+				fen += &empty.to_string();
+			}
+			if y > 0 {
+				fen.push('/');
+			}
+		}
+		fen.push(' ');
+		fen.push(if self.player() == Color::White { 'w' } else { 'b' });
+		fen.push(' ');
+		let mut castling = String::new();
+		for color in [Color::White, Color::Black] {
+			for side in [CastlingSide::King, CastlingSide::Queen] {
+				let Some(file) = self.castling(color, side) else {
+					continue;
+				};
+				let letter = if self.outermost_rook(color, side) == Some(file) {
+					if side == CastlingSide::King {
+						'K'
+					} else {
+						'Q'
+					}
+				} else {
+					(b'A' + file as u8) as char
+				};
+				castling.push(match color {
+					Color::White => letter,
+					Color::Black => letter.to_ascii_lowercase(),
+				});
+			}
+		}
+		fen += if castling.is_empty() { "-" } else { &castling };
+		fen.push(' ');
+		fen += &self.en_passant().map_or("-".to_owned(), tile_name);
+		fen + &format!(" {} {}", self.halfmove_clock(), self.fullmove_number())
+	}
+
+	/// Reads a position in Forsyth–Edwards Notation. The clocks can be omitted, and the castling
+	/// rights can also be written with the files of the rooks (as in Shredder-FEN and X-FEN).
+	///
+	/// The position must be valid: one king for each color, no pawn on the first or last rank,
+	/// and the player that just played isn't in check.
+	pub fn from_fen(fen: &str) -> Result<Self, ParseError> {
+		let fields: Vec<_> = fen.split_whitespace().collect();
+		if !(4..=6).contains(&fields.len()) {
+			return error(format!("A FEN has 4 to 6 fields, got {}", fields.len()));
+		}
+		let mut position = Position::empty();
+
+		let ranks: Vec<_> = fields[0].split('/').collect();
+		if ranks.len() != 8 {
+			return error(format!("The board has 8 ranks, got {}", ranks.len()));
+		}
+		for (i, rank) in ranks.iter().enumerate() {
+			let y = 7 - i;
+			let mut x = 0;
+			for c in rank.chars() {
+				if let Some(empty) = c.to_digit(10) {
+					x += empty as usize;
+					continue;
+				}
+				let Some(piece) = Piece::from_letter(c) else {
+					return error(format!("Invalid piece {:?}", c));
+				};
+				if x >= 8 {
+					return error(format!("The rank {} has more than 8 tiles", y + 1));
+				}
+				let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+				position.set((x, y), Some(Tile(piece, color)));
+				x += 1;
+			}
+			if x != 8 {
+				return error(format!("The rank {} doesn't have 8 tiles", y + 1));
+			}
+		}
+
+		position.set_player(match fields[1] {
+			"w" => Color::White,
+			"b" => Color::Black,
+			p => return error(format!("Invalid player {:?}", p)),
+		});
+
+		if fields[2] != "-" {
+			for c in fields[2].chars() {
+				let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+				let king = position.king(color).map(|(x, _)| x);
+				let (side, file) = match c.to_ascii_uppercase() {
+					'K' => (CastlingSide::King, position.outermost_rook(color, CastlingSide::King)),
+					'Q' => {
+						(CastlingSide::Queen, position.outermost_rook(color, CastlingSide::Queen))
+					}
+					f @ 'A'..='H' => {
+						let file = (f as u8 - b'A') as usize;
+						let side = if king.is_some_and(|k| k < file) {
+							CastlingSide::King
+						} else {
+							CastlingSide::Queen
+						};
+						(side, Some(file))
+					}
+					_ => return error(format!("Invalid castling right {:?}", c)),
+				};
+				let Some(file) = file.filter(|_| king.is_some()) else {
+					return error(format!("No king and rook to castle for {:?}", c));
+				};
+				position.set_castling(color, side, Some(file));
+			}
+		}
+
+		if fields[3] != "-" {
+			let Some(tile) = parse_tile(fields[3]) else {
+				return error(format!("Invalid en passant tile {:?}", fields[3]));
+			};
+			position.set_en_passant(Some(tile));
+		}
+
+		let clock = |i: usize, default: u32| {
+			fields.get(i).map_or(Ok(default), |f| {
+				f.parse().map_err(|_| ParseError(format!("Invalid move counter {:?}", f)))
+			})
+		};
+		position.set_clocks(clock(4, 0)?, clock(5, 1)?.max(1));
+
+		position.validate().map_err(|e| ParseError(e.to_owned()))?;
+		Ok(position)
+	}
+
+	/// The file of the outermost rook of the given color on its back rank and on the given side of
+	/// its king.
+	pub(super) fn outermost_rook(&self, color: Color, side: CastlingSide) -> Option<usize> {
+		let (king, _) = self.king(color)?;
+		let rook = |x: &usize| self[(*x, color.back_rank())] == Some(Tile(Piece::Rook, color));
+		match side {
+			CastlingSide::King => (king + 1..8).rev().find(rook),
+			CastlingSide::Queen => (0..king).find(rook),
+		}
+	}
+
+	/// The move in Standard Algebraic Notation, as `Nbd7`, `exd5`, `O-O` or `e8=Q+`. The move
+	/// must be legal.
+	pub fn san(&self, mv: &Move) -> String {
+		let mut san = self.san_without_check(mv, &self.legal_moves());
+		let after = self.after(mv);
+		if after.in_check() {
+			san.push(if after.legal_moves().is_empty() { '#' } else { '+' });
+		}
+		san
+	}
+
+	/// The SAN of the move, without the check or checkmate indicator. `legal` are all the legal
+	/// moves of the position, to know if the move is ambiguous.
+	fn san_without_check(&self, mv: &Move, legal: &[Move]) -> String {
+		match self.castling_side(mv) {
+			Some(CastlingSide::King) => return "O-O".to_owned(),
+			Some(CastlingSide::Queen) => return "O-O-O".to_owned(),
+			None => (),
+		}
+		let Some(tile) = self[mv.from] else {
+			return String::new();
+		};
+		let capture = self[mv.to].is_some() || tile.0 == Piece::Pawn && mv.from.0 != mv.to.0;
+		let from = tile_name(mv.from);
+		let mut san = String::new();
+		if tile.0 == Piece::Pawn {
+			if capture {
+				san += &from[..1];
+			}
+		} else {
+			san.push(tile.0.letter());
+			let others: Vec<_> = legal
+				.iter()
+				.filter(|m| {
+					m.to == mv.to
+						&& m.from != mv.from
+						&& self[m.from] == Some(tile)
+						&& self.castling_side(m).is_none()
+				})
+				.collect();
+			if !others.is_empty() {
+				if others.iter().all(|m| m.from.0 != mv.from.0) {
+					san += &from[..1];
+				} else if others.iter().all(|m| m.from.1 != mv.from.1) {
+					san += &from[1..];
+				} else {
+					san += &from;
+				}
+			}
+		}
+		if capture {
+			san.push('x');
+		}
+		san += &tile_name(mv.to);
+		if let Some(promotion) = mv.promotion {
+			san.push('=');
+			san.push(promotion.letter());
+		}
+		san
+	}
+
+	/// Reads a legal move in Standard Algebraic Notation. Check indicators and annotations (as
+	/// `!?`) are ignored, castling can be written with zeros and promotions without `=`.
+	pub fn parse_san(&self, san: &str) -> Result<Move, ParseError> {
+		let normalize = |san: &str| {
+			san.trim_end_matches(['+', '#', '!', '?']).replace('0', "O").replace('=', "")
+		};
+		let wanted = normalize(san.trim());
+		let legal = self.legal_moves();
+		legal
+			.iter()
+			.find(|mv| normalize(&self.san_without_check(mv, &legal)) == wanted)
+			.copied()
+			.ok_or_else(|| ParseError(format!("Illegal or invalid move {:?}", san)))
+	}
+
+	/// The move in the long algebraic notation of the UCI protocol, as `e2e4` or `e7e8q`. Castling
+	/// is written as the king moving two tiles when the king and rook start on their usual tiles,
+	/// and as the king taking its rook otherwise (as in Chess960).
+	pub fn uci(&self, mv: &Move) -> String {
+		let mut to = mv.to;
+		if let Some(side) = self.castling_side(mv) {
+			let corner = if side == CastlingSide::King { 7 } else { 0 };
+			if mv.from.0 == 4 && mv.to.0 == corner {
+				to.0 = side.king_file();
+			}
+		}
+		let promotion = mv.promotion.map(|p| p.letter().to_ascii_lowercase());
+		tile_name(mv.from) + &tile_name(to) + &promotion.map(String::from).unwrap_or_default()
+	}
+
+	/// Reads a legal move in the long algebraic notation of the UCI protocol. Castling can be
+	/// written either as the king moving two tiles or as the king taking its rook.
+	pub fn parse_uci(&self, uci: &str) -> Result<Move, ParseError> {
+		let uci = uci.trim();
+		self.legal_moves()
+			.into_iter()
+			.find(|mv| {
+				self.uci(mv) == uci
+					|| self.castling_side(mv).is_some()
+						&& tile_name(mv.from) + &tile_name(mv.to) == uci
+			})
+			.ok_or_else(|| ParseError(format!("Illegal or invalid move {:?}", uci)))
+	}
+}
+
+impl FromStr for Position {
+	type Err = ParseError;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Self::from_fen(s)
+	}
+}
+
+/// The result of a game as written in PGN.
+fn pgn_result(outcome: Option<Outcome>) -> &'static str {
+	match outcome {
+		None => "*",
+		Some(Outcome::Draw(_)) => "1/2-1/2",
+		Some(outcome) => outcome.score(),
+	}
+}
+
+/// A token of the move text of a PGN.
+#[derive(Debug, PartialEq, Eq)]
+enum Token<'a> {
+	/// A tag pair, with its name and value
+	Tag(&'a str, String),
+	/// Anything else that isn't a comment, a variation nor an annotation
+	Symbol(&'a str),
+}
+
+/// Splits a PGN into tokens, ignoring the comments, variations and annotations.
+fn tokenize(pgn: &str) -> Result<Vec<Token<'_>>, ParseError> {
+	let mut tokens = vec![];
+	let mut variations = 0;
+	let mut rest = pgn;
+	while let Some(c) = rest.chars().next() {
+		let end_of = |pat: char| rest.find(pat).unwrap_or(rest.len());
+		let len = match c {
+			'{' => end_of('}') + 1,
+			';' | '%' => end_of('\n'),
+			'(' => {
+				variations += 1;
+				1
+			}
+			')' => {
+				variations -= 1;
+				1
+			}
+			'[' if variations == 0 => {
+				let len = end_of(']');
+				let tag = rest[1..len].trim();
+				let (name, value) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+				let value =
+					value.trim().trim_matches('"').replace("\\\"", "\"").replace("\\\\", "\\");
+				tokens.push(Token::Tag(name, value));
+				len + 1
+			}
+			c if c.is_whitespace() => c.len_utf8(),
+			_ => {
+				let len = rest.find(|c: char| c.is_whitespace() || "{}();[".contains(c));
+				let len = len.unwrap_or(rest.len());
+				if variations == 0 && !rest.starts_with('$') {
+					tokens.push(Token::Symbol(&rest[..len]));
+				}
+				len
+			}
+		};
+		if variations < 0 {
+			return error("Unexpected end of variation".to_owned());
+		}
+		rest = rest.get(len..).unwrap_or("");
+	}
+	Ok(tokens)
+}
+
+impl History {
+	/// The game in Portable Game Notation. As terminity doesn't know who plays, most tags are
+	/// unknown.
+	pub fn to_pgn(&self) -> String {
+		let result = pgn_result(self.outcome());
+		let mut tags = vec![
+			("Event", "?".to_owned()),
+			("Site", "?".to_owned()),
+			("Date", "????.??.??".to_owned()),
+			("Round", "?".to_owned()),
+			("White", "?".to_owned()),
+			("Black", "?".to_owned()),
+			("Result", result.to_owned()),
+		];
+		if *self.start() != Position::default() {
+			tags.push(("SetUp", "1".to_owned()));
+			tags.push(("FEN", self.start().to_fen()));
+		}
+		let mut pgn: String =
+			tags.iter().map(|(name, value)| format!("[{} \"{}\"]\n", name, value)).collect();
+		pgn.push('\n');
+
+		// The move text, with lines of at most 80 characters
+		let mut line = String::new();
+		let mut words = vec![];
+		for (i, (position, mv)) in self.positions().iter().zip(self.moves()).enumerate() {
+			if position.player() == Color::White {
+				words.push(format!("{}.", position.fullmove_number()));
+			} else if i == 0 {
+				words.push(format!("{}...", position.fullmove_number()));
+			}
+			words.push(position.san(mv));
+		}
+		words.push(result.to_owned());
+		for word in words {
+			if !line.is_empty() && line.len() + 1 + word.len() > 80 {
+				pgn += &line;
+				pgn.push('\n');
+				line.clear();
+			}
+			if !line.is_empty() {
+				line.push(' ');
+			}
+			line += &word;
+		}
+		pgn + &line + "\n"
+	}
+
+	/// Reads the first game of a PGN. Comments, variations and annotations are ignored. The game
+	/// starts from the position of the `FEN` tag if any.
+	pub fn from_pgn(pgn: &str) -> Result<Self, ParseError> {
+		let mut history = None;
+		let mut start = Position::default();
+		for token in tokenize(pgn)? {
+			match token {
+				Token::Tag(_, _) if history.is_some() => break,
+				Token::Tag("FEN", fen) => start = fen.parse()?,
+				Token::Tag(_, _) => (),
+				Token::Symbol("1-0" | "0-1" | "1/2-1/2" | "*") => break,
+				Token::Symbol(symbol) => {
+					let history = history.get_or_insert_with(|| History::new(start.clone()));
+					// Removing any move number
+					let san = symbol.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+					if san.is_empty() {
+						continue;
+					}
+					let mv = history.position().parse_san(san)?;
+					if history.play(mv).is_err() {
+						return error(format!("The game is over before {:?}", san));
+					}
+				}
+			}
+		}
+		Ok(history.unwrap_or_else(|| History::new(start)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+	fn play(history: &mut History, moves: &str) {
+		for san in moves.split_whitespace() {
+			history.play(history.position().parse_san(san).unwrap()).unwrap();
+		}
+	}
+
+	#[test]
+	fn fen() {
+		let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+		assert_eq!(Position::default().to_fen(), start);
+		assert_eq!(Position::from_fen(start), Ok(Position::default()));
+		assert_eq!(Position::from_fen(KIWIPETE).unwrap().to_fen(), KIWIPETE);
+
+		let mut history = History::default();
+		play(&mut history, "e4 c5 e5 d5");
+		let fen = "rnbqkbnr/pp2pppp/8/2ppP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3";
+		assert_eq!(history.position().to_fen(), fen);
+		assert_eq!(Position::from_fen(fen).as_ref(), Ok(history.position()));
+		// The clocks are optional
+		let position = Position::from_fen("4k3/8/8/8/8/8/8/R3K3 b Q -").unwrap();
+		assert_eq!(position.to_fen(), "4k3/8/8/8/8/8/8/R3K3 b Q - 0 1");
+		// Rooks that aren't the outermost ones
+		let position = Position::from_fen("1r2k1r1/8/8/8/8/8/8/RR2K2R w BHg - 0 1").unwrap();
+		assert_eq!(position.castling(Color::White, CastlingSide::Queen), Some(1));
+		assert_eq!(position.castling(Color::Black, CastlingSide::King), Some(6));
+		assert_eq!(position.to_fen(), "1r2k1r1/8/8/8/8/8/8/RR2K2R w KBk - 0 1");
+	}
+
+	#[test]
+	fn invalid_fen() {
+		for fen in [
+			"",
+			"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1",
+			"rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+			"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+			"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN1 w KQkq - 0 1",
+			"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQ1BNR w - - 0 1",
+			"4k3/8/8/8/8/8/8/4K2p w - - 0 1",
+			"4k3/4R3/8/8/8/8/8/4K3 w - - 0 1",
+			"4k3/8/8/8/8/8/8/4K3 w - e9 0 1",
+		] {
+			assert!(Position::from_fen(fen).is_err(), "{}", fen);
+		}
+	}
+
+	#[test]
+	fn san() {
+		let position = Position::from_fen(KIWIPETE).unwrap();
+		let san: Vec<_> = position.legal_moves().iter().map(|mv| position.san(mv)).collect();
+		for expected in ["O-O", "O-O-O", "Nxf7", "Qxf6", "Bxa6", "dxe6", "Nb1", "gxh3"] {
+			assert!(san.contains(&expected.to_owned()), "{} not in {:?}", expected, san);
+		}
+		assert_eq!(position.parse_san("0-0-0!?"), Ok(Move::new((4, 0), (0, 0))));
+		assert!(position.parse_san("Ke3").is_err());
+
+		// Disambiguation by file, then by rank
+		let position = Position::from_fen("4k3/8/8/R7/8/8/4K3/R6R w - - 0 1").unwrap();
+		assert_eq!(position.san(&Move::new((0, 0), (3, 0))), "Rad1");
+		assert_eq!(position.san(&Move::new((0, 4), (0, 2))), "R5a3");
+		assert_eq!(position.san(&Move::new((0, 4), (1, 4))), "Rb5");
+		assert_eq!(position.parse_san("Rhf1"), Ok(Move::new((7, 0), (5, 0))));
+		assert!(position.parse_san("Rd1").is_err());
+
+		let position = Position::from_fen("4k3/1P6/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+		let promotion = Move { promotion: Some(Piece::Rook), ..Move::new((1, 6), (1, 7)) };
+		assert_eq!(position.san(&promotion), "b8=R+");
+		assert_eq!(position.parse_san("b8R"), Ok(promotion));
+		assert_eq!(position.san(&Move::new((0, 0), (0, 7))), "Ra8+");
+		let position = Position::from_fen("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+		assert_eq!(position.san(&Move::new((0, 0), (0, 7))), "Ra8#");
+	}
+
+	#[test]
+	fn uci() {
+		let position = Position::from_fen(KIWIPETE).unwrap();
+		let castling = Move::new((4, 0), (7, 0));
+		assert_eq!(position.uci(&castling), "e1g1");
+		assert_eq!(position.parse_uci("e1g1"), Ok(castling));
+		assert_eq!(position.parse_uci("e1h1"), Ok(castling));
+		assert_eq!(position.parse_uci("d5e6"), Ok(Move::new((3, 4), (4, 5))));
+		assert!(position.parse_uci("e1e3").is_err());
+
+		let position = Position::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+		let promotion = Move { promotion: Some(Piece::Knight), ..Move::new((1, 6), (1, 7)) };
+		assert_eq!(position.uci(&promotion), "b7b8n");
+		assert_eq!(position.parse_uci("b7b8n"), Ok(promotion));
+		assert!(position.parse_uci("b7b8").is_err());
+	}
+
+	#[test]
+	fn pgn() {
+		let mut history = History::default();
+		play(
+			&mut history,
+			"e4 e5 Nf3 Nc6 Bb5 a6 Ba4 Nf6 O-O Be7 Re1 b5 Bb3 d6 c3 O-O h3 Nb8 d4 Nbd7",
+		);
+		let pgn = history.to_pgn();
+		assert!(pgn.starts_with("[Event \"?\"]\n[Site \"?\"]\n"));
+		assert!(
+			pgn.contains("[Result \"*\"]\n\n1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4 Nf6 5. O-O Be7")
+		);
+		assert!(pgn.lines().all(|l| l.len() <= 80));
+		assert!(pgn.ends_with(" Nbd7 *\n"));
+		let read = History::from_pgn(&pgn).unwrap();
+		assert_eq!(read.moves(), history.moves());
+
+		// From a custom position, black to play first
+		let fen = "4k3/8/8/8/8/8/5PPP/3rR1K1 b - - 0 30";
+		let mut history = History::new(fen.parse().unwrap());
+		play(&mut history, "Rxe1#");
+		let pgn = history.to_pgn();
+		assert!(pgn.contains(&format!("[SetUp \"1\"]\n[FEN \"{}\"]\n\n30... Rxe1# 0-1\n", fen)));
+		assert_eq!(History::from_pgn(&pgn).unwrap().position(), history.position());
+	}
+
+	#[test]
+	fn pgn_import() {
+		let pgn = r#"[Event "Casual \"game\""]
+[White "Someone"]
+
+1. e4 {Best by test} e5 (1... c5 2. Nf3 (2. c3) d6) 2. Nf3 $1 Nc6 ; The most common
+3.Bb5 a6?! 1/2-1/2
+
+[Event "Next game"]
+
+1. d4"#;
+		let history = History::from_pgn(pgn).unwrap();
+		let san: Vec<_> =
+			history.positions().iter().zip(history.moves()).map(|(p, m)| p.san(m)).collect();
+		assert_eq!(san, ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]);
+
+		assert!(History::from_pgn("1. e4 e5 2. Ke3").is_err());
+		assert!(History::from_pgn("1. f3 e5 2. g4 Qh4# 3. a3").is_err());
+		assert!(History::from_pgn("1. e4 e5)").is_err());
+		assert_eq!(History::from_pgn("").unwrap().moves(), []);
+	}
+}
@@ -0,0 +1,1064 @@
+//! The rules of chess: positions, legal moves and how a game ends.
+//!
+//! Positions are indexed by `(x, y)` coordinates, `(0, 0)` being the `a1` tile and `(7, 7)` the
+//! `h8` one. Castling is represented by the king moving onto its own rook, which is unambiguous
+//! even when the king and the rook don't start on their usual tiles.
+
+use std::ops::Index;
+
+use rand::Rng;
+
+/// A position on the board, as `(x, y)`.
+pub type Pos = (usize, usize);
+
+/// A piece of a given color, as on a board's tile.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Tile(pub Piece, pub Color);
+
+/// The color of a piece or of a player.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Color {
+	Black,
+	White,
+}
+
+/// A kind of piece. Its value is the unicode character of the black piece.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[repr(u16)]
+pub enum Piece {
+	King = '\u{2654}' as u16,
+	Queen = '\u{2655}' as u16,
+	Rook = '\u{2656}' as u16,
+	Bishop = '\u{2657}' as u16,
+	Knight = '\u{2658}' as u16,
+	Pawn = '\u{2659}' as u16,
+}
+
+/// The pieces a pawn can be promoted to.
+pub const PROMOTIONS: [Piece; 4] = [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight];
+
+/// The side a king castles on.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CastlingSide {
+	/// Towards the `h` file.
+	King = 0,
+	/// Towards the `a` file.
+	Queen = 1,
+}
+
+/// A move. Castling is represented by the king moving onto its own rook.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Move {
+	pub from: Pos,
+	pub to: Pos,
+	/// The piece a pawn reaching the last rank is promoted to.
+	pub promotion: Option<Piece>,
+}
+
+/// How a game ended.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Outcome {
+	/// The given color won by checkmating the other.
+	Checkmate(Color),
+	/// The given color won because the other ran out of time.
+	Timeout(Color),
+	/// The game is drawn.
+	Draw(DrawReason),
+}
+
+/// Why a game is drawn.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DrawReason {
+	/// The player to move has no legal move but isn't in check.
+	Stalemate,
+	/// Fifty moves were played by each player without any capture nor pawn move.
+	FiftyMoves,
+	/// The same position occurred three times.
+	Repetition,
+	/// No player has enough pieces left to checkmate.
+	InsufficientMaterial,
+	/// A player ran out of time, but the other one doesn't have enough pieces left to checkmate.
+	TimeoutVsInsufficientMaterial,
+}
+
+/// The error of a move that can't be played, either because it is illegal or because the game is
+/// over.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct IllegalMove;
+
+/// The state of a game at a given time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+	/// The tiles, indexed by `y` then `x`.
+	tiles: [[Option<Tile>; 8]; 8],
+	/// The color of the next player to play
+	player: Color,
+	/// For each color (white then black) and each side (king then queen), the file of the rook
+	/// that can castle, if castling is still allowed.
+	castling: [[Option<usize>; 2]; 2],
+	/// The tile a pawn skipped over with a double step on the last move, where it can be taken en
+	/// passant.
+	en_passant: Option<Pos>,
+	/// The number of half-moves since the last capture or pawn move.
+	halfmove_clock: u32,
+	/// The number of the current move, starting at 1 and incremented after black plays.
+	fullmove_number: u32,
+}
+
+/// A game: the position it started from, and the moves played since then.
+#[derive(Debug, Clone)]
+pub struct History {
+	/// The positions of the game, the first being the starting one, and the last the current one
+	positions: Vec<Position>,
+	moves: Vec<Move>,
+	outcome: Option<Outcome>,
+}
+
+impl Color {
+	/// swaps the color of self
+	pub fn swap(&mut self) {
+		*self = self.opponent();
+	}
+	/// The other color.
+	pub fn opponent(self) -> Self {
+		match self {
+			Self::Black => Self::White,
+			Self::White => Self::Black,
+		}
+	}
+	/// The index of the color in the arrays indexed by color (white first).
+	pub(super) fn index(self) -> usize {
+		match self {
+			Self::White => 0,
+			Self::Black => 1,
+		}
+	}
+	/// The rank the pieces of this color start on.
+	pub fn back_rank(self) -> usize {
+		match self {
+			Self::White => 0,
+			Self::Black => 7,
+		}
+	}
+	/// The direction the pawns of this color go to on the `y` axis.
+	fn forward(self) -> isize {
+		match self {
+			Self::White => 1,
+			Self::Black => -1,
+		}
+	}
+}
+
+impl Piece {
+	/// The chess symbol of the piece: filled for white, outlined for black.
+	pub fn to_char(self, color: Color) -> char {
+		// The filled symbols follow the outlined ones
+		let offset = match color {
+			Color::White => 6,
+			Color::Black => 0,
+		};
+		char::from_u32(self as u32 + offset).expect("Chess symbols are valid characters")
+	}
+}
+
+impl CastlingSide {
+	/// The file the king ends on after castling on this side.
+	pub fn king_file(self) -> usize {
+		match self {
+			Self::King => 6,
+			Self::Queen => 2,
+		}
+	}
+	/// The file the rook ends on after castling on this side.
+	pub fn rook_file(self) -> usize {
+		match self {
+			Self::King => 5,
+			Self::Queen => 3,
+		}
+	}
+}
+
+impl Move {
+	/// A move that isn't a promotion.
+	pub fn new(from: Pos, to: Pos) -> Self {
+		Self { from, to, promotion: None }
+	}
+}
+
+impl Outcome {
+	/// The result of the game, as written in chess notations.
+	pub fn score(&self) -> &'static str {
+		match self {
+			Self::Checkmate(Color::White) | Self::Timeout(Color::White) => "1-0",
+			Self::Checkmate(Color::Black) | Self::Timeout(Color::Black) => "0-1",
+			Self::Draw(_) => "½-½",
+		}
+	}
+}
+
+impl Tile {
+	/// Checks if the current Piece can make said move.
+	/// Implicitly checks if the aimed position can be eaten or not.
+	/// Doesn't check the color of the aimed piece though, this can be made easily beforehand.
+	/// Special moves (castling and en passant) aren't handled here.
+	pub fn move_valid(&self, curr_pos: &Pos, new_pos: &Pos, board: &Position) -> bool {
+		curr_pos != new_pos
+			&& match self.0 {
+				Piece::King => {
+					(curr_pos.0).abs_diff(new_pos.0) <= 1 && (curr_pos.1).abs_diff(new_pos.1) <= 1
+				}
+				Piece::Rook => {
+					if curr_pos.0 == new_pos.0 {
+						let max = new_pos.1.max(curr_pos.1);
+						let min = new_pos.1.min(curr_pos.1);
+						// Check if there's no piece between curr_pos and new_pos, both excluded.
+						((min + 1)..max).all(|y| board[(curr_pos.0, y)].is_none())
+					} else if curr_pos.1 == new_pos.1 {
+						let max = new_pos.0.max(curr_pos.0);
+						let min = new_pos.0.min(curr_pos.0);
+						// Ditto
+						((min + 1)..max).all(|x| board[(x, curr_pos.1)].is_none())
+					} else {
+						false
+					}
+				}
+				Piece::Bishop => {
+					let dx = (curr_pos.0).abs_diff(new_pos.0);
+					let dy = (curr_pos.1).abs_diff(new_pos.1);
+					// Moves as much on x axis as on y axis
+					dx == dy
+					// No one in the way
+						&& (1..dx)
+							// Create positions
+							.map(|d| {
+								(
+									(curr_pos.0 as isize)
+										+ d as isize * if curr_pos.0 < new_pos.0 { 1 } else { -1 },
+									(curr_pos.1 as isize)
+										+ d as isize * if curr_pos.1 < new_pos.1 { 1 } else { -1 },
+								)
+							})
+							.all(|pos| board[(pos.0 as usize, pos.1 as usize)].is_none())
+				}
+				Piece::Queen => {
+					Tile(Piece::Rook, self.1).move_valid(curr_pos, new_pos, board)
+						|| Tile(Piece::Bishop, self.1).move_valid(curr_pos, new_pos, board)
+				}
+				Piece::Knight => {
+					let dx = (curr_pos.0).abs_diff(new_pos.0);
+					let dy = (curr_pos.1).abs_diff(new_pos.1);
+					dx == 1 && dy == 2 || dx == 2 && dy == 1
+				}
+				Piece::Pawn => {
+					let dx = (curr_pos.0).abs_diff(new_pos.0);
+					let dy = new_pos.1 as isize - curr_pos.1 as isize;
+					let forward = self.1.forward();
+					// Eating
+					dx == 1 && dy == forward && board[*new_pos].is_some()
+					// Moving straight
+					|| dx == 0 && dy == forward && board[*new_pos].is_none()
+					// First move: the pawn is still on its starting rank
+					|| dx == 0
+						&& dy == 2 * forward
+						&& curr_pos.1 == self.1.back_rank().abs_diff(1)
+						&& board[(new_pos.0, (curr_pos.1 + new_pos.1) / 2)].is_none()
+						&& board[*new_pos].is_none()
+				}
+			}
+	}
+
+	/// Checks if the piece attacks the aimed position, whether there is a piece on it or not.
+	fn attacks(&self, curr_pos: &Pos, new_pos: &Pos, board: &Position) -> bool {
+		match self.0 {
+			Piece::Pawn => {
+				(curr_pos.0).abs_diff(new_pos.0) == 1
+					&& new_pos.1 as isize - curr_pos.1 as isize == self.1.forward()
+			}
+			_ => self.move_valid(curr_pos, new_pos, board),
+		}
+	}
+}
+
+impl Default for Position {
+	/// The standard starting position.
+	fn default() -> Self {
+		use Color::*;
+		use Piece::*;
+		let back_rank = |color| {
+			[Rook, Knight, Bishop, Queen, King, Bishop, Knight, Rook].map(|p| Some(Tile(p, color)))
+		};
+		Self {
+			tiles: [
+				back_rank(White),
+				[Some(Tile(Pawn, White)); 8],
+				Default::default(),
+				Default::default(),
+				Default::default(),
+				Default::default(),
+				[Some(Tile(Pawn, Black)); 8],
+				back_rank(Black),
+			],
+			player: White,
+			castling: [[Some(7), Some(0)]; 2],
+			en_passant: None,
+			halfmove_clock: 0,
+			fullmove_number: 1,
+		}
+	}
+}
+
+impl Index<Pos> for Position {
+	type Output = Option<Tile>;
+	fn index(&self, (x, y): Pos) -> &Self::Output {
+		&self.tiles[y][x]
+	}
+}
+
+impl Position {
+	/// A position without any piece, white to play. Pieces are then added with
+	/// [`set`](Self::set).
+	pub fn empty() -> Self {
+		Self {
+			tiles: Default::default(),
+			player: Color::White,
+			castling: [[None; 2]; 2],
+			en_passant: None,
+			halfmove_clock: 0,
+			fullmove_number: 1,
+		}
+	}
+
+	/// The starting position of Chess960 (Fischer random chess) of the given number, from 0 to 959,
+	/// in the standard numbering where 518 is the classic starting position. The pieces of the
+	/// back ranks are shuffled, the bishops on tiles of different colors and the king between the
+	/// rooks.
+	pub fn chess960(number: u16) -> Option<Self> {
+		use Piece::*;
+		if number >= 960 {
+			return None;
+		}
+		let mut n = number as usize;
+		let mut rank = [None; 8];
+		// One bishop on each color
+		rank[2 * (n % 4) + 1] = Some(Bishop);
+		n /= 4;
+		rank[2 * (n % 4)] = Some(Bishop);
+		n /= 4;
+		// The other pieces are placed on the remaining tiles
+		let mut place = |piece, index| {
+			let x = (0..8).filter(|&x| rank[x].is_none()).nth(index).unwrap();
+			rank[x] = Some(piece);
+		};
+		place(Queen, n % 6);
+		n /= 6;
+		const KNIGHTS: [(usize, usize); 10] =
+			[(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)];
+		let (first, second) = KNIGHTS[n];
+		place(Knight, second);
+		place(Knight, first);
+		for piece in [Rook, King, Rook] {
+			place(piece, 0);
+		}
+
+		let mut position = Self::default();
+		let rooks: Vec<_> = (0..8).filter(|&x| rank[x] == Some(Rook)).collect();
+		for color in [Color::White, Color::Black] {
+			for (x, piece) in rank.iter().enumerate() {
+				position.set((x, color.back_rank()), piece.map(|piece| Tile(piece, color)));
+			}
+			position.set_castling(color, CastlingSide::King, Some(rooks[1]));
+			position.set_castling(color, CastlingSide::Queen, Some(rooks[0]));
+		}
+		Some(position)
+	}
+
+	/// A random starting position of Chess960.
+	pub fn random_chess960(rng: &mut impl Rng) -> Self {
+		Self::chess960(rng.gen_range(0..960)).unwrap()
+	}
+
+	/// Puts the given piece (or nothing) on the given tile.
+	pub fn set(&mut self, (x, y): Pos, tile: Option<Tile>) {
+		self.tiles[y][x] = tile;
+	}
+
+	/// Sets the color of the next player to play.
+	pub fn set_player(&mut self, player: Color) {
+		self.player = player;
+	}
+
+	/// Sets the file of the rook that can castle with the king of the given color on the given
+	/// side, or forbids castling with `None`.
+	pub fn set_castling(&mut self, color: Color, side: CastlingSide, rook_file: Option<usize>) {
+		self.castling[color.index()][side as usize] = rook_file;
+	}
+
+	/// Sets the tile a pawn can be taken en passant on.
+	pub fn set_en_passant(&mut self, en_passant: Option<Pos>) {
+		self.en_passant = en_passant;
+	}
+
+	/// Sets the half-moves since the last capture or pawn move, and the number of the current
+	/// move.
+	pub fn set_clocks(&mut self, halfmove_clock: u32, fullmove_number: u32) {
+		self.halfmove_clock = halfmove_clock;
+		self.fullmove_number = fullmove_number;
+	}
+
+	/// Checks that the position can be played from: one king of each color, no pawn on the first
+	/// or last rank, the player that just played isn't in check, and the castling rights and en
+	/// passant tile match the pieces.
+	pub fn validate(&self) -> Result<(), &'static str> {
+		for color in [Color::White, Color::Black] {
+			if self.pieces().filter(|(_, t)| *t == Tile(Piece::King, color)).count() != 1 {
+				return Err("Each player must have exactly one king");
+			}
+			for side in [CastlingSide::King, CastlingSide::Queen] {
+				let Some(rook) = self.castling(color, side) else {
+					continue;
+				};
+				let (king, y) = self.king(color).unwrap();
+				let on_side = match side {
+					CastlingSide::King => king < rook,
+					CastlingSide::Queen => rook < king,
+				};
+				if y != color.back_rank()
+					|| !on_side || self[(rook, y)] != Some(Tile(Piece::Rook, color))
+				{
+					return Err("Castling needs the king and the rook on their back rank");
+				}
+			}
+		}
+		if self.pieces().any(|((_, y), t)| t.0 == Piece::Pawn && (y == 0 || y == 7)) {
+			return Err("Pawns can't be on the first nor the last rank");
+		}
+		let waiting = self.player.opponent();
+		if self.attacked(self.king(waiting).unwrap(), self.player) {
+			return Err("The player that just played can't be in check");
+		}
+		if let Some((x, y)) = self.en_passant {
+			// The tile behind a pawn of the player that just played, on its fourth rank
+			let pawn_y = y as isize + waiting.forward();
+			if y != (waiting.back_rank() as isize + 2 * waiting.forward()) as usize
+				|| self[(x, y)].is_some()
+				|| self[(x, pawn_y as usize)] != Some(Tile(Piece::Pawn, waiting))
+			{
+				return Err("No pawn can be taken en passant there");
+			}
+		}
+		Ok(())
+	}
+
+	/// The color of the next player to play.
+	pub fn player(&self) -> Color {
+		self.player
+	}
+
+	/// The file of the rook that can castle with the king of the given color on the given side,
+	/// if castling is still allowed.
+	pub fn castling(&self, color: Color, side: CastlingSide) -> Option<usize> {
+		self.castling[color.index()][side as usize]
+	}
+
+	/// The tile a pawn can be taken en passant on, if the last move was a pawn double step.
+	pub fn en_passant(&self) -> Option<Pos> {
+		self.en_passant
+	}
+
+	/// The number of half-moves since the last capture or pawn move.
+	pub fn halfmove_clock(&self) -> u32 {
+		self.halfmove_clock
+	}
+
+	/// The number of the current move, starting at 1 and incremented after black plays.
+	pub fn fullmove_number(&self) -> u32 {
+		self.fullmove_number
+	}
+
+	/// Lists all the pieces with their positions.
+	pub fn pieces(&self) -> impl Iterator<Item = (Pos, Tile)> + '_ {
+		self.tiles
+			.iter()
+			.enumerate()
+			.flat_map(|(y, e)| e.iter().enumerate().filter_map(move |(x, t)| Some(((x, y), (*t)?))))
+	}
+
+	/// The position of the king of the given color, if any.
+	pub fn king(&self, color: Color) -> Option<Pos> {
+		self.pieces().find(|(_, t)| *t == Tile(Piece::King, color)).map(|(pos, _)| pos)
+	}
+
+	/// Lists the positions of the pieces of the given color attacking the given position.
+	pub fn attackers(&self, pos: Pos, color: Color) -> Vec<Pos> {
+		self.pieces()
+			.filter(|(p, t)| t.1 == color && t.attacks(p, &pos, self))
+			.map(|(p, _)| p)
+			.collect()
+	}
+
+	/// Whether any piece of the given color attacks the given position.
+	pub fn attacked(&self, pos: Pos, color: Color) -> bool {
+		self.pieces().any(|(p, t)| t.1 == color && t.attacks(&p, &pos, self))
+	}
+
+	/// Lists the positions of the pieces that are checking the king of the player to play.
+	pub fn checkers(&self) -> Vec<Pos> {
+		match self.king(self.player) {
+			Some(king) => self.attackers(king, self.player.opponent()),
+			None => vec![],
+		}
+	}
+
+	/// Whether the king of the player to play is in check.
+	pub fn in_check(&self) -> bool {
+		self.king(self.player).is_some_and(|king| self.attacked(king, self.player.opponent()))
+	}
+
+	/// The side the given move castles on, if it is a castling allowed by the castling rights.
+	/// Whether the king can actually castle isn't checked.
+	pub fn castling_side(&self, mv: &Move) -> Option<CastlingSide> {
+		let Some(Tile(Piece::King, color)) = self[mv.from] else {
+			return None;
+		};
+		let rank = color.back_rank();
+		if self[mv.to] != Some(Tile(Piece::Rook, color)) || mv.from.1 != rank || mv.to.1 != rank {
+			return None;
+		}
+		let side = if mv.to.0 > mv.from.0 { CastlingSide::King } else { CastlingSide::Queen };
+		(self.castling(color, side) == Some(mv.to.0)).then_some(side)
+	}
+
+	/// Checks if the move follows the rules, except that it may leave the king in check.
+	pub fn pseudo_legal(&self, mv: &Move) -> bool {
+		let Some(tile) = self[mv.from] else {
+			return false;
+		};
+		if tile.1 != self.player {
+			return false;
+		}
+		if let Some(side) = self.castling_side(mv) {
+			return mv.promotion.is_none() && self.can_castle(mv, side);
+		}
+		let promoting = tile.0 == Piece::Pawn && mv.to.1 == self.player.opponent().back_rank();
+		let en_passant = tile.0 == Piece::Pawn
+			&& Some(mv.to) == self.en_passant
+			&& self[mv.to].is_none()
+			&& tile.attacks(&mv.from, &mv.to, self);
+		self[mv.to].is_none_or(|t| t.1 != tile.1)
+			&& (tile.move_valid(&mv.from, &mv.to, self) || en_passant)
+			&& if promoting {
+				mv.promotion.is_some_and(|p| PROMOTIONS.contains(&p))
+			} else {
+				mv.promotion.is_none()
+			}
+	}
+
+	/// Checks if the king can castle with the given move, on the given side.
+	fn can_castle(&self, mv: &Move, side: CastlingSide) -> bool {
+		let rank = mv.from.1;
+		let (king_to, rook_to) = (side.king_file(), side.rook_file());
+		let files = [mv.from.0, mv.to.0, king_to, rook_to];
+		let (min, max) = (*files.iter().min().unwrap(), *files.iter().max().unwrap());
+		// Only the king and the rook between all the tiles they go through
+		if (min..=max).any(|x| self[(x, rank)].is_some() && x != mv.from.0 && x != mv.to.0) {
+			return false;
+		}
+		// The king can't castle out of, through or into check. The pieces moving don't block
+		// any attack.
+		let mut without = self.clone();
+		without.tiles[rank][mv.from.0] = None;
+		without.tiles[rank][mv.to.0] = None;
+		let opponent = self.player.opponent();
+		let (min, max) = (mv.from.0.min(king_to), mv.from.0.max(king_to));
+		!self.in_check() && (min..=max).all(|x| !without.attacked((x, rank), opponent))
+	}
+
+	/// Checks if the move is legal.
+	pub fn is_legal(&self, mv: &Move) -> bool {
+		if !self.pseudo_legal(mv) {
+			return false;
+		}
+		let after = self.after(mv);
+		after.king(self.player).is_none_or(|king| !after.attacked(king, self.player.opponent()))
+	}
+
+	/// Lists all the legal moves of the player to play.
+	pub fn legal_moves(&self) -> Vec<Move> {
+		let mut moves = vec![];
+		for (from, tile) in self.pieces().filter(|(_, t)| t.1 == self.player) {
+			for to in (0..8).flat_map(|y| (0..8).map(move |x| (x, y))) {
+				if tile.0 == Piece::Pawn && to.1 == self.player.opponent().back_rank() {
+					moves.extend(PROMOTIONS.map(|p| Move { from, to, promotion: Some(p) }));
+				} else {
+					moves.push(Move::new(from, to));
+				}
+			}
+		}
+		moves.retain(|mv| self.is_legal(mv));
+		moves
+	}
+
+	/// The position after the given move. The move is supposed to be pseudo-legal.
+	pub fn after(&self, mv: &Move) -> Self {
+		let mut res = self.clone();
+		res.play_unchecked(mv);
+		res
+	}
+
+	/// Plays the given move, supposed to be pseudo-legal.
+	pub fn play_unchecked(&mut self, mv: &Move) {
+		let tile = self[mv.from].expect("No piece to move");
+		let color = tile.1;
+		let rank = color.back_rank();
+		let pawn = tile.0 == Piece::Pawn;
+		let mut capture = self[mv.to].is_some_and(|t| t.1 != color);
+
+		if let Some(side) = self.castling_side(mv) {
+			let rook = self[mv.to];
+			self.tiles[rank][mv.from.0] = None;
+			self.tiles[rank][mv.to.0] = None;
+			self.tiles[rank][side.king_file()] = Some(tile);
+			self.tiles[rank][side.rook_file()] = rook;
+		} else {
+			if pawn && Some(mv.to) == self.en_passant && mv.from.0 != mv.to.0 && !capture {
+				// En passant: the pawn taken is next to the moving one
+				self.tiles[mv.from.1][mv.to.0] = None;
+				capture = true;
+			}
+			self.tiles[mv.to.1][mv.to.0] = Some(mv.promotion.map_or(tile, |p| Tile(p, color)));
+			self.tiles[mv.from.1][mv.from.0] = None;
+		}
+
+		// Castling rights are lost when the king moves, or when the rook moves or is taken
+		if tile.0 == Piece::King {
+			self.castling[color.index()] = [None, None];
+		}
+		for (x, y) in [mv.from, mv.to] {
+			for c in [Color::White, Color::Black] {
+				if y == c.back_rank() {
+					for right in &mut self.castling[c.index()] {
+						if *right == Some(x) {
+							*right = None;
+						}
+					}
+				}
+			}
+		}
+
+		self.en_passant = (pawn && mv.from.1.abs_diff(mv.to.1) == 2)
+			.then_some((mv.from.0, (mv.from.1 + mv.to.1) / 2));
+		self.halfmove_clock = if pawn || capture { 0 } else { self.halfmove_clock + 1 };
+		if color == Color::Black {
+			self.fullmove_number += 1;
+		}
+		self.player.swap();
+	}
+
+	/// Whether no player has enough pieces to checkmate the other.
+	pub fn insufficient_material(&self) -> bool {
+		let others: Vec<_> = self.pieces().filter(|(_, t)| t.0 != Piece::King).collect();
+		match others[..] {
+			[] => true,
+			[(_, Tile(Piece::Bishop | Piece::Knight, _))] => true,
+			// Only bishops, all on tiles of the same color
+			_ => others.iter().all(|((x, y), t)| {
+				t.0 == Piece::Bishop && (x + y) % 2 == (others[0].0 .0 + others[0].0 .1) % 2
+			}),
+		}
+	}
+
+	/// Whether the given color has enough pieces left to checkmate by some series of legal moves,
+	/// that is unless it has only its king, or a single bishop or knight against a lone king.
+	pub fn can_checkmate(&self, color: Color) -> bool {
+		let pieces = |c| self.pieces().filter(move |(_, t)| t.1 == c && t.0 != Piece::King);
+		match pieces(color).map(|(_, t)| t.0).collect::<Vec<_>>()[..] {
+			[] => false,
+			[Piece::Bishop | Piece::Knight] => pieces(color.opponent()).next().is_some(),
+			_ => true,
+		}
+	}
+
+	/// How the game ends in this position, regardless of the previous positions (and thus without
+	/// checking repetitions).
+	pub fn outcome(&self) -> Option<Outcome> {
+		if self.legal_moves().is_empty() {
+			Some(if self.in_check() {
+				Outcome::Checkmate(self.player.opponent())
+			} else {
+				Outcome::Draw(DrawReason::Stalemate)
+			})
+		} else if self.halfmove_clock >= 100 {
+			Some(Outcome::Draw(DrawReason::FiftyMoves))
+		} else if self.insufficient_material() {
+			Some(Outcome::Draw(DrawReason::InsufficientMaterial))
+		} else {
+			None
+		}
+	}
+
+	/// Whether the two positions are the same regarding repetitions: same pieces on the same
+	/// tiles, same player to play, same castling rights and same en passant possibilities.
+	pub fn repeats(&self, other: &Self) -> bool {
+		self.tiles == other.tiles
+			&& self.player == other.player
+			&& self.castling == other.castling
+			&& self.en_passant_capture() == other.en_passant_capture()
+	}
+
+	/// The en passant tile, only if a pawn can actually take en passant.
+	fn en_passant_capture(&self) -> Option<Pos> {
+		self.en_passant.filter(|&to| {
+			self.pieces().any(|(from, t)| {
+				t == Tile(Piece::Pawn, self.player) && self.pseudo_legal(&Move::new(from, to))
+			})
+		})
+	}
+}
+
+impl Default for History {
+	/// A game starting from the standard starting position.
+	fn default() -> Self {
+		Self::new(Position::default())
+	}
+}
+
+impl History {
+	/// A game starting from the given position.
+	pub fn new(start: Position) -> Self {
+		let outcome = start.outcome();
+		Self { positions: vec![start], moves: vec![], outcome }
+	}
+
+	/// The current position.
+	pub fn position(&self) -> &Position {
+		self.positions.last().unwrap()
+	}
+
+	/// The position the game started from.
+	pub fn start(&self) -> &Position {
+		&self.positions[0]
+	}
+
+	/// All the positions of the game, the starting one included. The position at index `i` is the
+	/// one before the `i`-th move.
+	pub fn positions(&self) -> &[Position] {
+		&self.positions
+	}
+
+	/// The moves played.
+	pub fn moves(&self) -> &[Move] {
+		&self.moves
+	}
+
+	/// How the game ended, if it did.
+	pub fn outcome(&self) -> Option<Outcome> {
+		self.outcome
+	}
+
+	/// Plays the given move, if it is legal and the game isn't over.
+	pub fn play(&mut self, mv: Move) -> Result<(), IllegalMove> {
+		if self.outcome.is_some() || !self.position().is_legal(&mv) {
+			return Err(IllegalMove);
+		}
+		self.positions.push(self.position().after(&mv));
+		self.moves.push(mv);
+		self.outcome = self.current_outcome();
+		Ok(())
+	}
+
+	/// Ends the game as the given player ran out of time: the other one wins, unless they can't
+	/// checkmate anymore. Does nothing if the game is already over.
+	pub fn time_out(&mut self, color: Color) {
+		if self.outcome.is_none() {
+			let winner = color.opponent();
+			self.outcome = Some(if self.position().can_checkmate(winner) {
+				Outcome::Timeout(winner)
+			} else {
+				Outcome::Draw(DrawReason::TimeoutVsInsufficientMaterial)
+			});
+		}
+	}
+
+	/// Takes back the last move, returning it. Returns `None` if no move was played.
+	pub fn undo(&mut self) -> Option<Move> {
+		let mv = self.moves.pop()?;
+		self.positions.pop();
+		self.outcome = self.current_outcome();
+		Some(mv)
+	}
+
+	/// How the game ended at the current position, if it did.
+	fn current_outcome(&self) -> Option<Outcome> {
+		let position = self.position();
+		let repetitions = self.positions.iter().filter(|p| p.repeats(position)).count();
+		position.outcome().or((repetitions >= 3).then_some(Outcome::Draw(DrawReason::Repetition)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Builds a position from the placement of the pieces, written as in FEN (the ranks from the
+	/// 8th to the 1st, separated by `/`).
+	fn position(board: &str, player: Color, castling: [[Option<usize>; 2]; 2]) -> Position {
+		let mut tiles = [[None; 8]; 8];
+		for (i, row) in board.split('/').enumerate() {
+			let mut x = 0;
+			for c in row.chars() {
+				let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+				let piece = match c.to_ascii_lowercase() {
+					'k' => Piece::King,
+					'q' => Piece::Queen,
+					'r' => Piece::Rook,
+					'b' => Piece::Bishop,
+					'n' => Piece::Knight,
+					'p' => Piece::Pawn,
+					c => {
+						x += c.to_digit(10).unwrap() as usize;
+						continue;
+					}
+				};
+				tiles[7 - i][x] = Some(Tile(piece, color));
+				x += 1;
+			}
+		}
+		Position {
+			tiles,
+			player,
+			castling,
+			en_passant: None,
+			halfmove_clock: 0,
+			fullmove_number: 1,
+		}
+	}
+
+	/// Counts the leaf nodes of the legal moves tree of the given depth.
+	fn perft(position: &Position, depth: usize) -> usize {
+		if depth == 0 {
+			return 1;
+		}
+		position.legal_moves().iter().map(|mv| perft(&position.after(mv), depth - 1)).sum()
+	}
+
+	fn mv(from: &str, to: &str) -> Move {
+		let pos = |s: &str| {
+			let b = s.as_bytes();
+			((b[0] - b'a') as usize, (b[1] - b'1') as usize)
+		};
+		Move::new(pos(from), pos(to))
+	}
+
+	#[test]
+	fn perft_start() {
+		let start = Position::default();
+		assert_eq!(perft(&start, 1), 20);
+		assert_eq!(perft(&start, 2), 400);
+		assert_eq!(perft(&start, 3), 8902);
+	}
+
+	#[test]
+	fn perft_kiwipete() {
+		// Lots of castling, promotions and pins
+		let position = position(
+			"r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R",
+			Color::White,
+			[[Some(7), Some(0)]; 2],
+		);
+		assert_eq!(perft(&position, 1), 48);
+		assert_eq!(perft(&position, 2), 2039);
+	}
+
+	#[test]
+	fn perft_en_passant() {
+		let position = position("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8", Color::White, [[None; 2]; 2]);
+		assert_eq!(perft(&position, 1), 14);
+		assert_eq!(perft(&position, 2), 191);
+		assert_eq!(perft(&position, 3), 2812);
+	}
+
+	#[test]
+	fn castling() {
+		let mut history = History::default();
+		for (from, to) in [("e2", "e4"), ("e7", "e5"), ("g1", "f3"), ("b8", "c6"), ("f1", "c4")] {
+			history.play(mv(from, to)).unwrap();
+		}
+		// Black can't castle: pieces in the way
+		assert_eq!(history.play(mv("e8", "a8")), Err(IllegalMove));
+		history.play(mv("g8", "f6")).unwrap();
+		history.play(mv("e1", "h1")).unwrap();
+		let position = history.position();
+		assert_eq!(position[(6, 0)], Some(Tile(Piece::King, Color::White)));
+		assert_eq!(position[(5, 0)], Some(Tile(Piece::Rook, Color::White)));
+		assert_eq!(position[(4, 0)], None);
+		assert_eq!(position[(7, 0)], None);
+		assert_eq!(position.castling(Color::White, CastlingSide::Queen), None);
+		assert_eq!(position.castling(Color::Black, CastlingSide::King), Some(7));
+	}
+
+	#[test]
+	fn chess960() {
+		assert_eq!(Position::chess960(518), Some(Position::default()));
+		assert_eq!(
+			Position::chess960(0).unwrap().to_fen(),
+			"bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1"
+		);
+		assert_eq!(
+			Position::chess960(959).unwrap().to_fen(),
+			"rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB w KQkq - 0 1"
+		);
+		assert_eq!(Position::chess960(960), None);
+		let mut fens: Vec<_> = (0..960).map(|n| Position::chess960(n).unwrap().to_fen()).collect();
+		for fen in &fens {
+			assert_eq!(&Position::from_fen(fen).unwrap().to_fen(), fen);
+		}
+		fens.sort();
+		fens.dedup();
+		assert_eq!(fens.len(), 960);
+
+		// Castling puts the king and the rook on their usual tiles, whatever their starting tiles
+		let start =
+			position("4k3/8/8/8/8/8/8/1R3KR1", Color::White, [[Some(6), Some(1)], [None; 2]]);
+		let after = start.after(&mv("f1", "g1"));
+		assert_eq!(after[(6, 0)], Some(Tile(Piece::King, Color::White)));
+		assert_eq!(after[(5, 0)], Some(Tile(Piece::Rook, Color::White)));
+		let after = start.after(&mv("f1", "b1"));
+		assert_eq!(after[(2, 0)], Some(Tile(Piece::King, Color::White)));
+		assert_eq!(after[(3, 0)], Some(Tile(Piece::Rook, Color::White)));
+		assert_eq!(after[(1, 0)], None);
+		assert!(start.is_legal(&mv("f1", "g1")) && start.is_legal(&mv("f1", "b1")));
+		// The rook's tile must be free for the king
+		let blocked =
+			position("4k3/8/8/8/8/8/8/1RN2KR1", Color::White, [[Some(6), Some(1)], [None; 2]]);
+		assert!(!blocked.is_legal(&mv("f1", "b1")));
+	}
+
+	#[test]
+	fn castling_through_check() {
+		let checked = position("r3k2r/8/8/8/8/8/8/R3K1r1", Color::White, [[Some(7), Some(0)]; 2]);
+		// The rook on g1 gives check
+		assert!(!checked.is_legal(&mv("e1", "h1")));
+		assert!(!checked.is_legal(&mv("e1", "a1")));
+		let attacked =
+			position("r3k2r/8/8/8/8/8/8/R3K1R1", Color::Black, [[None; 2], [Some(7), Some(0)]]);
+		// The rook on g1 attacks g8, but the queen side is safe
+		assert!(attacked.is_legal(&mv("e8", "a8")));
+		assert!(!attacked.is_legal(&mv("e8", "h8")));
+	}
+
+	#[test]
+	fn en_passant() {
+		let mut history = History::default();
+		for (from, to) in [("e2", "e4"), ("a7", "a6"), ("e4", "e5"), ("d7", "d5")] {
+			history.play(mv(from, to)).unwrap();
+		}
+		assert_eq!(history.position().en_passant(), Some((3, 5)));
+		history.play(mv("e5", "d6")).unwrap();
+		assert_eq!(history.position()[(3, 4)], None);
+		assert_eq!(history.position()[(3, 5)], Some(Tile(Piece::Pawn, Color::White)));
+		assert_eq!(history.position().halfmove_clock(), 0);
+	}
+
+	#[test]
+	fn promotion() {
+		let position = position("4k3/P7/8/8/8/8/8/4K3", Color::White, [[None; 2]; 2]);
+		// The promotion must be given
+		assert!(!position.is_legal(&mv("a7", "a8")));
+		let promotion = Move { promotion: Some(Piece::Knight), ..mv("a7", "a8") };
+		assert!(position.is_legal(&promotion));
+		assert_eq!(position.after(&promotion)[(0, 7)], Some(Tile(Piece::Knight, Color::White)));
+		assert_eq!(position.legal_moves().iter().filter(|m| m.promotion.is_some()).count(), 4);
+	}
+
+	#[test]
+	fn checkmate_and_stalemate() {
+		let mut history = History::default();
+		for (from, to) in [("f2", "f3"), ("e7", "e5"), ("g2", "g4"), ("d8", "h4")] {
+			history.play(mv(from, to)).unwrap();
+		}
+		assert_eq!(history.outcome(), Some(Outcome::Checkmate(Color::Black)));
+		assert_eq!(history.play(mv("a2", "a3")), Err(IllegalMove));
+
+		let position = position("k7/8/1Q6/8/8/8/8/4K3", Color::Black, [[None; 2]; 2]);
+		assert_eq!(position.outcome(), Some(Outcome::Draw(DrawReason::Stalemate)));
+	}
+
+	#[test]
+	fn draws() {
+		// Knights going back and forth
+		let mut history = History::default();
+		let moves = [("g1", "f3"), ("g8", "f6"), ("f3", "g1"), ("f6", "g8")];
+		for (from, to) in moves.iter().chain(&moves[..3]) {
+			assert_eq!(history.outcome(), None);
+			history.play(mv(from, to)).unwrap();
+		}
+		assert_eq!(history.outcome(), None);
+		history.play(mv("f6", "g8")).unwrap();
+		assert_eq!(history.outcome(), Some(Outcome::Draw(DrawReason::Repetition)));
+		// Taking back the last move cancels the draw
+		assert_eq!(history.undo(), Some(mv("f6", "g8")));
+		assert_eq!(history.outcome(), None);
+		assert_eq!(history.moves().len(), 7);
+		assert_eq!(History::default().undo(), None);
+
+		let mut rook_ending = position("4k3/8/8/8/8/8/3R4/4K3", Color::White, [[None; 2]; 2]);
+		rook_ending.halfmove_clock = 99;
+		assert_eq!(rook_ending.outcome(), None);
+		let rook_ending = rook_ending.after(&mv("d2", "d3"));
+		assert_eq!(rook_ending.outcome(), Some(Outcome::Draw(DrawReason::FiftyMoves)));
+	}
+
+	#[test]
+	fn insufficient_material() {
+		// The two kings and the given pieces
+		let with = |pieces: &[(&str, Piece, Color)]| {
+			let mut position = position("8/8/8/8/8/8/8/8", Color::White, [[None; 2]; 2]);
+			for (tile, piece, color) in
+				[("e1", Piece::King, Color::White), ("e8", Piece::King, Color::Black)]
+					.iter()
+					.chain(pieces)
+			{
+				let (x, y) = mv("a1", tile).to;
+				position.tiles[y][x] = Some(Tile(*piece, *color));
+			}
+			position
+		};
+		assert!(with(&[]).insufficient_material());
+		assert!(with(&[("d2", Piece::Bishop, Color::White)]).insufficient_material());
+		assert!(with(&[("g1", Piece::Knight, Color::Black)]).insufficient_material());
+		// Bishops on tiles of the same color
+		let bishops = [("d2", Piece::Bishop, Color::White), ("f8", Piece::Bishop, Color::Black)];
+		assert!(with(&bishops).insufficient_material());
+		let bishops = [("d2", Piece::Bishop, Color::White), ("g8", Piece::Bishop, Color::Black)];
+		assert!(!with(&bishops).insufficient_material());
+		assert!(!with(&[("d2", Piece::Rook, Color::White)]).insufficient_material());
+		let minors = [("d2", Piece::Bishop, Color::White), ("d1", Piece::Knight, Color::White)];
+		assert!(!with(&minors).insufficient_material());
+	}
+
+	#[test]
+	fn time_out() {
+		// White has a rook, black a knight and a pawn
+		let start: Position = "4k3/4p3/8/8/8/8/3R1n2/4K3 w - - 0 1".parse().unwrap();
+		let mut history = History::new(start.clone());
+		history.time_out(Color::Black);
+		assert_eq!(history.outcome(), Some(Outcome::Timeout(Color::White)));
+		assert_eq!(history.outcome().unwrap().score(), "1-0");
+		// The game being over, it can't end again
+		history.time_out(Color::White);
+		assert_eq!(history.outcome(), Some(Outcome::Timeout(Color::White)));
+		// A knight can checkmate with the help of the other's pieces, but not against a lone king
+		let mut history = History::new(start);
+		history.time_out(Color::White);
+		assert_eq!(history.outcome(), Some(Outcome::Timeout(Color::Black)));
+		let lone_king: Position = "4k3/8/8/8/8/8/5n2/4K3 b - - 0 1".parse().unwrap();
+		assert!(!lone_king.can_checkmate(Color::Black));
+		// And a lone king can't
+		let mut history = History::new("4k3/8/8/8/8/8/8/3QK3 w - - 0 1".parse().unwrap());
+		history.time_out(Color::White);
+		assert_eq!(
+			history.outcome(),
+			Some(Outcome::Draw(DrawReason::TimeoutVsInsufficientMaterial))
+		);
+	}
+}
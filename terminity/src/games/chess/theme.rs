@@ -0,0 +1,374 @@
+//! The looks of the chess board: themes giving the colours of the board, and the piece sets
+//! drawing the pieces.
+//!
+//! Themes can be defined in a config file made of `key = value` lines and `#` comments. The keys
+//! before any section
+//! choose the theme, the piece set and whether the board is large, and each `[name]` section
+//! defines a theme, starting from the colours of the classic one:
+//!
+//! ```
+//! use terminity::games::chess::theme::{Config, PieceSet};
+//!
+//! let config: Config = "
+//! 	theme = ocean
+//! 	pieces = filled # For the terminals drawing the outlined pieces badly
+//!
+//! 	[ocean]
+//! 	light = #8ca2ad
+//! 	dark = 24
+//! 	white = white
+//! 	black = black
+//! "
+//! .parse()
+//! .unwrap();
+//! assert_eq!(config.pieces, Some(PieceSet::Filled));
+//! assert!(config.theme("ocean").is_some() && config.theme("wood").is_some());
+//! ```
+//!
+//! Colours are given by name (as `dark_grey`), as an index in the 256 colours palette, or as
+//! `#rrggbb` for the terminals supporting true colours. `none` is the default colour of the
+//! terminal, or the default colour of the piece set for the pieces.
+
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crossterm::style::Color as TermColor;
+
+use super::notation::ParseError;
+use super::rules::{Color, Piece};
+
+/// The names of the themes always available, the first one being the default one.
+pub const BUILTIN_THEMES: [&str; 3] = ["classic", "wood", "wood-256"];
+
+/// The colours of the board. Where a background is `None`, the default colour of the terminal is
+/// used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+	/// The background of the light tiles
+	pub light_tile: Option<TermColor>,
+	/// The background of the dark tiles
+	pub dark_tile: Option<TermColor>,
+	/// The background of the tile under the cursor, and of the highlighted texts
+	pub cursor: Option<TermColor>,
+	/// The background of the tile of the selected piece
+	pub selected: Option<TermColor>,
+	/// The background of the checked king and the pieces checking it
+	pub checked: Option<TermColor>,
+	/// The background of the tiles of a move that was refused
+	pub invalid: Option<TermColor>,
+	/// The background of the tiles the selected piece can move to
+	pub legal: Option<TermColor>,
+	/// The background of the tiles of the suggested move
+	pub hint: Option<TermColor>,
+	/// The background of the tiles of the last move played
+	pub last_move: Option<TermColor>,
+	/// The colour of the white pieces, white if not given
+	pub white_pieces: Option<TermColor>,
+	/// The colour of the black pieces if not given: white, or red with the filled piece set to
+	/// tell them apart from the white ones
+	pub black_pieces: Option<TermColor>,
+}
+
+impl Default for Theme {
+	/// The classic theme, with the 16 basic colours.
+	fn default() -> Self {
+		Self {
+			light_tile: Some(TermColor::DarkGrey),
+			dark_tile: None,
+			cursor: Some(TermColor::DarkBlue),
+			selected: Some(TermColor::DarkGreen),
+			checked: Some(TermColor::DarkRed),
+			invalid: Some(TermColor::DarkYellow),
+			legal: Some(TermColor::DarkCyan),
+			hint: Some(TermColor::Magenta),
+			last_move: Some(TermColor::DarkMagenta),
+			white_pieces: None,
+			black_pieces: None,
+		}
+	}
+}
+
+impl Theme {
+	/// The built-in theme of the given name, if any.
+	pub fn builtin(name: &str) -> Option<Self> {
+		let rgb = |r, g, b| Some(TermColor::Rgb { r, g, b });
+		let ansi = |value| Some(TermColor::AnsiValue(value));
+		match name {
+			"classic" => Some(Self::default()),
+			"wood" => Some(Self {
+				light_tile: rgb(0xf0, 0xd9, 0xb5),
+				dark_tile: rgb(0xb5, 0x88, 0x63),
+				cursor: rgb(0x5b, 0x8b, 0xc9),
+				selected: rgb(0x82, 0x97, 0x69),
+				checked: rgb(0xd0, 0x4a, 0x3c),
+				invalid: rgb(0xd9, 0xa4, 0x41),
+				legal: rgb(0x9d, 0xb3, 0x6b),
+				hint: rgb(0x9b, 0x6b, 0xb3),
+				last_move: rgb(0xcd, 0xd2, 0x6a),
+				white_pieces: rgb(0xff, 0xff, 0xff),
+				black_pieces: rgb(0x00, 0x00, 0x00),
+			}),
+			"wood-256" => Some(Self {
+				light_tile: ansi(223),
+				dark_tile: ansi(137),
+				cursor: ansi(68),
+				selected: ansi(107),
+				checked: ansi(167),
+				invalid: ansi(179),
+				legal: ansi(143),
+				hint: ansi(133),
+				last_move: ansi(185),
+				white_pieces: ansi(231),
+				black_pieces: ansi(16),
+			}),
+			_ => None,
+		}
+	}
+
+	/// Sets the colour of the given key of a config file.
+	fn set(&mut self, key: &str, value: &str) -> Result<(), ParseError> {
+		let colour = parse_colour(value)?;
+		match key {
+			"light" => self.light_tile = colour,
+			"dark" => self.dark_tile = colour,
+			"cursor" => self.cursor = colour,
+			"selected" => self.selected = colour,
+			"checked" => self.checked = colour,
+			"invalid" => self.invalid = colour,
+			"legal" => self.legal = colour,
+			"hint" => self.hint = colour,
+			"last" => self.last_move = colour,
+			"white" => self.white_pieces = colour,
+			"black" => self.black_pieces = colour,
+			_ => return Err(ParseError(format!("Unknown theme key {:?}", key))),
+		}
+		Ok(())
+	}
+
+	/// The colour of the pieces of the given color when drawn with the given piece set.
+	pub fn pieces(&self, color: Color, set: PieceSet) -> TermColor {
+		match (color, set) {
+			(Color::White, _) => self.white_pieces.unwrap_or(TermColor::White),
+			(Color::Black, PieceSet::Filled) => self.black_pieces.unwrap_or(TermColor::Red),
+			(Color::Black, _) => self.black_pieces.unwrap_or(TermColor::White),
+		}
+	}
+}
+
+/// Parses a colour of a config file, `None` being the default colour of the terminal.
+fn parse_colour(colour: &str) -> Result<Option<TermColor>, ParseError> {
+	let invalid = || ParseError(format!("Invalid colour {:?}", colour));
+	if let Some(hex) = colour.strip_prefix('#') {
+		let value = u32::from_str_radix(hex, 16).map_err(|_| invalid())?;
+		if hex.len() != 6 {
+			return Err(invalid());
+		}
+		let [_, r, g, b] = value.to_be_bytes();
+		return Ok(Some(TermColor::Rgb { r, g, b }));
+	}
+	if let Ok(value) = colour.parse() {
+		return Ok(Some(TermColor::AnsiValue(value)));
+	}
+	Ok(Some(match colour.to_ascii_lowercase().replace('-', "_").as_str() {
+		"none" | "default" => return Ok(None),
+		"black" => TermColor::Black,
+		"dark_grey" | "dark_gray" => TermColor::DarkGrey,
+		"red" => TermColor::Red,
+		"dark_red" => TermColor::DarkRed,
+		"green" => TermColor::Green,
+		"dark_green" => TermColor::DarkGreen,
+		"yellow" => TermColor::Yellow,
+		"dark_yellow" => TermColor::DarkYellow,
+		"blue" => TermColor::Blue,
+		"dark_blue" => TermColor::DarkBlue,
+		"magenta" => TermColor::Magenta,
+		"dark_magenta" => TermColor::DarkMagenta,
+		"cyan" => TermColor::Cyan,
+		"dark_cyan" => TermColor::DarkCyan,
+		"white" => TermColor::White,
+		"grey" | "gray" => TermColor::Grey,
+		_ => return Err(invalid()),
+	}))
+}
+
+/// How the pieces are drawn.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum PieceSet {
+	/// The chess glyphs, filled for white and outlined for black.
+	#[default]
+	Glyphs,
+	/// The filled chess glyphs for both colours, told apart by the colours of the theme.
+	Filled,
+	/// The letters of the pieces, uppercase for white and lowercase for black, for the terminals
+	/// not displaying the chess glyphs well.
+	Ascii,
+}
+
+impl PieceSet {
+	/// The character of the given piece.
+	pub fn symbol(self, piece: Piece, color: Color) -> char {
+		match self {
+			Self::Glyphs => piece.to_char(color),
+			Self::Filled => piece.to_char(Color::White),
+			Self::Ascii if color == Color::White => piece.letter(),
+			Self::Ascii => piece.letter().to_ascii_lowercase(),
+		}
+	}
+}
+
+impl FromStr for PieceSet {
+	type Err = ParseError;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_ascii_lowercase().as_str() {
+			"glyphs" => Ok(Self::Glyphs),
+			"filled" => Ok(Self::Filled),
+			"ascii" => Ok(Self::Ascii),
+			_ => Err(ParseError(format!(
+				"Unknown piece set {:?}, expected glyphs, filled or ascii",
+				s
+			))),
+		}
+	}
+}
+
+/// The settings of a config file.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+	/// The name of the theme to use
+	pub theme: Option<String>,
+	/// The piece set to use
+	pub pieces: Option<PieceSet>,
+	/// Whether to draw a large board
+	pub large: Option<bool>,
+	/// The themes defined in the file
+	pub themes: HashMap<String, Theme>,
+}
+
+impl Config {
+	/// Where the config file is looked for by default: `terminity/chess.conf` in the config
+	/// directory of the user.
+	pub fn default_path() -> Option<PathBuf> {
+		let config = env::var_os("XDG_CONFIG_HOME")
+			.map(PathBuf::from)
+			.or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+		Some(config.join("terminity").join("chess.conf"))
+	}
+
+	/// The theme of the given name, defined in the file or built in.
+	pub fn theme(&self, name: &str) -> Option<Theme> {
+		self.themes.get(name).cloned().or_else(|| Theme::builtin(name))
+	}
+}
+
+/// The given line of a config file without its comment, which starts with a `#` at the start of the
+/// line or followed by a space: the colours start with one too.
+fn strip_comment(line: &str) -> &str {
+	let line = line.trim();
+	if line.starts_with('#') {
+		return "";
+	}
+	let end = line.find("# ").or(line.strip_suffix('#').map(str::len)).unwrap_or(line.len());
+	line[..end].trim_end()
+}
+
+impl FromStr for Config {
+	type Err = ParseError;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut config = Config::default();
+		let mut section: Option<&mut Theme> = None;
+		for (i, line) in s.lines().enumerate() {
+			let at_line = |ParseError(e)| ParseError(format!("Line {}: {}", i + 1, e));
+			let line = strip_comment(line);
+			if line.is_empty() {
+				continue;
+			}
+			if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+				section = Some(config.themes.entry(name.trim().to_owned()).or_default());
+				continue;
+			}
+			let Some((key, value)) = line.split_once('=') else {
+				return Err(at_line(ParseError(format!("Expected `key = value`, got {:?}", line))));
+			};
+			let (key, value) = (key.trim(), value.trim());
+			match (&mut section, key) {
+				(Some(theme), _) => theme.set(key, value).map_err(at_line)?,
+				(None, "theme") => config.theme = Some(value.to_owned()),
+				(None, "pieces") => config.pieces = Some(value.parse().map_err(at_line)?),
+				(None, "large") => {
+					let large = value.parse().map_err(|_| {
+						ParseError(format!("Expected true or false, got {:?}", value))
+					});
+					config.large = Some(large.map_err(at_line)?);
+				}
+				(None, _) => return Err(at_line(ParseError(format!("Unknown key {:?}", key)))),
+			}
+		}
+		Ok(config)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn colours() {
+		assert_eq!(parse_colour("dark_grey"), Ok(Some(TermColor::DarkGrey)));
+		assert_eq!(parse_colour("Dark-Blue"), Ok(Some(TermColor::DarkBlue)));
+		assert_eq!(parse_colour("none"), Ok(None));
+		assert_eq!(parse_colour("208"), Ok(Some(TermColor::AnsiValue(208))));
+		assert_eq!(parse_colour("#1a2B3c"), Ok(Some(TermColor::Rgb { r: 0x1a, g: 0x2b, b: 0x3c })));
+		for invalid in ["256", "#12345", "#12345g", "purple", ""] {
+			assert!(parse_colour(invalid).is_err(), "{}", invalid);
+		}
+	}
+
+	#[test]
+	fn config() {
+		let config: Config =
+			"# Comment\ntheme = mine\nlarge = true\n[mine]\ncursor = red # Comment\n[wood]\ndark = none"
+				.parse()
+				.unwrap();
+		assert_eq!(config.theme.as_deref(), Some("mine"));
+		assert_eq!(config.large, Some(true));
+		assert_eq!(config.pieces, None);
+		let mine = config.theme("mine").unwrap();
+		assert_eq!(mine, Theme { cursor: Some(TermColor::Red), ..Theme::default() });
+		// The themes of the file take precedence over the built-in ones
+		assert_eq!(config.theme("wood").unwrap().dark_tile, None);
+		assert_eq!(config.theme("wood-256"), Theme::builtin("wood-256"));
+		assert_eq!(config.theme("unknown"), None);
+		let config: Config = "[mine]\nlight = #102030 #\ndark = #405060# Comment".parse().unwrap();
+		let mine = config.theme("mine").unwrap();
+		assert_eq!(mine.light_tile, Some(TermColor::Rgb { r: 0x10, g: 0x20, b: 0x30 }));
+		assert_eq!(mine.dark_tile, Some(TermColor::Rgb { r: 0x40, g: 0x50, b: 0x60 }));
+
+		for invalid in ["theme", "colour = red", "[a]\nlight = purple", "pieces = big", "large = 1"]
+		{
+			assert!(invalid.parse::<Config>().is_err(), "{}", invalid);
+		}
+		let error = "\n[a]\nlight = purple".parse::<Config>().unwrap_err();
+		assert_eq!(error.to_string(), "Line 3: Invalid colour \"purple\"");
+	}
+
+	#[test]
+	fn piece_sets() {
+		assert_eq!(PieceSet::Glyphs.symbol(Piece::Queen, Color::White), '♛');
+		assert_eq!(PieceSet::Glyphs.symbol(Piece::Queen, Color::Black), '♕');
+		assert_eq!(PieceSet::Filled.symbol(Piece::Queen, Color::Black), '♛');
+		assert_eq!(PieceSet::Ascii.symbol(Piece::Knight, Color::White), 'N');
+		assert_eq!(PieceSet::Ascii.symbol(Piece::Pawn, Color::Black), 'p');
+		assert_eq!("ASCII".parse(), Ok(PieceSet::Ascii));
+		assert!("large".parse::<PieceSet>().is_err());
+		let classic = Theme::default();
+		assert_eq!(classic.pieces(Color::Black, PieceSet::Glyphs), TermColor::White);
+		assert_eq!(classic.pieces(Color::Black, PieceSet::Filled), TermColor::Red);
+		let wood = Theme::builtin("wood").unwrap();
+		assert_eq!(
+			wood.pieces(Color::Black, PieceSet::Filled),
+			TermColor::Rgb { r: 0, g: 0, b: 0 }
+		);
+	}
+}
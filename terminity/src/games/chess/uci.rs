@@ -0,0 +1,498 @@
+//! A client for chess engines speaking the Universal Chess Interface protocol, as Stockfish.
+//!
+//! The engine runs in its own process, whose output is read by a background thread so that
+//! [`Engine::poll`] never blocks.
+//!
+//! ```no_run
+//! use std::process::Command;
+//! use std::time::Duration;
+//! use terminity::games::chess::rules::History;
+//! use terminity::games::chess::uci::{Engine, Limit};
+//!
+//! let history = History::default();
+//! let mut engine = Engine::start(&mut Command::new("stockfish"), history.start()).unwrap();
+//! engine.go(&history, Limit::Depth(12)).unwrap();
+//! let best = engine.wait(Duration::from_secs(10)).unwrap();
+//! println!("{:?}, evaluated {:?}", best, engine.info().score);
+//! ```
+
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::rules::{CastlingSide, Color, History, Move, Position};
+
+/// How long the engine has to answer to the handshake and to `stop`.
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// When the engine has to stop searching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+	/// Searching the given number of half-moves ahead
+	Depth(u32),
+	/// Searching for the given time
+	MoveTime(Duration),
+	/// Searching until [`Engine::stop`] is called, to analyse a position
+	Infinite,
+}
+
+/// The evaluation of a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+	/// An advantage in hundredths of pawns
+	Centipawns(i32),
+	/// A checkmate in the given number of moves, negative if getting checkmated
+	Mate(i32),
+}
+
+/// What the engine tells about its search.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Info {
+	/// How many half-moves ahead the engine searched
+	pub depth: Option<u32>,
+	/// The evaluation of the position for the player to play
+	pub score: Option<Score>,
+	/// The moves the engine expects to be played, the best one first
+	pub pv: Vec<Move>,
+}
+
+/// A running engine.
+#[derive(Debug)]
+pub struct Engine {
+	process: Child,
+	input: ChildStdin,
+	/// The lines written by the engine, read by a background thread
+	output: Receiver<String>,
+	name: Option<String>,
+	/// The position being searched, if any
+	searching: Option<Position>,
+	info: Info,
+}
+
+impl Score {
+	/// The score for white, knowing the player it was given for.
+	pub fn for_white(self, player: Color) -> Self {
+		match (self, player) {
+			(score, Color::White) => score,
+			(Self::Centipawns(cp), Color::Black) => Self::Centipawns(-cp),
+			(Self::Mate(moves), Color::Black) => Self::Mate(-moves),
+		}
+	}
+}
+
+impl Display for Score {
+	/// Writes the score as in most chess software: `+0.35`, `-1.20` or `#3`, `#-2` for mates.
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match *self {
+			Self::Centipawns(cp) => {
+				let sign = if cp < 0 { '-' } else { '+' };
+				write!(f, "{}{}.{:02}", sign, cp.abs() / 100, cp.abs() % 100)
+			}
+			Self::Mate(moves) => write!(f, "#{}", moves),
+		}
+	}
+}
+
+impl Info {
+	/// Reads an `info` line of the engine searching the given position. Returns `None` if the
+	/// line isn't about the main line of the search.
+	pub fn parse(line: &str, position: &Position) -> Option<Self> {
+		let mut words = line.split_whitespace();
+		if words.next() != Some("info") {
+			return None;
+		}
+		let mut info = Self::default();
+		while let Some(word) = words.next() {
+			match word {
+				"depth" => info.depth = words.next().and_then(|d| d.parse().ok()),
+				"multipv" if words.next() != Some("1") => return None,
+				"score" => {
+					info.score = match (words.next(), words.next().and_then(|s| s.parse().ok())) {
+						(Some("cp"), Some(cp)) => Some(Score::Centipawns(cp)),
+						(Some("mate"), Some(moves)) => Some(Score::Mate(moves)),
+						_ => None,
+					}
+				}
+				"pv" => {
+					let mut position = position.clone();
+					for mv in words.by_ref() {
+						let Ok(mv) = position.parse_uci(mv) else {
+							break;
+						};
+						position.play_unchecked(&mv);
+						info.pv.push(mv);
+					}
+				}
+				// Anything after is free text
+				"string" => break,
+				_ => (),
+			}
+		}
+		Some(info)
+	}
+
+	/// Updates this info with the newer one, keeping what it doesn't tell.
+	fn update(&mut self, newer: Self) {
+		self.depth = newer.depth.or(self.depth);
+		self.score = newer.score.or(self.score);
+		if !newer.pv.is_empty() {
+			self.pv = newer.pv;
+		}
+	}
+}
+
+impl Engine {
+	/// Starts the engine with the given command and waits for it to be ready. The engine is
+	/// switched to Chess960 if the games it plays start from the given position and castling
+	/// there isn't the usual one.
+	pub fn start(command: &mut Command, start: &Position) -> io::Result<Self> {
+		// What the engine writes on stderr would be printed over the game
+		let mut process =
+			command.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::null()).spawn()?;
+		let input = process.stdin.take().unwrap();
+		let stdout = BufReader::new(process.stdout.take().unwrap());
+		let (sender, output) = mpsc::channel();
+		thread::spawn(move || {
+			for line in stdout.lines() {
+				let Ok(line) = line else {
+					break;
+				};
+				if sender.send(line).is_err() {
+					break;
+				}
+			}
+		});
+		let mut engine =
+			Self { process, input, output, name: None, searching: None, info: Info::default() };
+		engine.send("uci")?;
+		while let Some(line) = engine.next_line(ANSWER_TIMEOUT)? {
+			match line.strip_prefix("id name ") {
+				Some(name) => engine.name = Some(name.trim().to_owned()),
+				None if line.trim() == "uciok" => break,
+				None => (),
+			}
+		}
+		if chess960_castling(start) {
+			engine.send("setoption name UCI_Chess960 value true")?;
+		}
+		engine.send("isready")?;
+		while engine.next_line(ANSWER_TIMEOUT)?.is_some_and(|l| l.trim() != "readyok") {}
+		Ok(engine)
+	}
+
+	/// The name the engine gave, if any.
+	pub fn name(&self) -> Option<&str> {
+		self.name.as_deref()
+	}
+
+	/// Whether the engine is searching.
+	pub fn searching(&self) -> bool {
+		self.searching.is_some()
+	}
+
+	/// What the engine told about its last search.
+	pub fn info(&self) -> &Info {
+		&self.info
+	}
+
+	/// Starts searching the best move in the current position of the game, stopping the current
+	/// search if any.
+	pub fn go(&mut self, history: &History, limit: Limit) -> io::Result<()> {
+		if self.searching() {
+			self.stop()?;
+			self.wait(ANSWER_TIMEOUT)?;
+		}
+		let mut command = if *history.start() == Position::default() {
+			"position startpos".to_owned()
+		} else {
+			format!("position fen {}", history.start().to_fen())
+		};
+		if !history.moves().is_empty() {
+			command += " moves";
+			for (position, mv) in history.positions().iter().zip(history.moves()) {
+				command.push(' ');
+				command += &position.uci(mv);
+			}
+		}
+		self.send(&command)?;
+		self.send(&match limit {
+			Limit::Depth(depth) => format!("go depth {}", depth),
+			Limit::MoveTime(time) => format!("go movetime {}", time.as_millis()),
+			Limit::Infinite => "go infinite".to_owned(),
+		})?;
+		self.searching = Some(history.position().clone());
+		self.info = Info::default();
+		Ok(())
+	}
+
+	/// Asks the engine to stop searching as soon as possible. It still gives its best move, that
+	/// is then returned by [`poll`](Self::poll) or [`wait`](Self::wait).
+	pub fn stop(&mut self) -> io::Result<()> {
+		self.send("stop")
+	}
+
+	/// Reads what the engine wrote without blocking, and returns the best move if the search is
+	/// over. Once the search is over, `None` means that there was no legal move.
+	pub fn poll(&mut self) -> io::Result<Option<Move>> {
+		self.read_output(Duration::ZERO)
+	}
+
+	/// Waits for the search to be over and returns the best move, if there is a legal move.
+	/// Fails if the engine doesn't answer in time.
+	pub fn wait(&mut self, timeout: Duration) -> io::Result<Option<Move>> {
+		let deadline = Instant::now() + timeout;
+		while self.searching() {
+			let best = self.read_output(deadline.saturating_duration_since(Instant::now()))?;
+			if best.is_some() {
+				return Ok(best);
+			}
+			if self.searching() && Instant::now() >= deadline {
+				return Err(io::Error::new(ErrorKind::TimedOut, "The engine didn't answer"));
+			}
+		}
+		Ok(None)
+	}
+
+	/// Reads the lines written by the engine, waiting for the first one at most the given time.
+	/// Returns the best move once the search is over.
+	fn read_output(&mut self, timeout: Duration) -> io::Result<Option<Move>> {
+		let mut timeout = timeout;
+		while let Some(line) = self.next_line(timeout)? {
+			timeout = Duration::ZERO;
+			let Some(position) = &self.searching else {
+				continue;
+			};
+			if let Some(info) = Info::parse(&line, position) {
+				self.info.update(info);
+			} else if let Some(best) = line.strip_prefix("bestmove") {
+				let best = best.split_whitespace().next().unwrap_or("(none)");
+				let best = match best {
+					"(none)" | "0000" => None,
+					best => Some(
+						position
+							.parse_uci(best)
+							.map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?,
+					),
+				};
+				self.searching = None;
+				return Ok(best);
+			}
+		}
+		Ok(None)
+	}
+
+	/// The next line written by the engine, waiting at most the given time.
+	fn next_line(&mut self, timeout: Duration) -> io::Result<Option<String>> {
+		match self.output.recv_timeout(timeout) {
+			Ok(line) => Ok(Some(line)),
+			Err(RecvTimeoutError::Timeout) if timeout.is_zero() => Ok(None),
+			Err(RecvTimeoutError::Timeout) => {
+				Err(io::Error::new(ErrorKind::TimedOut, "The engine didn't answer"))
+			}
+			Err(RecvTimeoutError::Disconnected) => {
+				Err(io::Error::new(ErrorKind::UnexpectedEof, "The engine stopped"))
+			}
+		}
+	}
+
+	/// Sends a command to the engine.
+	fn send(&mut self, command: &str) -> io::Result<()> {
+		writeln!(self.input, "{}", command)?;
+		self.input.flush()
+	}
+}
+
+/// Whether a player can castle in the position with the king or the rook not on their usual
+/// tiles, which engines only understand in Chess960 mode.
+fn chess960_castling(position: &Position) -> bool {
+	[Color::White, Color::Black].into_iter().any(|color| {
+		[CastlingSide::King, CastlingSide::Queen].into_iter().any(|side| {
+			let corner = if side == CastlingSide::King { 7 } else { 0 };
+			position.castling(color, side).is_some_and(|rook| {
+				rook != corner || position.king(color).is_some_and(|king| king.0 != 4)
+			})
+		})
+	})
+}
+
+impl Drop for Engine {
+	/// Asks the engine to quit, and kills it if it doesn't in time.
+	fn drop(&mut self) {
+		let _ = self.send("stop").and_then(|_| self.send("quit"));
+		let deadline = Instant::now() + Duration::from_millis(500);
+		while Instant::now() < deadline {
+			if !matches!(self.process.try_wait(), Ok(None)) {
+				return;
+			}
+			thread::sleep(Duration::from_millis(10));
+		}
+		let _ = self.process.kill();
+		let _ = self.process.wait();
+	}
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+	use super::*;
+	use std::path::PathBuf;
+
+	/// A fake engine, that only knows what to play after `1. e4` (and how to start), and logs the
+	/// commands it receives in the file given as argument.
+	#[cfg(unix)]
+	const FAKE_ENGINE: &str = r#"#!/bin/sh
+while read -r line; do
+	echo "$line" >> "$1"
+	case "$line" in
+		uci) echo "id name Fake engine"; echo "option name Hash type spin default 1"; echo "uciok";;
+		isready) echo "readyok";;
+		"position startpos") move=e2e4;;
+		"position startpos moves e2e4") move=e7e5;;
+		position*) move=0000;;
+		"go infinite") echo "info depth 1 score cp 35 pv $move"; echo "info string ready";;
+		go*) echo "info depth 1 score cp 35 pv $move"; echo "info depth 2 score mate -3"; echo "bestmove $move";;
+		stop) echo "bestmove $move";;
+		quit) exit 0;;
+	esac
+done
+"#;
+
+	/// Writes the fake engine to a temporary file, and returns the command to run it and the file
+	/// it logs the commands it receives into.
+	#[cfg(unix)]
+	pub(in super::super) fn fake_engine(name: &str) -> (Command, PathBuf) {
+		use std::os::unix::fs::PermissionsExt;
+		let dir = std::env::temp_dir();
+		let id = std::process::id();
+		let script = dir.join(format!("terminity-fake-engine-{}-{}.sh", name, id));
+		let log = dir.join(format!("terminity-fake-engine-{}-{}.log", name, id));
+		std::fs::write(&script, FAKE_ENGINE).unwrap();
+		std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+		let _ = std::fs::remove_file(&log);
+		let mut command = Command::new(script);
+		command.arg(&log);
+		(command, log)
+	}
+
+	#[test]
+	fn info() {
+		let position = Position::default();
+		let info = Info::parse(
+			"info depth 12 seldepth 15 score cp -31 nodes 1000 pv e2e4 e7e5 g1f3",
+			&position,
+		);
+		assert_eq!(
+			info,
+			Some(Info {
+				depth: Some(12),
+				score: Some(Score::Centipawns(-31)),
+				pv: vec![
+					Move::new((4, 1), (4, 3)),
+					Move::new((4, 6), (4, 4)),
+					Move::new((6, 0), (5, 2))
+				],
+			})
+		);
+		let info =
+			Info::parse("info multipv 1 score mate 2 lowerbound pv e2e5", &position).unwrap();
+		assert_eq!((info.score, info.pv), (Some(Score::Mate(2)), vec![]));
+		assert_eq!(Info::parse("info multipv 2 score cp 10", &position), None);
+		assert_eq!(Info::parse("bestmove e2e4", &position), None);
+		assert_eq!(Info::parse("info string depth 3", &position), Some(Info::default()));
+	}
+
+	#[test]
+	fn score() {
+		assert_eq!(Score::Centipawns(35).to_string(), "+0.35");
+		assert_eq!(Score::Centipawns(-1204).to_string(), "-12.04");
+		assert_eq!(Score::Mate(-2).for_white(Color::Black).to_string(), "#2");
+		assert_eq!(Score::Centipawns(35).for_white(Color::Black), Score::Centipawns(-35));
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn fake_engine_game() {
+		let (mut command, log) = fake_engine("game");
+		let mut engine = Engine::start(&mut command, &Position::default()).unwrap();
+		assert_eq!(engine.name(), Some("Fake engine"));
+
+		let mut history = History::default();
+		engine.go(&history, Limit::MoveTime(Duration::from_millis(100))).unwrap();
+		let best = engine.wait(ANSWER_TIMEOUT).unwrap();
+		assert_eq!(best, Some(Move::new((4, 1), (4, 3))));
+		assert_eq!(engine.info().depth, Some(2));
+		assert_eq!(engine.info().score, Some(Score::Mate(-3)));
+		assert_eq!(engine.info().pv, [Move::new((4, 1), (4, 3))]);
+		history.play(best.unwrap()).unwrap();
+
+		// Searching until stopped, from a custom position
+		let fen = "4k3/8/8/8/8/8/4P3/4K3 b - - 0 1";
+		let mut history = History::new(fen.parse().unwrap());
+		history.play(Move::new((4, 7), (4, 6))).unwrap();
+		history.play(Move::new((4, 1), (4, 3))).unwrap();
+		engine.go(&history, Limit::Infinite).unwrap();
+		engine.stop().unwrap();
+		assert_eq!(engine.info().pv, []);
+		assert_eq!(engine.wait(ANSWER_TIMEOUT).unwrap(), None);
+		assert!(!engine.searching());
+		drop(engine);
+
+		let log = std::fs::read_to_string(log).unwrap();
+		assert_eq!(
+			log.lines().collect::<Vec<_>>(),
+			[
+				"uci",
+				"isready",
+				"position startpos",
+				"go movetime 100",
+				&format!("position fen {} moves e8e7 e2e4", fen),
+				"go infinite",
+				"stop",
+				"stop",
+				"quit"
+			]
+		);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn chess960() {
+		let (mut command, log) = fake_engine("chess960");
+		let start = Position::chess960(0).unwrap();
+		let mut engine = Engine::start(&mut command, &start).unwrap();
+		engine.go(&History::new(start.clone()), Limit::Depth(1)).unwrap();
+		assert_eq!(engine.wait(ANSWER_TIMEOUT).unwrap(), None);
+		drop(engine);
+		let log = std::fs::read_to_string(log).unwrap();
+		assert_eq!(
+			log.lines().take(4).collect::<Vec<_>>(),
+			[
+				"uci",
+				"setoption name UCI_Chess960 value true",
+				"isready",
+				&format!("position fen {}", start.to_fen())
+			]
+		);
+
+		// The standard position, even from a FEN, needs no Chess960
+		assert!(!chess960_castling(&Position::default()));
+		assert!(!chess960_castling(&Position::chess960(518).unwrap()));
+		assert!(chess960_castling(&start));
+		let fen = "4k3/8/8/8/8/8/8/R3K1R1 w Q - 0 1";
+		assert!(!chess960_castling(&fen.parse().unwrap()));
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn dead_engine() {
+		let default = Position::default();
+		assert!(Engine::start(&mut Command::new("/nonexistent/engine"), &default).is_err());
+		let mut engine = Engine::start(&mut fake_engine("dead").0, &default).unwrap();
+		engine.process.kill().unwrap();
+		engine.process.wait().unwrap();
+		let go = engine.go(&History::default(), Limit::Depth(1));
+		assert!(go.is_err() || engine.wait(ANSWER_TIMEOUT).is_err());
+	}
+}
@@ -1,18 +1,39 @@
-//! a module defining what is a game and registering all of them. Currently, only a "super tic tac
-//! toe" and a chess implementation that doesn't recognises checkmates are playable.
-
-use crossterm::{
-	event::{
-		DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
-		EnableFocusChange, EnableMouseCapture,
-	},
-	execute, QueueableCommand,
-};
+//! a module defining what is a game and registering all of them. Currently, a "super tic tac
+//! toe", a chess and a Stratego implementation are playable.
+//!
+//! Games don't have to be defined in this crate: any type implementing [`Game`] can be added to a
+//! [`Registery`] through a [`RegisteryBuilder`], and then be launched in the same environment as
+//! the built-in games, or listed in the [hub](crate::hub).
+//!
+//! ```no_run
+//! use std::io::{self, Write};
+//! use terminity::backend::Terminal;
+//! use terminity::games::{Game, Registery};
+//!
+//! struct MyGame;
+//! impl Game for MyGame {
+//! 	fn name(&self) -> &str {
+//! 		"MyGame"
+//! 	}
+//! 	fn run(&self, term: &mut dyn Terminal) -> io::Result<()> {
+//! 		write!(term, "Hello world!")?;
+//! 		term.flush()?;
+//! 		// Waits for any event
+//! 		term.read().map(|_| ())
+//! 	}
+//! }
+//!
+//! let registery = Registery::builder().with_defaults().register(MyGame).build();
+//! registery.get("MyGame").unwrap().run().unwrap();
+//! ```
+
 use lazy_static::lazy_static;
+
+use crate::backend::{Crossterm, Terminal};
 use std::{
-	collections::HashMap,
-	io::{stdout, Write},
-	panic::{self, catch_unwind, resume_unwind, RefUnwindSafe},
+	collections::BTreeMap,
+	ops::RangeInclusive,
+	panic::{self, catch_unwind, resume_unwind, AssertUnwindSafe, RefUnwindSafe},
 	sync::{Arc, Mutex},
 };
 
@@ -20,94 +41,206 @@ pub mod chess;
 pub mod stratego;
 pub mod sttt;
 
-/// A wrapper for any game, that indicate its name.
-pub struct GameWrapper {
-	/// The game itself.
-	game: Box<dyn Game + Send + Sync>,
-	/// The name of the game.
-	pub name: &'static str,
+/// A game that can be run by terminity.
+///
+/// Besides running the game, the implementors give a few metadata about the game, used to present
+/// it (as in the hub) and to check that it can be played. Only the name is mandatory.
+///
+/// This trait is object-safe, games are stored as trait objects in a [`GameWrapper`].
+pub trait Game: RefUnwindSafe + Send + Sync {
+	/// The name of the game. It is used to identify the game, and thus has to be unique in a
+	/// [`Registery`].
+	fn name(&self) -> &str;
+	/// A short description of the game.
+	fn description(&self) -> &str {
+		""
+	}
+	/// The numbers of players that can play the game.
+	fn players(&self) -> RangeInclusive<u8> {
+		1..=1
+	}
+	/// The minimum size (width, height) the terminal must have for the game to be displayed
+	/// correctly.
+	fn min_size(&self) -> (u16, u16) {
+		(0, 0)
+	}
+	/// Runs the game. Terminity already sets up the environment (see [`with_environment`]), the
+	/// game then only needs to display itself on `term` and to read the events from it.
+	///
+	/// The game shouldn't use `stdout` nor crossterm's events directly, so that it can also run on
+	/// a [`VirtualTerminal`](crate::backend::VirtualTerminal), as in tests.
+	fn run(&self, term: &mut dyn Terminal) -> std::io::Result<()>;
 }
 
-lazy_static! {
-	/// Registers all the playable games and their names.
-	pub static ref REGISTERY: HashMap<&'static str, GameWrapper> = {
-		let mut m = HashMap::new();
-		m.insert("SuperTicTacToe", GameWrapper {
-			game: Box::new(sttt::SuperTTT()),
-			name: "SuperTicTacToe"
-		}
-		);
-		/*m.insert("Stratego", GameWrapper {
-			game: Box::new(stratego::Stratego()),
-			name: "Stratego"
-		}
-		);*/
-		m.insert("Chess", GameWrapper {
-			game: Box::new(chess::Chess()),
-			name: "Chess"
-		}
-		);
-		m
-	};
+/// A wrapper for any game, allowing to run it in terminity's environment.
+pub struct GameWrapper {
+	/// The game itself.
+	game: Box<dyn Game>,
 }
 
 impl GameWrapper {
+	/// Wraps the given game.
+	pub fn new<G: Game + 'static>(game: G) -> Self {
+		Self { game: Box::new(game) }
+	}
+	/// The name of the wrapped game.
+	pub fn name(&self) -> &str {
+		self.game.name()
+	}
+	/// The wrapped game, to access its metadata.
+	pub fn game(&self) -> &dyn Game {
+		self.game.as_ref()
+	}
 	/// Runs the wrapped game.
 	pub fn run(&self) -> std::io::Result<()> {
-		// Set up new hook
-		let old_hook = panic::take_hook();
-		let panic_buffer = Arc::new(Mutex::new(String::with_capacity(200)));
-		panic::set_hook({
-			let panic_buffer = panic_buffer.clone();
-			Box::new(move |info| {
-				panic_buffer.lock().unwrap().push_str(&format!(
-					"payload: {:?}\nlocation: {:?}",
-					info.payload().downcast_ref::<&str>(),
-					info.location()
-				));
-			})
-		});
-		// Prepare game
-		crossterm::terminal::enable_raw_mode()?;
-		execute!(stdout(), EnableBracketedPaste, EnableFocusChange, EnableMouseCapture)?;
-		stdout()
-			.queue(crossterm::cursor::SavePosition)?
-			.queue(crossterm::terminal::EnterAlternateScreen)?
-			.queue(crossterm::cursor::MoveTo(0, 0))?
-			.flush()?;
-		// Game!
-		let res = catch_unwind(move || self.game.run(&mut stdout()));
-		// Restore console state
-		stdout()
-			.queue(crossterm::terminal::LeaveAlternateScreen)?
-			.queue(crossterm::cursor::RestorePosition)?
-			.queue(crossterm::cursor::Show)?
-			.flush()?;
-		execute!(stdout(), DisableBracketedPaste, DisableFocusChange, DisableMouseCapture)?;
-		crossterm::terminal::disable_raw_mode()?;
-		// Restore panic state and manage any error during game
-		panic::set_hook(old_hook);
-		match res {
-			Ok(r) => r,
-			Err(e) => {
-				eprintln!("Thread panicked: {}", panic_buffer.lock().unwrap());
-				resume_unwind(e)
-			}
+		with_environment(move |term| self.game.run(term))
+	}
+	/// Runs the wrapped game on the given terminal, without setting up any environment. This is
+	/// mostly useful to run the game on a [`VirtualTerminal`](crate::backend::VirtualTerminal).
+	pub fn run_on(&self, term: &mut dyn Terminal) -> std::io::Result<()> {
+		self.game.run(term)
+	}
+}
+
+/// A collection of games, indexed by their names.
+///
+/// A registery is built through a [`RegisteryBuilder`], and [`REGISTERY`] holds all the built-in
+/// games.
+pub struct Registery {
+	games: BTreeMap<String, GameWrapper>,
+}
+
+impl Registery {
+	/// Gives a builder for a new registery, initially empty.
+	pub fn builder() -> RegisteryBuilder {
+		RegisteryBuilder { games: BTreeMap::new() }
+	}
+	/// Gets the game of the given name.
+	pub fn get(&self, name: &str) -> Option<&GameWrapper> {
+		self.games.get(name)
+	}
+	/// Iterates over the registered games, sorted by name.
+	pub fn iter(&self) -> impl Iterator<Item = &GameWrapper> {
+		self.games.values()
+	}
+	/// The number of registered games.
+	pub fn len(&self) -> usize {
+		self.games.len()
+	}
+	/// Whether no game is registered.
+	pub fn is_empty(&self) -> bool {
+		self.games.is_empty()
+	}
+}
+
+/// Builder for a [`Registery`].
+pub struct RegisteryBuilder {
+	games: BTreeMap<String, GameWrapper>,
+}
+
+impl RegisteryBuilder {
+	/// Registers the given game.
+	///
+	/// Panics if a game of the same name is already registered.
+	pub fn register<G: Game + 'static>(mut self, game: G) -> Self {
+		let wrapper = GameWrapper::new(game);
+		let name = wrapper.name().to_owned();
+		if self.games.insert(name.clone(), wrapper).is_some() {
+			panic!("A game named {:?} is already registered", name);
 		}
+		self
+	}
+	/// Registers all the games built in terminity.
+	pub fn with_defaults(self) -> Self {
+		self.register(sttt::SuperTTT::new())
+			.register(stratego::Stratego::new())
+			.register(chess::Chess::default())
+	}
+	/// Builds the registery.
+	pub fn build(self) -> Registery {
+		Registery { games: self.games }
 	}
 }
 
-trait Game: RefUnwindSafe {
-	fn run(&self, out: &mut dyn Write) -> std::io::Result<()>;
+lazy_static! {
+	/// Registers all the built-in games.
+	pub static ref REGISTERY: Registery = Registery::builder().with_defaults().build();
 }
 
-impl Game for () {
-	fn run(&self, _: &mut dyn Write) -> std::io::Result<()> {
-		panic!("Internal error: dummy game running")
+/// Sets up the terminal environment games run in, runs the given function in it and then restores
+/// the terminal state, even if the function panicked. This is what [`GameWrapper::run`] uses, and
+/// allows anything else (like a game selection menu) to run in the same environment.
+///
+/// The setup consists in enabling raw mode, paste, focus change and mouse captures, entering the
+/// alternate screen and moving the cursor to (0, 0). If `f` panics, the panic is resumed once the
+/// terminal is restored.
+pub fn with_environment<R, F>(f: F) -> std::io::Result<R>
+where
+	F: FnOnce(&mut dyn Terminal) -> std::io::Result<R>,
+{
+	// Set up new hook
+	let old_hook = panic::take_hook();
+	let panic_buffer = Arc::new(Mutex::new(String::with_capacity(200)));
+	panic::set_hook({
+		let panic_buffer = panic_buffer.clone();
+		Box::new(move |info| {
+			panic_buffer.lock().unwrap().push_str(&format!(
+				"payload: {:?}\nlocation: {:?}",
+				info.payload().downcast_ref::<&str>(),
+				info.location()
+			));
+		})
+	});
+	// Prepare game
+	let mut term = Crossterm::new();
+	term.setup()?;
+	// Game!
+	let res = catch_unwind(AssertUnwindSafe(|| f(&mut term)));
+	// Restore console state
+	term.restore()?;
+	// Restore panic state and manage any error during game
+	panic::set_hook(old_hook);
+	match res {
+		Ok(r) => r,
+		Err(e) => {
+			eprintln!("Thread panicked: {}", panic_buffer.lock().unwrap());
+			resume_unwind(e)
+		}
 	}
 }
 
-/// Gets the game corresponding to the given name.
+/// Gets the built-in game corresponding to the given name.
 pub fn get(name: &str) -> Option<&'static GameWrapper> {
 	REGISTERY.get(name)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	struct Dummy(&'static str);
+	impl Game for Dummy {
+		fn name(&self) -> &str {
+			self.0
+		}
+		fn run(&self, _: &mut dyn Terminal) -> std::io::Result<()> {
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn registery() {
+		let registery = Registery::builder().with_defaults().register(Dummy("Abc")).build();
+		assert_eq!(registery.get("Abc").map(|g| g.name()), Some("Abc"));
+		assert_eq!(registery.get("Chess").map(|g| g.game().players()), Some(2..=2));
+		assert!(registery.get("Nope").is_none());
+		let names: Vec<_> = registery.iter().map(|g| g.name()).collect();
+		assert_eq!(names, ["Abc", "Chess", "Stratego", "SuperTicTacToe"]);
+	}
+
+	#[test]
+	#[should_panic]
+	fn registery_duplicate() {
+		Registery::builder().register(Dummy("Abc")).register(Dummy("Abc"));
+	}
+}
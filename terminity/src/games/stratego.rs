@@ -1,17 +0,0 @@
-//! Not yet implemented
-
-#![allow(missing_docs)]
-
-use std::io::{self, Write};
-
-use super::Game;
-
-#[derive(Debug)]
-pub struct Stratego();
-
-impl Game for Stratego {
-	// add code here
-	fn run(&self, _out: &mut dyn Write) -> Result<(), io::Error> {
-		unimplemented!()
-	}
-}
@@ -0,0 +1,377 @@
+//! A computer opponent, that only knows what a player knows.
+//!
+//! The computer never reads the ranks of the enemy pieces it wasn't shown: it looks at the board
+//! through [`Stratego::known`], and follows the moves played to identify the scouts running over
+//! several tiles. From the ranks the enemy lost or revealed, it deduces how likely each hidden
+//! piece is to be of each rank, a piece having moved being neither a bomb nor the flag. It then
+//! plays the move with the best expected outcome: attacking the pieces it is likely to defeat and
+//! keeping its pieces away from those likely to defeat them.
+//!
+//! Its choices are deterministic for a given seed.
+//!
+//! ```
+//! use terminity::games::stratego::ai::Ai;
+//! use terminity::games::stratego::rules::{Player, Setup, Stratego};
+//!
+//! let mut ai = Ai::new(Player::Blue, 42);
+//! let blue = ai.setup();
+//! let red = Setup::random(Player::Red, &mut rand::thread_rng());
+//! let mut game = Stratego::new(&red, &blue).unwrap();
+//! let mv = game.legal_moves()[0];
+//! game.play(mv).unwrap();
+//! let answer = ai.best_move(&game).unwrap();
+//! assert!(game.check(answer).is_ok());
+//! ```
+
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use super::rules::{positions, Fight, Move, Player, Pos, Rank, Setup, Stratego, RANKS, SIZE};
+
+/// The score of capturing the flag.
+const WIN: f32 = 1000.0;
+
+/// How likely an enemy piece next to a piece is to attack it.
+const THREAT: f32 = 0.6;
+
+/// How much moving a piece a tile closer to the enemy is worth.
+const ADVANCE: f32 = 0.2;
+
+/// How much a piece is worth, as what capturing it gains.
+fn value(rank: Rank) -> f32 {
+	match rank {
+		Rank::Spy => 6.0,
+		Rank::Scout => 2.0,
+		Rank::Miner => 5.0,
+		Rank::Sergeant => 3.0,
+		Rank::Lieutenant => 4.0,
+		Rank::Captain => 5.0,
+		Rank::Major => 7.0,
+		Rank::Colonel => 9.0,
+		Rank::General => 12.0,
+		Rank::Marshal => 16.0,
+		Rank::Bomb => 3.0,
+		Rank::Flag => WIN,
+	}
+}
+
+/// How likely a piece is to be of each rank, in the order of [`RANKS`].
+type Belief = [f32; RANKS.len()];
+
+/// A computer player.
+#[derive(Debug, Clone)]
+pub struct Ai {
+	player: Player,
+	rng: StdRng,
+	/// The enemy pieces whose rank was deduced from their moves without being revealed, by tile
+	identified: HashMap<Pos, Rank>,
+	/// The number of turns of the game already followed
+	followed: usize,
+}
+
+impl Ai {
+	/// A computer playing the given player, making its random choices using the given seed.
+	pub fn new(player: Player, seed: u64) -> Self {
+		Self { player, rng: StdRng::seed_from_u64(seed), identified: HashMap::new(), followed: 0 }
+	}
+
+	/// The player the computer plays.
+	pub fn player(&self) -> Player {
+		self.player
+	}
+
+	/// Sets up the army of the computer: the flag in the back row surrounded by bombs, scouts in
+	/// the front row to explore, and the other pieces at random.
+	pub fn setup(&mut self) -> Setup {
+		let mut setup = Setup::new(self.player);
+		let rows = self.player.setup_rows();
+		let (back, front, forward) = match self.player {
+			Player::Red => (rows.end - 1, rows.start, -1),
+			Player::Blue => (rows.start, rows.end - 1, 1),
+		};
+		let x = self.rng.gen_range(0..SIZE);
+		setup.place((x, back), Rank::Flag).expect("Flag out of the setup rows");
+		let guards =
+			[(x as i8 - 1, back as i8), (x as i8 + 1, back as i8), (x as i8, back as i8 + forward)];
+		for (x, y) in guards {
+			if (0..SIZE as i8).contains(&x) {
+				setup.place((x as u8, y as u8), Rank::Bomb).expect("Bomb out of the setup rows");
+			}
+		}
+		let mut fronts: Vec<_> = (0..SIZE).map(|x| (x, front)).collect();
+		fronts.shuffle(&mut self.rng);
+		fronts.retain(|&pos| setup.get(pos).is_none());
+		for pos in fronts.into_iter().take(4) {
+			setup.place(pos, Rank::Scout).expect("Scout out of the setup rows");
+		}
+		setup.fill_randomly(&mut self.rng);
+		setup
+	}
+
+	/// Chooses the move to play in the given game, if it isn't over and it is the computer's turn.
+	pub fn best_move(&mut self, game: &Stratego) -> Option<Move> {
+		if game.player() != self.player {
+			return None;
+		}
+		self.follow(game);
+		let unknown = self.unknown(game);
+		let mut moves = game.legal_moves();
+		// Choosing randomly between the moves evaluated the same
+		moves.shuffle(&mut self.rng);
+		let scored: Vec<_> = moves
+			.into_iter()
+			.map(|mv| {
+				// Breaking the ties a bit, not to go back and forth forever
+				let noise = self.rng.gen_range(0.0..0.05);
+				(mv, self.score(game, mv, &unknown) + noise)
+			})
+			.collect();
+		scored.into_iter().max_by(|(_, a), (_, b)| a.total_cmp(b)).map(|(mv, _)| mv)
+	}
+
+	/// Follows the turns played since the last time, identifying the enemy scouts running over
+	/// several tiles, and following the pieces identified.
+	fn follow(&mut self, game: &Stratego) {
+		if game.turns().len() < self.followed {
+			// Another game
+			self.identified.clear();
+			self.followed = 0;
+		}
+		for (i, &(mv, combat)) in game.turns().iter().enumerate().skip(self.followed) {
+			let mut rank = self.identified.remove(&mv.from);
+			let fight = combat.map(|combat| combat.fight);
+			if fight.is_some_and(|fight| fight != Fight::Lost) {
+				self.identified.remove(&mv.to);
+			}
+			let distance = mv.from.0.abs_diff(mv.to.0) + mv.from.1.abs_diff(mv.to.1);
+			// Red plays the even turns
+			let mover = if i % 2 == 0 { Player::Red } else { Player::Blue };
+			if distance > 1 && mover != self.player {
+				rank = Some(Rank::Scout);
+			}
+			if let (Some(rank), None | Some(Fight::Won)) = (rank, fight) {
+				self.identified.insert(mv.to, rank);
+			}
+		}
+		self.followed = game.turns().len();
+		// The pieces revealed since then are known anyway
+		let player = self.player;
+		self.identified.retain(|&pos, _| {
+			game.known(pos, player)
+				.is_some_and(|known| known.owner != player && known.rank.is_none())
+		});
+	}
+
+	/// The number of enemy pieces of each rank whose tile isn't known.
+	fn unknown(&self, game: &Stratego) -> Belief {
+		let enemy = self.player.opponent();
+		let mut counts = RANKS.map(|rank| (rank.count() - game.lost(enemy, rank)) as f32);
+		for pos in positions() {
+			if let Some(rank) = self.rank(game, pos).filter(|_| self.is_enemy(game, pos)) {
+				counts[rank_index(rank)] -= 1.0;
+			}
+		}
+		counts
+	}
+
+	/// The rank of the piece on the given tile, if the computer knows it.
+	fn rank(&self, game: &Stratego, pos: Pos) -> Option<Rank> {
+		let known = game.known(pos, self.player)?;
+		known.rank.or_else(|| self.identified.get(&pos).copied())
+	}
+
+	/// Whether the piece on the given tile is an enemy one.
+	fn is_enemy(&self, game: &Stratego, pos: Pos) -> bool {
+		game.known(pos, self.player).is_some_and(|known| known.owner != self.player)
+	}
+
+	/// How likely the enemy piece on the given tile is to be of each rank.
+	fn belief(&self, game: &Stratego, pos: Pos, unknown: &Belief) -> Belief {
+		let mut belief = [0.0; RANKS.len()];
+		if let Some(rank) = self.rank(game, pos) {
+			belief[rank_index(rank)] = 1.0;
+			return belief;
+		}
+		let moved = game.known(pos, self.player).is_some_and(|known| known.moved);
+		for (i, rank) in RANKS.into_iter().enumerate() {
+			if !moved || rank.movable() {
+				belief[i] = unknown[i].max(0.0);
+			}
+		}
+		let total: f32 = belief.iter().sum();
+		if total > 0.0 {
+			belief.iter_mut().for_each(|p| *p /= total);
+		}
+		belief
+	}
+
+	/// The expected gain of the given move.
+	fn score(&self, game: &Stratego, mv: Move, unknown: &Belief) -> f32 {
+		let mine = self.rank(game, mv.from).expect("Moving an unknown piece");
+		if self.is_enemy(game, mv.to) {
+			let belief = self.belief(game, mv.to, unknown);
+			return RANKS
+				.into_iter()
+				.zip(belief)
+				.filter(|&(_, p)| p > 0.0)
+				.map(|(defender, p)| {
+					p * match mine.attack(defender) {
+						Fight::Won => value(defender),
+						Fight::Lost => -value(mine),
+						Fight::Tie => value(defender) - value(mine),
+					}
+				})
+				.sum();
+		}
+		let advance = match self.player {
+			Player::Red => mv.from.1 as f32 - mv.to.1 as f32,
+			Player::Blue => mv.to.1 as f32 - mv.from.1 as f32,
+		};
+		ADVANCE * advance - self.threat(game, mv.to, mine, Some(mv.from), unknown)
+			+ self.threat(game, mv.from, mine, None, unknown)
+	}
+
+	/// The loss to expect from the enemy pieces next to the given tile, if a piece of the given
+	/// rank stands there. The tile it comes from, if any, is empty then.
+	fn threat(
+		&self,
+		game: &Stratego,
+		pos: Pos,
+		mine: Rank,
+		from: Option<Pos>,
+		unknown: &Belief,
+	) -> f32 {
+		let neighbours = [(0, -1), (1, 0), (0, 1), (-1, 0)]
+			.map(|(dx, dy)| ((pos.0 as i8 + dx) as u8, (pos.1 as i8 + dy) as u8));
+		neighbours
+			.into_iter()
+			.filter(|&next| next.0 < SIZE && next.1 < SIZE && Some(next) != from)
+			.filter(|&next| self.is_enemy(game, next))
+			.map(|next| {
+				let belief = self.belief(game, next, unknown);
+				let loss: f32 = RANKS
+					.into_iter()
+					.zip(belief)
+					.filter(|&(attacker, p)| p > 0.0 && attacker.movable())
+					.map(|(attacker, p)| match attacker.attack(mine) {
+						Fight::Won => p * value(mine),
+						Fight::Tie => p * (value(mine) - value(attacker)).max(0.0),
+						Fight::Lost => 0.0,
+					})
+					.sum();
+				THREAT * loss
+			})
+			.fold(0.0, f32::max)
+	}
+}
+
+/// The index of the given rank in [`RANKS`].
+fn rank_index(rank: Rank) -> usize {
+	RANKS.iter().position(|&r| r == rank).expect("Unknown rank")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A game starting from the setups of the given computers.
+	fn game(red: &mut Ai, blue: &mut Ai) -> Stratego {
+		Stratego::new(&red.setup(), &blue.setup()).unwrap()
+	}
+
+	#[test]
+	fn setup() {
+		for seed in 0..20 {
+			let setup = Ai::new(Player::Blue, seed).setup();
+			assert!(setup.is_complete());
+			let flag = positions().find(|&pos| setup.get(pos) == Some(Rank::Flag)).unwrap();
+			assert_eq!(flag.1, 0);
+			assert_eq!(setup.get((flag.0, 1)), Some(Rank::Bomb));
+			let scouts = (0..SIZE).filter(|&x| setup.get((x, 3)) == Some(Rank::Scout));
+			assert!(scouts.count() >= 4);
+		}
+		assert_eq!(Ai::new(Player::Red, 3).setup(), Ai::new(Player::Red, 3).setup());
+		assert_ne!(Ai::new(Player::Red, 3).setup(), Ai::new(Player::Red, 4).setup());
+	}
+
+	#[test]
+	fn deterministic() {
+		let play = |seed| {
+			let (mut red, mut blue) = (Ai::new(Player::Red, seed), Ai::new(Player::Blue, seed + 1));
+			let mut game = game(&mut red, &mut blue);
+			for _ in 0..200 {
+				let ai = if game.player() == Player::Red { &mut red } else { &mut blue };
+				let Some(mv) = ai.best_move(&game) else {
+					break;
+				};
+				game.play(mv).unwrap();
+			}
+			game.turns().to_vec()
+		};
+		assert_eq!(play(5), play(5));
+		assert_ne!(play(5), play(6));
+	}
+
+	#[test]
+	fn no_cheating() {
+		// Two games where blue swapped two pieces red knows nothing about: red plays the same
+		let mut setup = Ai::new(Player::Blue, 0).setup();
+		let red = Ai::new(Player::Red, 0).setup();
+		let game = Stratego::new(&red, &setup).unwrap();
+		let (a, b) = ((0..SIZE).map(|x| (x, 3)).find_map(|a| {
+			let b = (0..SIZE).map(|x| (x, 3)).find(|&b| setup.get(a) != setup.get(b))?;
+			Some((a, b))
+		}))
+		.unwrap();
+		setup.swap(a, b).unwrap();
+		let swapped = Stratego::new(&red, &setup).unwrap();
+		assert_ne!(game, swapped);
+		for seed in 0..10 {
+			let mv = Ai::new(Player::Red, seed).best_move(&game);
+			assert_eq!(mv, Ai::new(Player::Red, seed).best_move(&swapped));
+		}
+	}
+
+	#[test]
+	fn beliefs() {
+		let mut red = Setup::new(Player::Red);
+		let mut blue = Setup::new(Player::Blue);
+		// Red's scout runs to the sergeant and loses, revealing it
+		red.place((0, 6), Rank::Scout).unwrap();
+		red.place((4, 6), Rank::Marshal).unwrap();
+		red.place((5, 6), Rank::General).unwrap();
+		blue.place((0, 3), Rank::Sergeant).unwrap();
+		blue.place((4, 3), Rank::Marshal).unwrap();
+		blue.place((5, 3), Rank::Scout).unwrap();
+		let mut rng = StdRng::seed_from_u64(0);
+		red.fill_randomly(&mut rng);
+		blue.fill_randomly(&mut rng);
+		let mut game = Stratego::new(&red, &blue).unwrap();
+		game.play(Move { from: (0, 6), to: (0, 3) }).unwrap();
+		// Blue's scout runs next to red's general, getting identified
+		game.play(Move { from: (5, 3), to: (5, 5) }).unwrap();
+		let mut ai = Ai::new(Player::Red, 0);
+		let unknown = ai.unknown(&game);
+		ai.follow(&game);
+		assert_eq!(ai.rank(&game, (0, 3)), Some(Rank::Sergeant));
+		assert_eq!(ai.rank(&game, (5, 5)), Some(Rank::Scout));
+		assert_eq!(ai.rank(&game, (4, 3)), None);
+
+		let unknown_now = ai.unknown(&game);
+		assert_eq!(unknown_now[rank_index(Rank::Scout)], 7.0);
+		assert_eq!(unknown[rank_index(Rank::Scout)], 8.0);
+		assert_eq!(unknown_now[rank_index(Rank::Sergeant)], 3.0);
+		// A piece that moved is neither a bomb nor the flag
+		let belief = ai.belief(&game, (5, 5), &unknown_now);
+		assert_eq!(belief[rank_index(Rank::Scout)], 1.0);
+		let belief = ai.belief(&game, (4, 3), &unknown_now);
+		assert!(belief[rank_index(Rank::Bomb)] > 0.0);
+
+		// The general takes the scout that came next to it
+		let mv = ai.best_move(&game).unwrap();
+		assert_eq!(mv, Move { from: (5, 6), to: (5, 5) });
+	}
+}
@@ -0,0 +1,1064 @@
+//! Stratego, for two players on the same device or against the computer.
+//!
+//! Each player first sets up their army in their four back rows, by dragging the pieces from the
+//! list next to the board or typing their symbols. The players then take turns moving their
+//! pieces, only seeing the ranks of the enemy pieces revealed by a combat. Between turns, the
+//! board is hidden while the device is passed to the other player.
+//!
+//! The rules are in [`rules`], this module only displays the game and lets the players play it,
+//! against each other or against the computer of [`ai`].
+
+#![allow(missing_docs)]
+
+use std::fmt::Formatter;
+use std::io;
+use std::ops::{Range, RangeInclusive};
+use std::time::Duration;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::style::{Color, ContentStyle, Stylize};
+use crossterm::{cursor, QueueableCommand};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use terminity_widgets::render::{Buffer, Cell, Renderer};
+use terminity_widgets::widgets::layout::{Constraint, HStack, LayoutChild, VStack};
+use terminity_widgets::widgets::text::{Align, Text};
+use terminity_widgets::{MouseEventWidget, ResizableWisget, Widget, WidgetDisplay};
+
+use super::Game;
+use crate::backend::Terminal;
+use crate::events::{self, Event, EventHandler, Flow};
+
+pub mod ai;
+pub mod rules;
+
+use ai::Ai;
+use rules::{is_lake, positions, Combat, Fight, Move, Outcome, Player, Pos, Rank, Setup, RANKS};
+
+/// Stratego, played by two players taking turns on the same device.
+#[derive(Debug, Default)]
+pub struct Stratego {
+	/// The seed the pieces left to place are placed at random with, random by default
+	seed: Option<u64>,
+	/// The player played by the computer and its seed, if playing against it
+	computer: Option<(Player, u64)>,
+}
+
+impl Stratego {
+	/// A game between two players on the same device.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Places the pieces the players leave to place at random with the given seed.
+	pub fn with_seed(mut self, seed: u64) -> Self {
+		self.seed = Some(seed);
+		self
+	}
+
+	/// Plays against the computer, that plays the given player. The seed decides the computer's
+	/// setup and random choices.
+	pub fn against_computer(mut self, player: Player, seed: u64) -> Self {
+		self.computer = Some((player, seed));
+		self
+	}
+}
+
+impl Game for Stratego {
+	fn name(&self) -> &str {
+		"Stratego"
+	}
+	fn description(&self) -> &str {
+		"Capture the enemy flag with an army whose ranks your opponent can't see, against a friend \
+		or against the computer."
+	}
+	fn players(&self) -> RangeInclusive<u8> {
+		2..=2
+	}
+	fn min_size(&self) -> (u16, u16) {
+		(TEXT_WIDTH as u16, (Screen::HEIGHT + TEXT_HEIGHT) as u16)
+	}
+	fn run(&self, term: &mut dyn Terminal) -> io::Result<()> {
+		let seed = self.seed.unwrap_or_else(rand::random);
+		let mut state = GameState::new(seed);
+		if let Some((player, seed)) = self.computer {
+			state.play_against(Ai::new(player, seed));
+		}
+		events::run(&mut state, term)
+	}
+}
+
+/// The number of lines of the texts under the board.
+const TEXT_HEIGHT: usize = 5;
+
+/// The width of the texts under the board, and of the game.
+const TEXT_WIDTH: usize = 70;
+
+/// The color of the pieces of the given player.
+fn color(player: Player) -> Color {
+	match player {
+		Player::Red => Color::DarkRed,
+		Player::Blue => Color::DarkBlue,
+	}
+}
+
+/// What a tile of the board shows.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Shown {
+	Land,
+	Lake,
+	/// A piece of the given player, with its rank if the player looking at the board knows it
+	Piece(Player, Option<Rank>),
+}
+
+/// The board, as seen by a player.
+struct Board {
+	/// What the tiles show, indexed as `x + SIZE * y`
+	tiles: Vec<Shown>,
+	/// The player whose army is at the bottom of the screen
+	bottom: Player,
+	cursor: Option<Pos>,
+	/// The tile of the piece picked up to move it
+	selected: Option<Pos>,
+	last_move: Option<Move>,
+	/// The rows pieces can be placed in, during the setup
+	zone: Option<Range<u8>>,
+}
+
+impl Board {
+	/// The columns taken by a tile
+	const TILE: usize = 3;
+	const WIDTH: usize = rules::SIZE as usize * Self::TILE + 2;
+	const HEIGHT: usize = rules::SIZE as usize + 2;
+
+	fn new() -> Self {
+		Self {
+			tiles: positions()
+				.map(|pos| if is_lake(pos) { Shown::Lake } else { Shown::Land })
+				.collect(),
+			bottom: Player::Red,
+			cursor: None,
+			selected: None,
+			last_move: None,
+			zone: None,
+		}
+	}
+
+	/// The tile shown at the given row and column of the board, from the top left corner of the
+	/// screen.
+	fn at(&self, column: u8, row: u8) -> Pos {
+		let last = rules::SIZE - 1;
+		match self.bottom {
+			Player::Red => (column, row),
+			Player::Blue => (last - column, last - row),
+		}
+	}
+
+	/// The tile under the given column and line of the widget, if any.
+	fn tile_at(&self, column: usize, line: usize) -> Option<Pos> {
+		let column = column.checked_sub(1)? / Self::TILE;
+		let row = line.checked_sub(1)?;
+		let size = rules::SIZE as usize;
+		(column < size && row < size).then(|| self.at(column as u8, row as u8))
+	}
+}
+
+impl Widget for Board {
+	fn displ_line(&self, f: &mut Formatter<'_>, line: usize) -> std::fmt::Result {
+		let row = match line.checked_sub(1) {
+			Some(row) if row < rules::SIZE as usize => row as u8,
+			_ => return write!(f, "+{}+", "-".repeat(Self::WIDTH - 2)),
+		};
+		f.write_str("|")?;
+		for column in 0..rules::SIZE {
+			let pos = self.at(column, row);
+			let shown = self.tiles[pos.0 as usize + rules::SIZE as usize * pos.1 as usize];
+			let highlight = if self.selected == Some(pos) {
+				Some(Color::Yellow)
+			} else if self.cursor == Some(pos) {
+				Some(Color::Grey)
+			} else if self.last_move.is_some_and(|mv| mv.from == pos || mv.to == pos) {
+				Some(Color::DarkYellow)
+			} else {
+				None
+			};
+			let in_zone = self.zone.as_ref().is_some_and(|rows| rows.contains(&pos.1));
+			let (content, background, owner) = match shown {
+				Shown::Lake => (" ~ ".to_owned(), Color::DarkCyan, None),
+				Shown::Land if in_zone => (" · ".to_owned(), Color::DarkGreen, None),
+				Shown::Land => ("   ".to_owned(), Color::DarkGreen, None),
+				Shown::Piece(player, rank) => {
+					let symbol = rank.map_or('?', Rank::symbol);
+					(format!(" {} ", symbol), color(player), Some(player))
+				}
+			};
+			let style = match highlight {
+				Some(highlight) => ContentStyle {
+					foreground_color: Some(owner.map_or(Color::Black, color)),
+					background_color: Some(highlight),
+					..Default::default()
+				},
+				None => ContentStyle {
+					foreground_color: Some(Color::White),
+					background_color: Some(background),
+					..Default::default()
+				},
+			};
+			write!(f, "{}", style.apply(content).bold())?;
+		}
+		f.write_str("|")
+	}
+	fn size(&self) -> (usize, usize) {
+		(Self::WIDTH, Self::HEIGHT)
+	}
+}
+
+impl LayoutChild<Target> for Board {
+	/// The tile under the pointer.
+	fn mouse(&mut self, event: MouseEvent) -> Option<Target> {
+		self.tile_at(event.column as usize, event.row as usize).map(Target::Tile)
+	}
+}
+
+/// The ranks of the pieces, with the number of pieces left to place during the setup, and of
+/// pieces lost by each player during the game.
+struct Panel {
+	title: String,
+	/// The counts shown next to each rank, in the order of `RANKS`, and their color
+	counts: Vec<Vec<(u8, Option<Color>)>>,
+	/// The rank of the piece picked up to place it
+	selected: Option<Rank>,
+}
+
+impl Panel {
+	const WIDTH: usize = 18;
+	const HEIGHT: usize = RANKS.len() + 1;
+}
+
+impl Widget for Panel {
+	fn displ_line(&self, f: &mut Formatter<'_>, line: usize) -> std::fmt::Result {
+		let Some(rank) = line.checked_sub(1).map(|i| RANKS[i]) else {
+			return write!(f, "{:1$}", self.title, Self::WIDTH);
+		};
+		let name = format!("{} {:<10}", rank.symbol(), rank.to_string());
+		if self.selected == Some(rank) {
+			write!(f, "{}", name.as_str().on(Color::Yellow).with(Color::Black))?;
+		} else {
+			f.write_str(&name)?;
+		}
+		let counts = &self.counts[line - 1];
+		for &(count, color) in counts {
+			let count = format!(" {:>2}", count);
+			match color {
+				Some(color) => write!(f, "{}", count.with(color).bold())?,
+				None => f.write_str(&count)?,
+			}
+		}
+		write!(f, "{:1$}", "", Self::WIDTH - name.chars().count() - 3 * counts.len())
+	}
+	fn size(&self) -> (usize, usize) {
+		(Self::WIDTH, Self::HEIGHT)
+	}
+}
+
+impl LayoutChild<Target> for Panel {
+	/// The rank under the pointer.
+	fn mouse(&mut self, event: MouseEvent) -> Option<Target> {
+		let rank = (event.row as usize).checked_sub(1).and_then(|i| RANKS.get(i))?;
+		Some(Target::Tray(*rank))
+	}
+}
+
+/// What can be picked up and dropped with the mouse or the keyboard.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Target {
+	/// The piece on the tile, or the tile a piece is dropped on
+	Tile(Pos),
+	/// A piece of the rank, from the pieces left to place
+	Tray(Rank),
+}
+
+/// A part of the screen, telling what is under the pointer.
+type Part = Box<dyn LayoutChild<Target>>;
+
+/// The board and next to it the ranks of the pieces, above the texts telling what happens.
+#[derive(WidgetDisplay)]
+struct Screen(VStack<Part>);
+
+impl Screen {
+	/// The columns between the board and the panel
+	const GAP: usize = 2;
+	/// The size of the board and the panel
+	const WIDTH: usize = Board::WIDTH + Self::GAP + Panel::WIDTH;
+	const HEIGHT: usize = Panel::HEIGHT;
+
+	fn new(board: Board, panel: Panel, text: Text<TEXT_HEIGHT>) -> Self {
+		// The area of the board also holds the gap, and is as high as the panel
+		let top = HStack::<Part>::new()
+			.with(Constraint::Fixed(Board::WIDTH + Self::GAP), Box::new(board))
+			.with(Constraint::Fixed(Panel::WIDTH), Box::new(panel));
+		let mut stack = VStack::<Part>::new()
+			.with(Constraint::Fixed(Self::HEIGHT), Box::new(top))
+			.with(Constraint::Fixed(TEXT_HEIGHT), Box::new(text));
+		stack.resize((Self::WIDTH.max(TEXT_WIDTH), Self::HEIGHT + TEXT_HEIGHT));
+		Self(stack)
+	}
+
+	/// The board, the panel and the texts, to update them.
+	fn parts(&mut self) -> (&mut Board, &mut Panel, &mut Text<TEXT_HEIGHT>) {
+		let [top, text] = &mut self.0[..] else {
+			unreachable!("The screen has two rows");
+		};
+		let top = top.downcast_mut::<HStack<Part>>().expect("The top of the screen isn't a stack");
+		let [board, panel] = &mut top[..] else {
+			unreachable!("The top of the screen has two columns");
+		};
+		(
+			board.downcast_mut().expect("The board isn't on the left"),
+			panel.downcast_mut().expect("The panel isn't on the right"),
+			text.downcast_mut().expect("The texts aren't at the bottom"),
+		)
+	}
+
+	/// The texts under the board.
+	fn text_mut(&mut self) -> &mut Text<TEXT_HEIGHT> {
+		self.parts().2
+	}
+}
+
+impl Widget for Screen {
+	fn displ_line(&self, f: &mut Formatter<'_>, line: usize) -> std::fmt::Result {
+		self.0.displ_line(f, line)
+	}
+	fn draw_line(&self, cells: &mut [Cell], line: usize) {
+		self.0.draw_line(cells, line)
+	}
+	fn size(&self) -> (usize, usize) {
+		self.0.size()
+	}
+}
+
+impl MouseEventWidget for Screen {
+	/// The tile or the rank under the pointer.
+	type MouseHandlingResult = Option<Target>;
+	fn mouse_event(&mut self, event: MouseEvent) -> Self::MouseHandlingResult {
+		self.0.mouse_event(event)?.1
+	}
+}
+
+/// The step of the game.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Phase {
+	/// The player sets up their army.
+	Setup(Player),
+	/// The device is passed to the player, the board being hidden until they are ready to set up
+	/// their army or to play.
+	Pass(Player),
+	/// The next player chooses their move.
+	Play,
+	/// The player who just played sees how their move went, before passing the device.
+	Played,
+	/// The game is over, and all the pieces are shown.
+	Over,
+}
+
+struct GameState {
+	setups: [Setup; 2],
+	/// The game, once both armies are set up
+	game: Option<rules::Stratego>,
+	phase: Phase,
+	screen: Screen,
+	/// The tile under the cursor
+	cursor: Pos,
+	/// What was picked up to be moved or placed, with the mouse or the keyboard
+	hand: Option<Target>,
+	/// Places the pieces left to place at random
+	rng: StdRng,
+	/// The computer, if playing against it
+	computer: Option<Ai>,
+	renderer: Renderer,
+}
+
+impl GameState {
+	fn new(seed: u64) -> Self {
+		let mut state = Self {
+			setups: [Setup::new(Player::Red), Setup::new(Player::Blue)],
+			game: None,
+			phase: Phase::Setup(Player::Red),
+			screen: Screen::new(
+				Board::new(),
+				Panel { title: String::new(), counts: vec![], selected: None },
+				Text {
+					content: Default::default(),
+					align: Align::Center,
+					padding: ' ',
+					width: TEXT_WIDTH,
+				},
+			),
+			cursor: (0, 0),
+			hand: None,
+			rng: StdRng::seed_from_u64(seed),
+			computer: None,
+			renderer: Renderer::new(),
+		};
+		state.start_phase(Phase::Setup(Player::Red));
+		state.refresh();
+		state
+	}
+
+	/// Plays against the given computer, that sets up its army right away.
+	fn play_against(&mut self, mut ai: Ai) {
+		let player = ai.player();
+		self.setups[player as usize] = ai.setup();
+		self.computer = Some(ai);
+		self.start_phase(Phase::Setup(player.opponent()));
+		self.refresh();
+	}
+
+	/// The player playing against the computer, if playing against it.
+	fn human(&self) -> Option<Player> {
+		self.computer.as_ref().map(|ai| ai.player().opponent())
+	}
+
+	/// Whether the computer is to play.
+	fn computer_turn(&self) -> bool {
+		let (Some(ai), Some(game), Phase::Play) = (&self.computer, &self.game, self.phase) else {
+			return false;
+		};
+		game.player() == ai.player()
+	}
+
+	/// The player looking at the board, who can see the ranks of their own pieces. `None` while
+	/// the device is passed, or once the game is over.
+	fn viewer(&self) -> Option<Player> {
+		if self.phase != Phase::Over && self.human().is_some() {
+			return self.human();
+		}
+		match (self.phase, &self.game) {
+			(Phase::Setup(player), _) => Some(player),
+			(Phase::Play, Some(game)) => Some(game.player()),
+			(Phase::Played, Some(game)) => Some(game.player().opponent()),
+			_ => None,
+		}
+	}
+
+	/// Moves to the given phase, telling the players what to do.
+	fn start_phase(&mut self, phase: Phase) {
+		self.phase = phase;
+		self.hand = None;
+		self.screen.text_mut().clear();
+		match phase {
+			Phase::Setup(player) => {
+				// At the center of the front row
+				let rows = player.setup_rows();
+				let front = if player == Player::Red { rows.start } else { rows.end - 1 };
+				self.cursor = (rules::SIZE / 2, front);
+				self.screen.text_mut()[1] =
+					format!("{}, set up your army in your four back rows.", player);
+				self.screen.text_mut()[2] =
+					"Drag the pieces from the list, or type their symbol on a tile.".to_owned();
+				self.screen.text_mut()[4] =
+					"Space: pick/drop, Del: remove, r: place randomly, Enter: done".to_owned();
+			}
+			Phase::Pass(player) => {
+				self.screen.text_mut()[1] =
+					format!("Pass the device to {}, without looking!", player);
+				self.screen.text_mut()[4] = format!("{}, press Enter when you're ready", player);
+			}
+			Phase::Play => {
+				let game = self.game.as_ref().expect("Playing without a game");
+				let viewer = self.human().unwrap_or(game.player());
+				// The last move, and the one before against the computer
+				let shown = if self.human().is_some() { 2 } else { 1 };
+				for (i, &(_, combat)) in game.turns().iter().rev().take(shown).enumerate() {
+					let mover = if i == 0 { game.player().opponent() } else { game.player() };
+					self.screen.text_mut()[2 - i] = report(combat, mover, viewer);
+				}
+				self.screen.text_mut()[3] = if self.computer_turn() {
+					"The computer is thinking...".to_owned()
+				} else {
+					"Your turn!".to_owned()
+				};
+				self.screen.text_mut()[4] =
+					"Arrows and Enter, or drag a piece with the mouse to move it".to_owned();
+			}
+			Phase::Played => {
+				let game = self.game.as_ref().expect("Playing without a game");
+				let &(_, combat) = game.turns().last().expect("No move played");
+				let mover = game.player().opponent();
+				self.screen.text_mut()[2] = report(combat, mover, mover);
+				self.screen.text_mut()[4] =
+					format!("Press Enter and pass the device to {}", game.player());
+			}
+			Phase::Over => {
+				let game = self.game.as_ref().expect("Playing without a game");
+				if let Some(&(_, combat)) = game.turns().last() {
+					let mover = game.player().opponent();
+					self.screen.text_mut()[1] =
+						report(combat, mover, self.human().unwrap_or(mover));
+				}
+				self.screen.text_mut()[2] = match game.outcome().expect("The game isn't over") {
+					Outcome::FlagCaptured(winner) => {
+						format!("{} captured the flag! Well done {}!", winner, winner)
+					}
+					Outcome::NoMovablePieces(winner) => format!(
+						"{} has no piece left that can move. Well done {}!",
+						winner.opponent(),
+						winner
+					),
+					Outcome::Draw => {
+						"No one has a piece left that can move, that's a draw.".to_owned()
+					}
+				};
+				self.screen.text_mut()[4] = "Press any key to exit".to_owned();
+			}
+		}
+	}
+
+	/// Tells the player something went wrong.
+	fn refuse(&mut self, reason: String) {
+		self.screen.text_mut()[3] = reason;
+	}
+
+	/// Picks up what is under the pointer or the cursor, if it is something the player can move.
+	fn pick(&mut self, target: Target) {
+		match (self.phase, target) {
+			(Phase::Setup(player), Target::Tray(rank)) => {
+				if self.setups[player as usize].remaining(rank) > 0 {
+					self.hand = Some(target);
+				} else {
+					self.refuse(format!("All your pieces of rank {} are placed.", rank));
+				}
+			}
+			(Phase::Setup(player), Target::Tile(pos))
+				if self.setups[player as usize].get(pos).is_some() =>
+			{
+				self.hand = Some(target)
+			}
+			(Phase::Play, Target::Tile(pos)) => {
+				let game = self.game.as_ref().expect("Playing without a game");
+				match game.piece(pos) {
+					Some(piece) if piece.owner == game.player() && !piece.rank.movable() => {
+						self.refuse(format!("{}.", rules::IllegalMove::Immovable));
+					}
+					Some(piece) if piece.owner == game.player() => self.hand = Some(target),
+					_ => (),
+				}
+			}
+			_ => (),
+		}
+	}
+
+	/// Drops what was picked up at the given place, placing or moving a piece.
+	fn drop(&mut self, target: Target) {
+		let Some(held) = self.hand.take() else {
+			return;
+		};
+		match self.phase {
+			Phase::Setup(player) => {
+				let setup = &mut self.setups[player as usize];
+				let result = match (held, target) {
+					(Target::Tray(rank), Target::Tile(pos)) => setup.place(pos, rank).map(|_| ()),
+					(Target::Tile(from), Target::Tile(to)) => setup.swap(from, to),
+					(Target::Tile(from), Target::Tray(_)) => setup.remove(from).map(|_| ()),
+					// Picking another rank instead
+					(Target::Tray(_), Target::Tray(_)) => return self.pick(target),
+				};
+				if let Err(e) = result {
+					self.refuse(format!("{}.", e));
+				}
+			}
+			Phase::Play => {
+				if let (Target::Tile(from), Target::Tile(to)) = (held, target) {
+					if from != to {
+						self.play(Move { from, to });
+					}
+				}
+			}
+			_ => (),
+		}
+	}
+
+	/// Plays the given move, showing how it went.
+	fn play(&mut self, mv: Move) {
+		let game = self.game.as_mut().expect("Playing without a game");
+		match game.play(mv) {
+			Ok(_) if game.outcome().is_some() => self.start_phase(Phase::Over),
+			// No device to pass to the computer
+			Ok(_) if self.computer.is_some() => self.start_phase(Phase::Play),
+			Ok(_) => self.start_phase(Phase::Played),
+			Err(e) => self.refuse(format!("{}.", e)),
+		}
+	}
+
+	/// Ends the setup of the given player, if all their pieces are placed.
+	fn end_setup(&mut self, player: Player) {
+		let setup = &self.setups[player as usize];
+		if !setup.is_complete() {
+			let left: u8 = RANKS.iter().map(|&rank| setup.remaining(rank)).sum();
+			self.refuse(format!("{} pieces are left to place. r places them randomly.", left));
+			return;
+		}
+		if !self.setups[player.opponent() as usize].is_complete() {
+			self.start_phase(Phase::Pass(player.opponent()));
+			return;
+		}
+		let [red, blue] = &self.setups;
+		self.game = Some(rules::Stratego::new(red, blue).expect("Incomplete setup"));
+		if self.computer.is_some() {
+			self.start_phase(Phase::Play);
+		} else {
+			self.start_phase(Phase::Pass(Player::Red));
+		}
+	}
+
+	/// Moves the cursor, picks, drops or places pieces with the given key.
+	fn key(&mut self, code: KeyCode) -> Flow {
+		use KeyCode::*;
+		match (self.phase, code) {
+			(Phase::Over, _) => return Flow::Exit,
+			_ if self.computer_turn() => (),
+			(Phase::Pass(player), Enter) if self.game.is_none() => {
+				self.start_phase(Phase::Setup(player))
+			}
+			(Phase::Pass(_), Enter) => self.start_phase(Phase::Play),
+			(Phase::Played, Enter) => {
+				let next = self.game.as_ref().expect("Playing without a game").player();
+				self.start_phase(Phase::Pass(next));
+			}
+			(Phase::Setup(_) | Phase::Play, Left | Right | Up | Down) => {
+				let (dx, dy) = match code {
+					Left => (-1, 0),
+					Right => (1, 0),
+					Up => (0, -1),
+					_ => (0, 1),
+				};
+				// The arrows move on the screen, where blue's board is upside down
+				let sign = if self.viewer() == Some(Player::Blue) { -1 } else { 1 };
+				let step = |value: u8, delta: i8| {
+					(value as i8 + sign * delta).clamp(0, rules::SIZE as i8 - 1) as u8
+				};
+				self.cursor = (step(self.cursor.0, dx), step(self.cursor.1, dy));
+			}
+			(Phase::Setup(_) | Phase::Play, Char(' ')) | (Phase::Play, Enter) => match self.hand {
+				None => self.pick(Target::Tile(self.cursor)),
+				Some(_) => self.drop(Target::Tile(self.cursor)),
+			},
+			(Phase::Setup(_) | Phase::Play, Esc) => self.hand = None,
+			(Phase::Setup(player), Enter) => self.end_setup(player),
+			(Phase::Setup(player), Char('r')) => {
+				self.setups[player as usize].fill_randomly(&mut self.rng);
+				self.hand = None;
+			}
+			(Phase::Setup(player), Delete | Backspace) => {
+				if let Err(e) = self.setups[player as usize].remove(self.cursor) {
+					self.refuse(format!("{}.", e));
+				}
+			}
+			(Phase::Setup(_), Char(c)) => {
+				if let Some(rank) = Rank::from_symbol(c) {
+					self.hand = Some(Target::Tray(rank));
+					self.drop(Target::Tile(self.cursor));
+				}
+			}
+			_ => (),
+		}
+		Flow::Continue
+	}
+
+	/// Picks up what is clicked and drops it where it is clicked again or released, the cursor
+	/// following the pointer.
+	fn mouse(&mut self, event: MouseEvent) {
+		if !matches!(self.phase, Phase::Setup(_) | Phase::Play) || self.computer_turn() {
+			return;
+		}
+		let target = self.screen.mouse_event(event);
+		if let Some(Target::Tile(pos)) = target {
+			self.cursor = pos;
+		}
+		match (event.kind, target, self.hand) {
+			(MouseEventKind::Down(MouseButton::Left), Some(target), None) => self.pick(target),
+			(MouseEventKind::Down(MouseButton::Left), Some(target), Some(_)) => self.drop(target),
+			// Dragged from elsewhere
+			(MouseEventKind::Up(MouseButton::Left), Some(target), Some(held)) if target != held => {
+				self.drop(target)
+			}
+			// Dropped out of the board and the list
+			(
+				MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Up(MouseButton::Left),
+				None,
+				_,
+			) => self.hand = None,
+			_ => (),
+		}
+	}
+
+	/// Shows the board as seen by the player looking at it, and the pieces left to place or lost.
+	fn refresh(&mut self) {
+		let viewer = self.viewer();
+		let (board, panel, text) = self.screen.parts();
+		let playing = matches!(self.phase, Phase::Setup(_) | Phase::Play);
+		board.cursor = playing.then_some(self.cursor);
+		board.selected = match self.hand {
+			Some(Target::Tile(pos)) if playing => Some(pos),
+			_ => None,
+		};
+		panel.selected = match self.hand {
+			Some(Target::Tray(rank)) if playing => Some(rank),
+			_ => None,
+		};
+		board.bottom = match self.phase {
+			Phase::Setup(player) | Phase::Pass(player) => player,
+			_ => viewer.unwrap_or(Player::Red),
+		};
+		board.zone = match self.phase {
+			Phase::Setup(player) => Some(player.setup_rows()),
+			_ => None,
+		};
+		match &self.game {
+			None => {
+				for (pos, tile) in positions().zip(&mut board.tiles) {
+					*tile = match self.setups.iter().find_map(|s| Some((s.player(), s.get(pos)?))) {
+						Some((owner, rank)) if Some(owner) == viewer => {
+							Shown::Piece(owner, Some(rank))
+						}
+						Some((owner, _)) => Shown::Piece(owner, None),
+						None if is_lake(pos) => Shown::Lake,
+						None => Shown::Land,
+					};
+				}
+				panel.title = "Left to place".to_owned();
+				panel.counts = RANKS
+					.iter()
+					.map(|&rank| match viewer {
+						Some(player) => vec![(self.setups[player as usize].remaining(rank), None)],
+						None => vec![],
+					})
+					.collect();
+				board.last_move = None;
+			}
+			Some(game) => {
+				let over = self.phase == Phase::Over;
+				for (pos, tile) in positions().zip(&mut board.tiles) {
+					*tile = match game.piece(pos) {
+						Some(piece) if over || piece.revealed || Some(piece.owner) == viewer => {
+							Shown::Piece(piece.owner, Some(piece.rank))
+						}
+						Some(piece) => Shown::Piece(piece.owner, None),
+						None if is_lake(pos) => Shown::Lake,
+						None => Shown::Land,
+					};
+				}
+				panel.title = "Lost pieces".to_owned();
+				panel.counts = RANKS
+					.iter()
+					.map(|&rank| {
+						[Player::Red, Player::Blue]
+							.map(|player| (game.lost(player, rank), Some(color(player))))
+							.to_vec()
+					})
+					.collect();
+				board.last_move =
+					game.turns().last().map(|&(mv, _)| mv).filter(|_| viewer.is_some() || over);
+			}
+		}
+		text[0] = match (self.phase, &self.game) {
+			(Phase::Setup(player), _) => format!("{} sets up their army", player),
+			(Phase::Pass(player), _) => format!("{}'s turn", player),
+			(Phase::Play, Some(game)) => format!("{}'s turn", game.player()),
+			(Phase::Played, Some(game)) => format!("{} played", game.player().opponent()),
+			_ => "Game over".to_owned(),
+		};
+	}
+}
+
+/// Tells the given viewer how the last move of the given player went.
+fn report(combat: Option<Combat>, mover: Player, viewer: Player) -> String {
+	let Some(combat) = combat else {
+		return if viewer == mover {
+			"Done.".to_owned()
+		} else {
+			format!("{} moved a piece.", mover)
+		};
+	};
+	let (attacker, defender) = if viewer == mover {
+		(format!("Your {}", combat.attacker), format!("a {}", combat.defender))
+	} else {
+		(format!("{}'s {}", mover, combat.attacker), format!("your {}", combat.defender))
+	};
+	let end = match combat.fight {
+		Fight::Won => "and won",
+		Fight::Lost => "and lost",
+		Fight::Tie => "and both fell",
+	};
+	format!("{} attacked {} {}.", attacker, defender, end)
+}
+
+impl EventHandler for GameState {
+	fn update(&mut self, event: Event) -> Flow {
+		let flow = match event {
+			Event::Key(KeyEvent { code, kind: KeyEventKind::Press, .. }) => self.key(code),
+			Event::Resize(..) => {
+				self.renderer.invalidate();
+				Flow::Continue
+			}
+			Event::Mouse(event) => {
+				self.mouse(event);
+				Flow::Continue
+			}
+			// The computer plays on the first tick of its turn, once the last move is displayed
+			Event::Tick if self.computer_turn() => {
+				let game = self.game.as_ref().expect("Playing without a game");
+				let ai = self.computer.as_mut().expect("Playing without the computer");
+				let mv = ai.best_move(game).expect("The computer found no move");
+				self.play(mv);
+				Flow::Continue
+			}
+			_ => Flow::Continue,
+		};
+		self.refresh();
+		flow
+	}
+
+	fn render(&mut self, out: &mut dyn io::Write) -> io::Result<()> {
+		out.queue(cursor::Hide)?;
+		self.renderer.render(out, Buffer::from_widget(&self.screen))
+	}
+
+	fn tick_rate(&self) -> Option<Duration> {
+		self.computer_turn().then_some(Duration::from_millis(300))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::backend::VirtualTerminal;
+	use crossterm::event::KeyModifiers;
+
+	/// The screen coordinates of the symbol of the given tile, with red at the bottom.
+	fn at((x, y): Pos) -> (u16, u16) {
+		(2 + 3 * x as u16, 1 + y as u16)
+	}
+
+	/// The screen coordinates of the given rank in the panel.
+	fn in_panel(rank: Rank) -> (u16, u16) {
+		let line = RANKS.iter().position(|&r| r == rank).unwrap();
+		((Board::WIDTH + Screen::GAP) as u16, 1 + line as u16)
+	}
+
+	/// The given line of the texts under the board.
+	fn text(state: &GameState, line: usize) -> &str {
+		&state.screen.0[1].downcast_ref::<Text<TEXT_HEIGHT>>().unwrap()[line]
+	}
+
+	fn mouse(state: &mut GameState, kind: MouseEventKind, (column, row): (u16, u16)) {
+		let modifiers = KeyModifiers::NONE;
+		state.update(Event::Mouse(MouseEvent { kind, column, row, modifiers }));
+	}
+
+	/// Drags with the mouse from a place to another.
+	fn drag(state: &mut GameState, from: (u16, u16), to: (u16, u16)) {
+		mouse(state, MouseEventKind::Down(MouseButton::Left), from);
+		mouse(state, MouseEventKind::Drag(MouseButton::Left), to);
+		mouse(state, MouseEventKind::Up(MouseButton::Left), to);
+	}
+
+	fn keys(state: &mut GameState, codes: impl IntoIterator<Item = KeyCode>) {
+		for code in codes {
+			state.update(Event::Key(code.into()));
+		}
+	}
+
+	/// The screen coordinates of the symbol of the given tile, with blue at the bottom.
+	fn flipped((x, y): Pos) -> (u16, u16) {
+		at((rules::SIZE - 1 - x, rules::SIZE - 1 - y))
+	}
+
+	/// The symbols shown on the given row of the board.
+	fn symbols(term: &VirtualTerminal, row: u16) -> String {
+		let cell = |x| term.screen().cell(2 + 3 * x, 1 + row).content.clone();
+		(0..rules::SIZE as u16).map(cell).collect()
+	}
+
+	/// Sets up both armies at random, the given pieces being placed first, and starts the game.
+	fn start(pieces: &[(Pos, Rank, Player)]) -> GameState {
+		let mut state = GameState::new(0);
+		for &(pos, rank, player) in pieces {
+			state.setups[player as usize].place(pos, rank).unwrap();
+		}
+		keys(&mut state, [KeyCode::Char('r'), KeyCode::Enter, KeyCode::Enter]);
+		keys(&mut state, [KeyCode::Char('r'), KeyCode::Enter, KeyCode::Enter]);
+		assert_eq!(state.phase, Phase::Play);
+		state
+	}
+
+	#[test]
+	fn setup() {
+		let mut state = GameState::new(0);
+		let setup = |state: &GameState| state.setups[Player::Red as usize].clone();
+		// From the list to the board, then from tile to tile
+		drag(&mut state, in_panel(Rank::Marshal), at((3, 6)));
+		assert_eq!(setup(&state).get((3, 6)), Some(Rank::Marshal));
+		drag(&mut state, at((3, 6)), at((3, 9)));
+		assert_eq!(setup(&state).get((3, 9)), Some(Rank::Marshal));
+		assert_eq!(setup(&state).get((3, 6)), None);
+		assert_eq!(state.cursor, (3, 9));
+		// Clicking the piece then the tile
+		mouse(&mut state, MouseEventKind::Down(MouseButton::Left), in_panel(Rank::Flag));
+		mouse(&mut state, MouseEventKind::Up(MouseButton::Left), in_panel(Rank::Flag));
+		assert_eq!(state.hand, Some(Target::Tray(Rank::Flag)));
+		mouse(&mut state, MouseEventKind::Down(MouseButton::Left), at((0, 9)));
+		assert_eq!(setup(&state).get((0, 9)), Some(Rank::Flag));
+		assert_eq!(state.hand, None);
+		// Out of the setup rows, or none left
+		drag(&mut state, in_panel(Rank::Bomb), at((0, 5)));
+		assert_eq!(text(&state, 3), "Pieces are placed in your four back rows.");
+		drag(&mut state, in_panel(Rank::Flag), at((1, 9)));
+		assert_eq!(text(&state, 3), "All your pieces of rank flag are placed.");
+		// Back to the list
+		drag(&mut state, at((0, 9)), in_panel(Rank::Spy));
+		assert_eq!(setup(&state).remaining(Rank::Flag), 1);
+
+		// With the keyboard, from the last tile clicked
+		keys(&mut state, [KeyCode::Right, KeyCode::Char('b'), KeyCode::Right, KeyCode::Char('s')]);
+		assert_eq!(setup(&state).get((1, 9)), Some(Rank::Bomb));
+		assert_eq!(setup(&state).get((2, 9)), Some(Rank::Spy));
+		keys(&mut state, [KeyCode::Delete, KeyCode::Enter]);
+		assert_eq!(text(&state, 3), "38 pieces are left to place. r places them randomly.");
+		assert_eq!(state.phase, Phase::Setup(Player::Red));
+		keys(&mut state, [KeyCode::Char('r'), KeyCode::Enter]);
+		assert!(setup(&state).is_complete());
+		assert_eq!(setup(&state).get((3, 9)), Some(Rank::Marshal));
+		assert_eq!(state.phase, Phase::Pass(Player::Blue));
+
+		// Blue sees red's army hidden, upside down
+		keys(&mut state, [KeyCode::Enter]);
+		assert_eq!(state.phase, Phase::Setup(Player::Blue));
+		let mut term = VirtualTerminal::new((70, 18));
+		state.render(&mut term).unwrap();
+		assert_eq!(symbols(&term, 0), "??????????");
+		assert_eq!(symbols(&term, 6), "··········");
+		assert_eq!(symbols(&term, 4), "  ~~  ~~  ");
+		assert!(term.screen().find("Blue sets up their army").is_some());
+	}
+
+	#[test]
+	fn hidden_information() {
+		let red = [((0, 6), Rank::Marshal, Player::Red), ((0, 3), Rank::Sergeant, Player::Blue)];
+		let mut state = start(&red);
+		let mut term = VirtualTerminal::new((70, 18));
+		state.render(&mut term).unwrap();
+		// Red only sees their own pieces
+		assert_eq!(&symbols(&term, 6)[..1], "M");
+		assert_eq!(symbols(&term, 3), "??????????");
+		assert!(term.screen().find("Red's turn").is_some());
+
+		// Immovable pieces and enemy pieces can't be picked up
+		let bomb = positions().find(|&pos| state.setups[0].get(pos) == Some(Rank::Bomb)).unwrap();
+		drag(&mut state, at(bomb), at((bomb.0, 5)));
+		assert_eq!(text(&state, 3), "Bombs and flags can't move.");
+		drag(&mut state, at((0, 6)), at((0, 5)));
+		assert_eq!(state.phase, Phase::Played);
+		assert_eq!(text(&state, 2), "Done.");
+
+		// The board is hidden while the device is passed
+		keys(&mut state, [KeyCode::Enter]);
+		assert_eq!(state.phase, Phase::Pass(Player::Blue));
+		state.render(&mut term).unwrap();
+		assert_eq!(symbols(&term, 9), "??????????");
+		keys(&mut state, [KeyCode::Enter]);
+		state.render(&mut term).unwrap();
+		assert_eq!(text(&state, 2), "Red moved a piece.");
+		// Blue's sergeant at the bottom right, and red's marshal hidden
+		assert_eq!(&symbols(&term, 6)[9..], "4");
+		assert_eq!(&symbols(&term, 4)[9..], "?");
+
+		// The sergeant advances, and the marshal attacks it
+		drag(&mut state, flipped((0, 3)), flipped((0, 4)));
+		keys(&mut state, [KeyCode::Enter]);
+		assert_eq!(state.phase, Phase::Pass(Player::Red));
+		keys(&mut state, [KeyCode::Enter]);
+		drag(&mut state, at((0, 5)), at((0, 4)));
+		assert_eq!(text(&state, 2), "Your marshal attacked a sergeant and won.");
+		keys(&mut state, [KeyCode::Enter, KeyCode::Enter]);
+		assert_eq!(text(&state, 2), "Red's marshal attacked your sergeant and won.");
+		// Blue now knows the marshal
+		state.render(&mut term).unwrap();
+		assert_eq!(&symbols(&term, 5)[9..], "M");
+		let lost = term.screen().find("sergeant").unwrap();
+		assert_eq!(term.screen().line(lost.1).split_whitespace().last(), Some("1"));
+	}
+
+	#[test]
+	fn full_game() {
+		// Red's scout runs to blue's flag
+		let pieces = [
+			((0, 6), Rank::Scout, Player::Red),
+			((0, 3), Rank::Flag, Player::Blue),
+			((1, 3), Rank::Scout, Player::Blue),
+		];
+		let mut state = start(&pieces);
+		drag(&mut state, at((0, 6)), at((0, 4)));
+		keys(&mut state, [KeyCode::Enter, KeyCode::Enter]);
+		// Blue's scout runs forward
+		drag(&mut state, flipped((1, 3)), flipped((1, 4)));
+		assert_eq!(state.phase, Phase::Played);
+		keys(&mut state, [KeyCode::Enter, KeyCode::Enter]);
+		drag(&mut state, at((0, 4)), at((0, 3)));
+		assert_eq!(state.phase, Phase::Over);
+		assert_eq!(text(&state, 2), "Red captured the flag! Well done Red!");
+
+		// All the pieces are shown, and any key exits
+		let mut term = VirtualTerminal::new((70, 18));
+		state.render(&mut term).unwrap();
+		assert!(!symbols(&term, 0).contains('?'));
+		assert_eq!(state.update(Event::Key(KeyCode::Char('a').into())), Flow::Exit);
+	}
+
+	#[test]
+	fn against_computer() {
+		// The computer sets up as soon as it is given the game
+		let mut state = GameState::new(0);
+		state.play_against(Ai::new(Player::Blue, 0));
+		assert!(state.setups[Player::Blue as usize].is_complete());
+		assert_eq!(state.tick_rate(), None);
+		keys(&mut state, [KeyCode::Char('r'), KeyCode::Enter]);
+		assert_eq!(state.phase, Phase::Play);
+		assert!(!state.computer_turn());
+
+		// No device to pass: the computer answers on the next tick
+		let scout = (0..rules::SIZE)
+			.map(|x| (x, 6))
+			.find(|&pos| state.setups[0].get(pos).is_some_and(|rank| rank.movable()))
+			.unwrap();
+		drag(&mut state, at(scout), at((scout.0, 5)));
+		assert_eq!(state.phase, Phase::Play);
+		assert!(state.computer_turn());
+		assert!(state.tick_rate().is_some());
+		keys(&mut state, [KeyCode::Up, KeyCode::Enter]);
+		assert_eq!(state.game.as_ref().unwrap().turns().len(), 1);
+		state.update(Event::Tick);
+		assert_eq!(state.game.as_ref().unwrap().turns().len(), 2);
+		assert!(!state.computer_turn());
+		assert_eq!(text(&state, 3), "Your turn!");
+
+		// The computer's pieces stay hidden
+		let mut term = VirtualTerminal::new((70, 18));
+		state.render(&mut term).unwrap();
+		assert!(symbols(&term, 0).contains('?'));
+	}
+
+	#[test]
+	fn computer_plays_first() {
+		let mut state = GameState::new(0);
+		state.play_against(Ai::new(Player::Red, 0));
+		keys(&mut state, [KeyCode::Char('r'), KeyCode::Enter]);
+		assert_eq!(state.phase, Phase::Play);
+		assert!(state.computer_turn());
+		state.update(Event::Tick);
+		assert_eq!(state.game.as_ref().unwrap().turns().len(), 1);
+		assert!(text(&state, 2).starts_with("Red"));
+		assert_eq!(text(&state, 3), "Your turn!");
+	}
+}
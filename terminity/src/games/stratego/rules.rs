@@ -0,0 +1,763 @@
+//! The rules of Stratego, independent of how the game is displayed.
+//!
+//! The board has 10×10 tiles indexed by `(x, y)` coordinates, `(0, 0)` being the top left one,
+//! with two lakes of 2×2 tiles in its middle rows. Red sets up their army in the four bottom rows
+//! and blue in the four top ones, then red moves first.
+//!
+//! Each player only knows the ranks of their own pieces, and of the enemy pieces revealed by a
+//! combat. The game is won by capturing the enemy flag, or when the enemy has no piece left that
+//! can move.
+//!
+//! ```
+//! use terminity::games::stratego::rules::{Move, Player, Rank, Setup, Stratego};
+//!
+//! let mut rng = rand::thread_rng();
+//! let mut red = Setup::new(Player::Red);
+//! red.place((0, 6), Rank::Scout).unwrap();
+//! red.fill_randomly(&mut rng);
+//! let blue = Setup::random(Player::Blue, &mut rng);
+//! let mut game = Stratego::new(&red, &blue).unwrap();
+//! // The scout runs up to the last empty tile before the blue army
+//! game.play(Move { from: (0, 6), to: (0, 4) }).unwrap();
+//! assert_eq!(game.player(), Player::Blue);
+//! ```
+
+use std::fmt::{self, Display, Formatter};
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// The coordinates of a tile, as `(x, y)`.
+pub type Pos = (u8, u8);
+
+/// The number of tiles on each side of the board.
+pub const SIZE: u8 = 10;
+
+/// The number of rows each player sets up their army in.
+pub const SETUP_ROWS: u8 = 4;
+
+/// The ranks of the pieces of an army, from the weakest to the strongest, then the pieces that
+/// can't move.
+pub const RANKS: [Rank; 12] = [
+	Rank::Spy,
+	Rank::Scout,
+	Rank::Miner,
+	Rank::Sergeant,
+	Rank::Lieutenant,
+	Rank::Captain,
+	Rank::Major,
+	Rank::Colonel,
+	Rank::General,
+	Rank::Marshal,
+	Rank::Bomb,
+	Rank::Flag,
+];
+
+/// A player, named after the color of their army. Red moves first.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Player {
+	Red = 0,
+	Blue = 1,
+}
+
+/// The rank of a piece. The ranks that can move are ordered by strength.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Rank {
+	/// Rank 1, but defeats the marshal when attacking it.
+	Spy = 1,
+	/// Rank 2, moves any number of empty tiles in a straight line.
+	Scout,
+	/// Rank 3, defuses the bombs.
+	Miner,
+	Sergeant,
+	Lieutenant,
+	Captain,
+	Major,
+	Colonel,
+	General,
+	/// Rank 10, the strongest.
+	Marshal,
+	/// Can't move, and defeats any piece attacking it but the miners.
+	Bomb,
+	/// Can't move, and its capture wins the game.
+	Flag,
+}
+
+/// A piece on the board.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Piece {
+	pub rank: Rank,
+	pub owner: Player,
+	/// Whether the rank of the piece was shown to the opponent, by a combat.
+	pub revealed: bool,
+	/// Whether the piece ever moved, showing the opponent it isn't a bomb nor the flag.
+	pub moved: bool,
+}
+
+/// What a player knows about a piece on the board.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Known {
+	pub owner: Player,
+	/// The rank of the piece, if it is one of the player's or was revealed.
+	pub rank: Option<Rank>,
+	/// Whether the piece ever moved.
+	pub moved: bool,
+}
+
+/// A move: the tile of the piece moved, and where it goes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Move {
+	pub from: Pos,
+	pub to: Pos,
+}
+
+/// How a combat ended, for the attacker.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Fight {
+	/// The defender is removed, and the attacker takes its tile.
+	Won,
+	/// The attacker is removed.
+	Lost,
+	/// Both pieces have the same rank, and are both removed.
+	Tie,
+}
+
+/// A combat, happening when a piece moves to a tile taken by an enemy piece.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Combat {
+	pub attacker: Rank,
+	pub defender: Rank,
+	pub fight: Fight,
+}
+
+/// How the game ended, with its winner.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Outcome {
+	/// The player captured the enemy flag.
+	FlagCaptured(Player),
+	/// The opponent of the player has no piece left that can move.
+	NoMovablePieces(Player),
+	/// No player has a piece left that can move.
+	Draw,
+}
+
+/// Why a move can't be played.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum IllegalMove {
+	/// The game is over.
+	GameOver,
+	/// There is no piece of the player on the starting tile.
+	NotYourPiece,
+	/// Bombs and flags can't move.
+	Immovable,
+	/// The destination is a lake, or off the board.
+	Lake,
+	/// The destination is taken by a piece of the player.
+	OwnPiece,
+	/// The piece can't go that far, or not in that direction.
+	Unreachable,
+	/// A scout can't run through pieces nor lakes.
+	Blocked,
+}
+
+/// Why a setup can't be used.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SetupError {
+	/// The tile is out of the rows the player sets up their army in.
+	OutOfZone,
+	/// All the pieces of the rank are already placed.
+	NoneLeft(Rank),
+	/// Some pieces aren't placed yet.
+	Incomplete,
+	/// The setup is of the other player.
+	WrongPlayer,
+}
+
+/// Where a player placed the pieces of their army before the game.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Setup {
+	player: Player,
+	/// The ranks placed in the setup rows of the player, indexed as `x + SIZE * row`, the rows
+	/// being counted from the top of the board
+	tiles: Vec<Option<Rank>>,
+}
+
+/// A game of Stratego: the pieces on the board, and the turns played so far.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stratego {
+	/// The pieces, indexed as `x + SIZE * y`
+	board: Vec<Option<Piece>>,
+	/// The player who plays next
+	player: Player,
+	/// The moves played so far, with the combat they led to
+	turns: Vec<(Move, Option<Combat>)>,
+	outcome: Option<Outcome>,
+}
+
+impl Player {
+	/// The other player.
+	pub fn opponent(self) -> Self {
+		match self {
+			Self::Red => Self::Blue,
+			Self::Blue => Self::Red,
+		}
+	}
+
+	/// The rows the player sets up their army in.
+	pub fn setup_rows(self) -> std::ops::Range<u8> {
+		match self {
+			Self::Red => SIZE - SETUP_ROWS..SIZE,
+			Self::Blue => 0..SETUP_ROWS,
+		}
+	}
+}
+
+impl Rank {
+	/// The number of pieces of the rank in an army.
+	pub fn count(self) -> u8 {
+		match self {
+			Self::Flag | Self::Spy | Self::General | Self::Marshal => 1,
+			Self::Colonel => 2,
+			Self::Major => 3,
+			Self::Sergeant | Self::Lieutenant | Self::Captain => 4,
+			Self::Miner => 5,
+			Self::Bomb => 6,
+			Self::Scout => 8,
+		}
+	}
+
+	/// Whether pieces of the rank can move.
+	pub fn movable(self) -> bool {
+		!matches!(self, Self::Bomb | Self::Flag)
+	}
+
+	/// The character the rank is shown with: its number, but `S` for the spy, `M` for the
+	/// marshal, `B` for the bombs and `F` for the flag.
+	pub fn symbol(self) -> char {
+		match self {
+			Self::Spy => 'S',
+			Self::Marshal => 'M',
+			Self::Bomb => 'B',
+			Self::Flag => 'F',
+			rank => char::from(b'0' + rank as u8),
+		}
+	}
+
+	/// The rank shown with the given character, as given by [`symbol`](Self::symbol), ignoring
+	/// the case.
+	pub fn from_symbol(symbol: char) -> Option<Self> {
+		RANKS.into_iter().find(|rank| rank.symbol() == symbol.to_ascii_uppercase())
+	}
+
+	/// The outcome of a combat of a piece of this rank attacking one of the given rank.
+	pub fn attack(self, defender: Rank) -> Fight {
+		match (self, defender) {
+			(_, Self::Flag) | (Self::Spy, Self::Marshal) | (Self::Miner, Self::Bomb) => Fight::Won,
+			(_, Self::Bomb) => Fight::Lost,
+			(attacker, defender) if attacker == defender => Fight::Tie,
+			(attacker, defender) if attacker > defender => Fight::Won,
+			_ => Fight::Lost,
+		}
+	}
+}
+
+impl Display for Player {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::Red => "Red",
+			Self::Blue => "Blue",
+		})
+	}
+}
+
+impl Display for Rank {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::Spy => "spy",
+			Self::Scout => "scout",
+			Self::Miner => "miner",
+			Self::Sergeant => "sergeant",
+			Self::Lieutenant => "lieutenant",
+			Self::Captain => "captain",
+			Self::Major => "major",
+			Self::Colonel => "colonel",
+			Self::General => "general",
+			Self::Marshal => "marshal",
+			Self::Bomb => "bomb",
+			Self::Flag => "flag",
+		})
+	}
+}
+
+impl Display for IllegalMove {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::GameOver => "The game is over",
+			Self::NotYourPiece => "There is none of your pieces there",
+			Self::Immovable => "Bombs and flags can't move",
+			Self::Lake => "Pieces can't go in the lakes",
+			Self::OwnPiece => "One of your pieces is already there",
+			Self::Unreachable => "The piece can't go there",
+			Self::Blocked => "Scouts can't run through pieces nor lakes",
+		})
+	}
+}
+
+impl Display for SetupError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::OutOfZone => f.write_str("Pieces are placed in your four back rows"),
+			Self::NoneLeft(rank) => write!(f, "All your pieces of rank {} are placed", rank),
+			Self::Incomplete => f.write_str("Some pieces aren't placed yet"),
+			Self::WrongPlayer => f.write_str("The setup is of the other player"),
+		}
+	}
+}
+
+/// Whether the given tile is in a lake.
+pub fn is_lake((x, y): Pos) -> bool {
+	(4..=5).contains(&y) && matches!(x, 2 | 3 | 6 | 7)
+}
+
+/// The coordinates of all the tiles of the board, row by row.
+pub fn positions() -> impl Iterator<Item = Pos> {
+	(0..SIZE).flat_map(|y| (0..SIZE).map(move |x| (x, y)))
+}
+
+/// The index of the given tile in the board.
+fn index((x, y): Pos) -> usize {
+	x as usize + SIZE as usize * y as usize
+}
+
+impl Setup {
+	/// A setup of the given player where no piece is placed yet.
+	pub fn new(player: Player) -> Self {
+		Self { player, tiles: vec![None; (SIZE * SETUP_ROWS) as usize] }
+	}
+
+	/// A setup of the given player with all the pieces placed at random.
+	pub fn random(player: Player, rng: &mut impl Rng) -> Self {
+		let mut setup = Self::new(player);
+		setup.fill_randomly(rng);
+		setup
+	}
+
+	/// The player whose army is set up.
+	pub fn player(&self) -> Player {
+		self.player
+	}
+
+	/// The rank placed on the given tile, if any.
+	pub fn get(&self, pos: Pos) -> Option<Rank> {
+		self.index(pos).ok().and_then(|i| self.tiles[i])
+	}
+
+	/// The number of pieces of the given rank left to place.
+	pub fn remaining(&self, rank: Rank) -> u8 {
+		rank.count() - self.tiles.iter().filter(|&&tile| tile == Some(rank)).count() as u8
+	}
+
+	/// Whether all the pieces are placed.
+	pub fn is_complete(&self) -> bool {
+		self.tiles.iter().all(Option::is_some)
+	}
+
+	/// Places a piece of the given rank on the given tile, giving back the rank of the piece that
+	/// was there, if any.
+	pub fn place(&mut self, pos: Pos, rank: Rank) -> Result<Option<Rank>, SetupError> {
+		let i = self.index(pos)?;
+		if self.tiles[i] != Some(rank) && self.remaining(rank) == 0 {
+			return Err(SetupError::NoneLeft(rank));
+		}
+		Ok(self.tiles[i].replace(rank))
+	}
+
+	/// Removes the piece on the given tile, giving back its rank if there was one.
+	pub fn remove(&mut self, pos: Pos) -> Result<Option<Rank>, SetupError> {
+		let i = self.index(pos)?;
+		Ok(self.tiles[i].take())
+	}
+
+	/// Swaps the pieces of the given tiles, any of them being possibly empty.
+	pub fn swap(&mut self, a: Pos, b: Pos) -> Result<(), SetupError> {
+		let (a, b) = (self.index(a)?, self.index(b)?);
+		self.tiles.swap(a, b);
+		Ok(())
+	}
+
+	/// Places the pieces left to place at random on the empty tiles.
+	pub fn fill_randomly(&mut self, rng: &mut impl Rng) {
+		let mut left: Vec<_> = RANKS
+			.into_iter()
+			.flat_map(|rank| std::iter::repeat_n(rank, self.remaining(rank) as usize))
+			.collect();
+		left.shuffle(rng);
+		for (tile, rank) in self.tiles.iter_mut().filter(|tile| tile.is_none()).zip(left) {
+			*tile = Some(rank);
+		}
+	}
+
+	/// The index of the given tile in `tiles`, if it is in the setup rows of the player.
+	fn index(&self, (x, y): Pos) -> Result<usize, SetupError> {
+		let rows = self.player.setup_rows();
+		if x >= SIZE || !rows.contains(&y) {
+			return Err(SetupError::OutOfZone);
+		}
+		Ok(x as usize + SIZE as usize * (y - rows.start) as usize)
+	}
+}
+
+impl Stratego {
+	/// A game starting with the armies of the given setups, which must be complete.
+	pub fn new(red: &Setup, blue: &Setup) -> Result<Self, SetupError> {
+		let mut board = vec![None; (SIZE * SIZE) as usize];
+		for (setup, owner) in [(red, Player::Red), (blue, Player::Blue)] {
+			if setup.player != owner {
+				return Err(SetupError::WrongPlayer);
+			}
+			if !setup.is_complete() {
+				return Err(SetupError::Incomplete);
+			}
+			for y in owner.setup_rows() {
+				for x in 0..SIZE {
+					board[index((x, y))] = setup.get((x, y)).map(|rank| Piece {
+						rank,
+						owner,
+						revealed: false,
+						moved: false,
+					});
+				}
+			}
+		}
+		Ok(Self { board, player: Player::Red, turns: vec![], outcome: None })
+	}
+
+	/// The player who plays next.
+	pub fn player(&self) -> Player {
+		self.player
+	}
+
+	/// The piece on the given tile, if any. This is the actual piece, that its owner's opponent
+	/// may not know the rank of.
+	pub fn piece(&self, pos: Pos) -> Option<Piece> {
+		self.board[index(pos)]
+	}
+
+	/// What the given player knows about the piece on the given tile, if any: the ranks of the
+	/// enemy pieces are hidden until revealed.
+	pub fn known(&self, pos: Pos, player: Player) -> Option<Known> {
+		self.board[index(pos)].map(|piece| Known {
+			owner: piece.owner,
+			rank: (piece.owner == player || piece.revealed).then_some(piece.rank),
+			moved: piece.moved,
+		})
+	}
+
+	/// The moves played so far, with the combat they led to.
+	pub fn turns(&self) -> &[(Move, Option<Combat>)] {
+		&self.turns
+	}
+
+	/// How the game ended, if it did.
+	pub fn outcome(&self) -> Option<Outcome> {
+		self.outcome
+	}
+
+	/// The number of pieces of the given rank the given player lost.
+	pub fn lost(&self, player: Player, rank: Rank) -> u8 {
+		let left = self.board.iter().flatten().filter(|p| p.owner == player && p.rank == rank);
+		rank.count() - left.count() as u8
+	}
+
+	/// Checks whether the given move can be played, telling why it can't.
+	pub fn check(&self, mv: Move) -> Result<(), IllegalMove> {
+		if self.outcome.is_some() {
+			return Err(IllegalMove::GameOver);
+		}
+		let piece = match self.get(mv.from) {
+			Some(piece) if piece.owner == self.player => piece,
+			_ => return Err(IllegalMove::NotYourPiece),
+		};
+		if !piece.rank.movable() {
+			return Err(IllegalMove::Immovable);
+		}
+		if mv.to.0 >= SIZE || mv.to.1 >= SIZE || is_lake(mv.to) {
+			return Err(IllegalMove::Lake);
+		}
+		if self.get(mv.to).is_some_and(|target| target.owner == self.player) {
+			return Err(IllegalMove::OwnPiece);
+		}
+		let (dx, dy) = (mv.from.0.abs_diff(mv.to.0), mv.from.1.abs_diff(mv.to.1));
+		let distance = dx + dy;
+		if (dx != 0 && dy != 0) || distance == 0 {
+			return Err(IllegalMove::Unreachable);
+		}
+		if distance > 1 {
+			if piece.rank != Rank::Scout {
+				return Err(IllegalMove::Unreachable);
+			}
+			let step = |from: u8, to: u8| (to as i8 - from as i8).signum();
+			let (sx, sy) = (step(mv.from.0, mv.to.0), step(mv.from.1, mv.to.1));
+			let blocked = (1..distance as i8).any(|i| {
+				let pos = ((mv.from.0 as i8 + sx * i) as u8, (mv.from.1 as i8 + sy * i) as u8);
+				is_lake(pos) || self.get(pos).is_some()
+			});
+			if blocked {
+				return Err(IllegalMove::Blocked);
+			}
+		}
+		Ok(())
+	}
+
+	/// The moves the given piece can play, none if it isn't one of the next player.
+	pub fn moves_from(&self, from: Pos) -> Vec<Move> {
+		if self.outcome.is_some() {
+			return vec![];
+		}
+		self.piece_moves(self.player, from)
+	}
+
+	/// The moves the given piece can play if it is one of the given player, even if the game is
+	/// over.
+	fn piece_moves(&self, player: Player, from: Pos) -> Vec<Move> {
+		const DIRECTIONS: [(i8, i8); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+		let Some(piece) = self.get(from).filter(|piece| piece.owner == player) else {
+			return vec![];
+		};
+		if !piece.rank.movable() {
+			return vec![];
+		}
+		let range = if piece.rank == Rank::Scout { SIZE } else { 1 };
+		let mut moves = vec![];
+		for (dx, dy) in DIRECTIONS {
+			let mut to = from;
+			for _ in 0..range {
+				let (x, y) = (to.0 as i8 + dx, to.1 as i8 + dy);
+				if !(0..SIZE as i8).contains(&x) || !(0..SIZE as i8).contains(&y) {
+					break;
+				}
+				to = (x as u8, y as u8);
+				if is_lake(to) {
+					break;
+				}
+				match self.get(to) {
+					None => moves.push(Move { from, to }),
+					Some(target) => {
+						if target.owner != player {
+							moves.push(Move { from, to });
+						}
+						break;
+					}
+				}
+			}
+		}
+		moves
+	}
+
+	/// The moves the next player can play, none if the game is over.
+	pub fn legal_moves(&self) -> Vec<Move> {
+		positions().flat_map(|from| self.moves_from(from)).collect()
+	}
+
+	/// Plays the given move for the next player, giving the combat it led to if it attacked an
+	/// enemy piece. Both pieces of a combat are revealed.
+	pub fn play(&mut self, mv: Move) -> Result<Option<Combat>, IllegalMove> {
+		self.check(mv)?;
+		let mut attacker = self.board[index(mv.from)].take().expect("Checked move");
+		attacker.moved = true;
+		let combat = match self.board[index(mv.to)] {
+			None => {
+				self.board[index(mv.to)] = Some(attacker);
+				None
+			}
+			Some(mut defender) => {
+				let fight = attacker.rank.attack(defender.rank);
+				attacker.revealed = true;
+				defender.revealed = true;
+				self.board[index(mv.to)] = match fight {
+					Fight::Won => Some(attacker),
+					Fight::Lost => Some(defender),
+					Fight::Tie => None,
+				};
+				if defender.rank == Rank::Flag {
+					self.outcome = Some(Outcome::FlagCaptured(self.player));
+				}
+				Some(Combat { attacker: attacker.rank, defender: defender.rank, fight })
+			}
+		};
+		self.turns.push((mv, combat));
+		self.player = self.player.opponent();
+		if self.outcome.is_none() {
+			self.outcome = match (self.can_move(self.player), self.can_move(self.player.opponent()))
+			{
+				(true, _) => None,
+				(false, true) => Some(Outcome::NoMovablePieces(self.player.opponent())),
+				(false, false) => Some(Outcome::Draw),
+			};
+		}
+		Ok(combat)
+	}
+
+	/// Whether the given player has a piece that can move somewhere.
+	fn can_move(&self, player: Player) -> bool {
+		positions().any(|from| !self.piece_moves(player, from).is_empty())
+	}
+
+	/// The piece on the given tile, `None` if off the board.
+	fn get(&self, (x, y): Pos) -> Option<Piece> {
+		if x >= SIZE || y >= SIZE {
+			return None;
+		}
+		self.board[index((x, y))]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rand::rngs::StdRng;
+	use rand::SeedableRng;
+
+	/// A game where the given pieces are alone on the board, the flags being hidden in the
+	/// corners behind bombs if not given.
+	fn alone(pieces: &[(Pos, Rank, Player)]) -> Stratego {
+		let mut board = vec![None; (SIZE * SIZE) as usize];
+		let corners = [((0, 9), (0, 8), Player::Red), ((9, 0), (9, 1), Player::Blue)];
+		for (flag, bomb, owner) in corners {
+			if !pieces.iter().any(|&(_, rank, player)| rank == Rank::Flag && player == owner) {
+				let piece = |rank| Some(Piece { rank, owner, revealed: false, moved: false });
+				board[index(flag)] = piece(Rank::Flag);
+				board[index(bomb)] = piece(Rank::Bomb);
+				board[index((flag.0.abs_diff(1), flag.1))] = piece(Rank::Bomb);
+			}
+		}
+		for &(pos, rank, owner) in pieces {
+			board[index(pos)] = Some(Piece { rank, owner, revealed: false, moved: false });
+		}
+		Stratego { board, player: Player::Red, turns: vec![], outcome: None }
+	}
+
+	#[test]
+	fn combats() {
+		use Rank::*;
+		assert_eq!(Marshal.attack(General), Fight::Won);
+		assert_eq!(Sergeant.attack(Captain), Fight::Lost);
+		assert_eq!(Major.attack(Major), Fight::Tie);
+		assert_eq!(Spy.attack(Marshal), Fight::Won);
+		assert_eq!(Marshal.attack(Spy), Fight::Won);
+		assert_eq!(Spy.attack(General), Fight::Lost);
+		assert_eq!(Miner.attack(Bomb), Fight::Won);
+		assert_eq!(Marshal.attack(Bomb), Fight::Lost);
+		assert_eq!(Scout.attack(Flag), Fight::Won);
+	}
+
+	#[test]
+	fn setup() {
+		let mut setup = Setup::new(Player::Red);
+		assert_eq!(setup.place((0, 5), Rank::Spy), Err(SetupError::OutOfZone));
+		assert_eq!(setup.place((0, 6), Rank::Spy), Ok(None));
+		assert_eq!(setup.place((1, 6), Rank::Spy), Err(SetupError::NoneLeft(Rank::Spy)));
+		assert_eq!(setup.place((0, 6), Rank::Spy), Ok(Some(Rank::Spy)));
+		assert_eq!(setup.place((0, 6), Rank::Flag), Ok(Some(Rank::Spy)));
+		assert_eq!(setup.remaining(Rank::Spy), 1);
+		setup.swap((0, 6), (9, 9)).unwrap();
+		assert_eq!(setup.get((9, 9)), Some(Rank::Flag));
+		assert_eq!(setup.get((0, 6)), None);
+		assert_eq!(Stratego::new(&setup, &Setup::new(Player::Blue)), Err(SetupError::Incomplete));
+
+		setup.fill_randomly(&mut StdRng::seed_from_u64(0));
+		assert!(setup.is_complete());
+		assert_eq!(setup.get((9, 9)), Some(Rank::Flag));
+		assert!(RANKS.iter().all(|&rank| setup.remaining(rank) == 0));
+		let blue = Setup::random(Player::Blue, &mut StdRng::seed_from_u64(1));
+		assert_eq!(Stratego::new(&blue, &setup), Err(SetupError::WrongPlayer));
+		let game = Stratego::new(&setup, &blue).unwrap();
+		assert_eq!(game.piece((9, 9)).map(|piece| piece.rank), Some(Rank::Flag));
+		assert_eq!(game.piece((0, 3)).map(|piece| piece.owner), Some(Player::Blue));
+		assert_eq!(game.piece((0, 4)), None);
+		// Only the front row can move at first
+		assert!(game.legal_moves().iter().all(|mv| mv.from.1 == 6 && mv.to.1 == 5));
+		assert_eq!(Rank::from_symbol('m'), Some(Rank::Marshal));
+		assert_eq!(Rank::from_symbol('7'), Some(Rank::Major));
+	}
+
+	#[test]
+	fn moves() {
+		let mut game = alone(&[
+			((4, 6), Rank::Scout, Player::Red),
+			((4, 1), Rank::Sergeant, Player::Blue),
+			((1, 4), Rank::Captain, Player::Red),
+			((0, 5), Rank::Bomb, Player::Red),
+		]);
+		let mv = |from, to| Move { from, to };
+		assert_eq!(game.check(mv((4, 6), (4, 2))), Ok(()));
+		assert_eq!(game.check(mv((4, 6), (4, 1))), Ok(()));
+		assert_eq!(game.check(mv((4, 6), (4, 0))), Err(IllegalMove::Blocked));
+		assert_eq!(game.check(mv((4, 6), (2, 6))), Ok(()));
+		assert_eq!(game.check(mv((4, 6), (5, 5))), Err(IllegalMove::Unreachable));
+		assert_eq!(game.check(mv((1, 4), (2, 4))), Err(IllegalMove::Lake));
+		assert_eq!(game.check(mv((1, 4), (1, 2))), Err(IllegalMove::Unreachable));
+		assert_eq!(game.check(mv((0, 5), (0, 6))), Err(IllegalMove::Immovable));
+		assert_eq!(game.check(mv((4, 1), (4, 2))), Err(IllegalMove::NotYourPiece));
+		assert_eq!(game.check(mv((0, 9), (1, 9))), Err(IllegalMove::Immovable));
+		assert_eq!(game.moves_from((1, 4)).len(), 3);
+		// Up to the sergeant, or sideways up to the board's sides
+		assert_eq!(game.moves_from((4, 6)).len(), 5 + 4 + 5 + 3);
+
+		// The scout attacks from afar and loses, both pieces being revealed
+		let lost = game.lost(Player::Red, Rank::Scout);
+		let combat = game.play(mv((4, 6), (4, 1))).unwrap();
+		assert_eq!(
+			combat,
+			Some(Combat { attacker: Rank::Scout, defender: Rank::Sergeant, fight: Fight::Lost })
+		);
+		assert_eq!(game.piece((4, 6)), None);
+		assert!(game.piece((4, 1)).is_some_and(|piece| piece.revealed && !piece.moved));
+		let sergeant = Known { owner: Player::Blue, rank: Some(Rank::Sergeant), moved: false };
+		assert_eq!(game.known((4, 1), Player::Red), Some(sergeant));
+		assert_eq!(game.known((9, 0), Player::Red).and_then(|known| known.rank), None);
+		assert_eq!(game.known((9, 0), Player::Blue).and_then(|known| known.rank), Some(Rank::Flag));
+		assert_eq!(game.lost(Player::Red, Rank::Scout), lost + 1);
+		assert_eq!(game.player(), Player::Blue);
+		assert_eq!(game.check(mv((1, 4), (1, 3))), Err(IllegalMove::NotYourPiece));
+		game.play(mv((4, 1), (4, 2))).unwrap();
+		assert!(game.piece((4, 2)).is_some_and(|piece| piece.moved));
+		assert_eq!(game.turns().len(), 2);
+	}
+
+	#[test]
+	fn outcomes() {
+		// Capturing the flag
+		let mut game = alone(&[
+			((5, 5), Rank::Miner, Player::Red),
+			((5, 4), Rank::Flag, Player::Blue),
+			((0, 0), Rank::Scout, Player::Blue),
+		]);
+		game.play(Move { from: (5, 5), to: (5, 4) }).unwrap();
+		assert_eq!(game.outcome(), Some(Outcome::FlagCaptured(Player::Red)));
+		assert_eq!(game.check(Move { from: (0, 0), to: (1, 0) }), Err(IllegalMove::GameOver));
+		assert!(game.legal_moves().is_empty());
+
+		// Taking the last piece of blue that can move
+		let mut game =
+			alone(&[((5, 5), Rank::Marshal, Player::Red), ((5, 4), Rank::General, Player::Blue)]);
+		game.play(Move { from: (5, 5), to: (5, 4) }).unwrap();
+		assert_eq!(game.outcome(), Some(Outcome::NoMovablePieces(Player::Red)));
+
+		// Bombs and flag left on both sides
+		let mut game =
+			alone(&[((5, 5), Rank::Major, Player::Red), ((5, 4), Rank::Major, Player::Blue)]);
+		let combat = game.play(Move { from: (5, 5), to: (5, 4) }).unwrap();
+		assert_eq!(combat.map(|combat| combat.fight), Some(Fight::Tie));
+		assert_eq!(game.piece((5, 4)), None);
+		assert_eq!(game.outcome(), Some(Outcome::Draw));
+
+		// The miner defuses the bomb, but the flag isn't captured yet
+		let mut game =
+			alone(&[((0, 7), Rank::Miner, Player::Blue), ((5, 5), Rank::Scout, Player::Red)]);
+		game.player = Player::Blue;
+		game.play(Move { from: (0, 7), to: (0, 8) }).unwrap();
+		assert_eq!(game.piece((0, 8)).map(|piece| piece.rank), Some(Rank::Miner));
+		assert_eq!(game.outcome(), None);
+	}
+}
@@ -0,0 +1,388 @@
+//! Computer opponents, from the one playing at random to a Monte Carlo tree search.
+//!
+//! All of them only choose among the legal moves, and thus play in the zone they were sent to.
+//! Their choices are deterministic for a given seed.
+//!
+//! ```
+//! use terminity::games::sttt::ai::{Ai, Level};
+//! use terminity::games::sttt::rules::{Move, SuperTTT};
+//!
+//! let mut game = SuperTTT::new();
+//! game.play(Move { zone: (0, 0), cell: (2, 2) }).unwrap();
+//! let mut ai = Ai::new(Level::Heuristic, 42);
+//! // O was sent to the bottom right zone
+//! let mv = ai.best_move(&game).unwrap();
+//! assert_eq!(mv.zone, (2, 2));
+//! ```
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use super::rules::{Move, Outcome, Player, SuperTTT};
+
+/// The number of games the Monte Carlo tree search plays out before choosing a move, by default.
+pub const MCTS_ITERATIONS: u32 = 4000;
+
+/// The score of a won game for the heuristic.
+const WIN: i32 = 1_000_000;
+
+/// How the computer chooses its moves.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Level {
+	/// Plays any legal move.
+	Random,
+	/// Plays the move leading to the best position once the opponent answered, positions being
+	/// evaluated by the zones won and the lines about to be completed.
+	Heuristic,
+	/// Plays the move winning the most random games played out from it, exploring the most
+	/// promising moves first (Monte Carlo tree search).
+	#[default]
+	Mcts,
+}
+
+impl FromStr for Level {
+	type Err = String;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_ascii_lowercase().as_str() {
+			"random" => Ok(Self::Random),
+			"heuristic" => Ok(Self::Heuristic),
+			"mcts" => Ok(Self::Mcts),
+			_ => Err(format!("Unknown level {:?}, expected random, heuristic or mcts", s)),
+		}
+	}
+}
+
+impl Display for Level {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::Random => "random",
+			Self::Heuristic => "heuristic",
+			Self::Mcts => "mcts",
+		})
+	}
+}
+
+/// A computer player.
+#[derive(Debug, Clone)]
+pub struct Ai {
+	level: Level,
+	/// The number of games played out by the Monte Carlo tree search
+	iterations: u32,
+	rng: StdRng,
+}
+
+impl Ai {
+	/// A computer player of the given level, making its random choices using the given seed.
+	pub fn new(level: Level, seed: u64) -> Self {
+		Self { level, iterations: MCTS_ITERATIONS, rng: StdRng::seed_from_u64(seed) }
+	}
+
+	/// Sets the number of games the Monte Carlo tree search plays out before choosing a move,
+	/// [`MCTS_ITERATIONS`] by default. The more, the stronger and the slower.
+	pub fn with_iterations(mut self, iterations: u32) -> Self {
+		self.iterations = iterations.max(1);
+		self
+	}
+
+	/// The level of the player.
+	pub fn level(&self) -> Level {
+		self.level
+	}
+
+	/// Chooses the move to play in the given game, if it isn't over.
+	pub fn best_move(&mut self, game: &SuperTTT) -> Option<Move> {
+		match self.level {
+			Level::Random => game.legal_moves().choose(&mut self.rng).copied(),
+			Level::Heuristic => self.heuristic(game),
+			Level::Mcts => self.mcts(game),
+		}
+	}
+
+	/// The move leading to the best evaluated position once the opponent answered their best, or
+	/// once the player played again if the opponent lost their turn.
+	fn heuristic(&mut self, game: &SuperTTT) -> Option<Move> {
+		let player = game.player();
+		let mut game = game.clone();
+		let mut moves = game.legal_moves();
+		// Choosing randomly between the moves evaluated the same
+		moves.shuffle(&mut self.rng);
+		moves.into_iter().max_by_key(|&mv| {
+			let score = match game.play(mv).expect("Legal move refused") {
+				// Only the player who just played can win
+				Some(Outcome::Win(_)) => WIN,
+				Some(Outcome::Draw) => 0,
+				None => {
+					let replies = game.legal_moves();
+					let again = game.player() == player;
+					let scores = replies.into_iter().map(|reply| {
+						game.play(reply).expect("Legal move refused");
+						let score = evaluate(&game, player);
+						game.undo();
+						score
+					});
+					if again {
+						scores.max().unwrap_or(0)
+					} else {
+						scores.min().unwrap_or(0)
+					}
+				}
+			};
+			game.undo();
+			score
+		})
+	}
+
+	/// The move of the given game that was played the most by a Monte Carlo tree search.
+	fn mcts(&mut self, game: &SuperTTT) -> Option<Move> {
+		let mut untried = game.legal_moves();
+		untried.shuffle(&mut self.rng);
+		let mut nodes = vec![Node::new(None, game.player().opponent(), None, untried)];
+		for _ in 0..self.iterations {
+			let mut game = game.clone();
+			let mut node = 0;
+			// Going down the tree to a node that still has moves to try
+			while nodes[node].untried.is_empty() && !nodes[node].children.is_empty() {
+				let parent_visits = nodes[node].visits;
+				node = *nodes[node]
+					.children
+					.iter()
+					.max_by(|&&a, &&b| {
+						nodes[a].uct(parent_visits).total_cmp(&nodes[b].uct(parent_visits))
+					})
+					.unwrap();
+				game.play(nodes[node].mv.unwrap()).expect("Legal move refused");
+			}
+			// Trying one of them
+			if let Some(mv) = nodes[node].untried.pop() {
+				let player = game.player();
+				game.play(mv).expect("Legal move refused");
+				let mut untried = game.legal_moves();
+				untried.shuffle(&mut self.rng);
+				nodes.push(Node::new(Some(mv), player, Some(node), untried));
+				let child = nodes.len() - 1;
+				nodes[node].children.push(child);
+				node = child;
+			}
+			// Playing the game out at random
+			let outcome = loop {
+				if let Some(outcome) = game.outcome() {
+					break outcome;
+				}
+				let mv = *game.legal_moves().choose(&mut self.rng).expect("No move to play");
+				game.play(mv).expect("Legal move refused");
+			};
+			let mut current = Some(node);
+			while let Some(node) = current {
+				let node = &mut nodes[node];
+				node.visits += 1;
+				node.score += match outcome {
+					Outcome::Win(winner) if winner == node.player => 1.,
+					Outcome::Win(_) => 0.,
+					Outcome::Draw => 0.5,
+				};
+				current = node.parent;
+			}
+		}
+		let children = nodes[0].children.iter();
+		children.max_by_key(|&&child| nodes[child].visits).and_then(|&child| nodes[child].mv)
+	}
+}
+
+/// A position of the Monte Carlo search tree.
+#[derive(Debug)]
+struct Node {
+	/// The move leading to the position, `None` for the root
+	mv: Option<Move>,
+	/// The player who played the move
+	player: Player,
+	parent: Option<usize>,
+	children: Vec<usize>,
+	/// The legal moves that weren't tried yet from the position
+	untried: Vec<Move>,
+	/// The number of games played out through the position
+	visits: u32,
+	/// The sum of the results of these games for `player`: 1 for a win and 0.5 for a draw
+	score: f64,
+}
+
+impl Node {
+	fn new(mv: Option<Move>, player: Player, parent: Option<usize>, untried: Vec<Move>) -> Self {
+		Self { mv, player, parent, children: vec![], untried, visits: 0, score: 0. }
+	}
+
+	/// The upper confidence bound of the node, balancing the most winning moves and the least
+	/// explored ones.
+	fn uct(&self, parent_visits: u32) -> f64 {
+		let visits = self.visits as f64;
+		self.score / visits + (2. * (parent_visits as f64).ln() / visits).sqrt()
+	}
+}
+
+/// The weight of a line that can still be completed with the given number of values in it.
+fn weight(count: u32) -> i32 {
+	(1 << (2 * count)) / 4
+}
+
+/// The score of the given values of a tic tac toe of the game for the given player: the more
+/// values in lines that can still be completed, the better. The values are `None` for the empty
+/// cells or undecided zones, and `Err(())` for the drawn zones, that nobody can align.
+fn lines(game: &SuperTTT, values: &[Result<Option<Player>, ()>], player: Player) -> i32 {
+	game.lines()
+		.iter()
+		.map(|line| {
+			if line.iter().any(|&i| values[i].is_err()) {
+				return 0;
+			}
+			let count = |p| line.iter().filter(|&&i| values[i] == Ok(Some(p))).count() as u32;
+			match (count(player), count(player.opponent())) {
+				(mine, 0) => weight(mine),
+				(0, theirs) => -weight(theirs),
+				_ => 0,
+			}
+		})
+		.sum()
+}
+
+/// The score of the given game for the given player.
+fn evaluate(game: &SuperTTT, player: Player) -> i32 {
+	match game.outcome() {
+		Some(Outcome::Win(winner)) => return if winner == player { WIN } else { -WIN },
+		Some(Outcome::Draw) => return 0,
+		None => (),
+	}
+	let zones: Vec<_> = game
+		.positions()
+		.map(|zone| match game.zone(zone) {
+			Some(Outcome::Win(winner)) => Ok(Some(winner)),
+			Some(Outcome::Draw) => Err(()),
+			None => Ok(None),
+		})
+		.collect();
+	let mut score = 100 * lines(game, &zones, player);
+	for (zone, value) in game.positions().zip(&zones) {
+		score += match value {
+			Ok(Some(winner)) if *winner == player => 50,
+			Ok(Some(_)) => -50,
+			Ok(None) => {
+				let cells: Vec<_> =
+					game.positions().map(|cell| Ok(game.cell(zone, cell))).collect();
+				lines(game, &cells, player)
+			}
+			Err(()) => 0,
+		};
+	}
+	// Choosing the zone to play in is an advantage
+	if game.forced_zone().is_none() {
+		score += if game.player() == player { 20 } else { -20 };
+	}
+	score
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::games::sttt::rules::{Rules, SentToClosed};
+
+	/// Plays a game between the two given players, returning its outcome.
+	fn play_out(x: &mut Ai, o: &mut Ai) -> Outcome {
+		let mut game = SuperTTT::new();
+		loop {
+			let ai = if game.player() == Player::X { &mut *x } else { &mut *o };
+			let forced = game.forced_zone();
+			let mv = ai.best_move(&game).expect("No move found");
+			assert!(forced.is_none() || forced == Some(mv.zone), "{:?} not in {:?}", mv, forced);
+			if let Some(outcome) = game.play(mv).expect("Illegal move") {
+				assert_eq!(ai.best_move(&game), None);
+				return outcome;
+			}
+		}
+	}
+
+	#[test]
+	fn levels() {
+		assert_eq!("MCTS".parse(), Ok(Level::Mcts));
+		assert_eq!(Level::Heuristic.to_string().parse(), Ok(Level::Heuristic));
+		assert!("hard".parse::<Level>().is_err());
+		for level in [Level::Random, Level::Heuristic, Level::Mcts] {
+			let mut x = Ai::new(level, 1).with_iterations(100);
+			let mut o = Ai::new(Level::Random, 2);
+			play_out(&mut x, &mut o);
+		}
+	}
+
+	#[test]
+	fn seeded() {
+		let game = SuperTTT::new();
+		for level in [Level::Random, Level::Heuristic, Level::Mcts] {
+			let moves = |seed| {
+				let mut ai = Ai::new(level, seed).with_iterations(100);
+				(0..5).map(|_| ai.best_move(&game).unwrap()).collect::<Vec<_>>()
+			};
+			assert_eq!(moves(7), moves(7));
+		}
+	}
+
+	#[test]
+	fn takes_the_win() {
+		// X has the zones of the first diagonal but the last one, where it has a cell left to
+		// align, and O sends it there
+		let mut game = SuperTTT::new();
+		let moves = [
+			(0, 1),
+			(1, 0),
+			(0, 2),
+			(2, 0),
+			(0, 0),
+			(3, 4),
+			(4, 3),
+			(3, 0),
+			(4, 5),
+			(5, 4),
+			(4, 4),
+			(6, 8),
+			(8, 6),
+			(6, 0),
+			(8, 7),
+			(7, 8),
+		];
+		for (zone, cell) in moves {
+			game.play(Move { zone: (zone % 3, zone / 3), cell: (cell % 3, cell / 3) }).unwrap();
+		}
+		let win = Move { zone: (2, 2), cell: (2, 2) };
+		for level in [Level::Heuristic, Level::Mcts] {
+			assert_eq!(Ai::new(level, 0).with_iterations(500).best_move(&game), Some(win));
+		}
+	}
+
+	#[test]
+	fn variants() {
+		let rules = Rules { size: 4, won_zones_open: true, sent_to_closed: SentToClosed::LoseTurn };
+		let mut game = SuperTTT::with_rules(rules);
+		let mut heuristic = Ai::new(Level::Heuristic, 3);
+		let mut mcts = Ai::new(Level::Mcts, 4).with_iterations(20);
+		while game.outcome().is_none() {
+			let ai = if game.player() == Player::X { &mut heuristic } else { &mut mcts };
+			let mv = ai.best_move(&game).expect("No move found");
+			assert_eq!(game.check(mv), Ok(()));
+			game.play(mv).unwrap();
+		}
+	}
+
+	#[test]
+	fn stronger_than_random() {
+		for level in [Level::Heuristic, Level::Mcts] {
+			let mut wins = 0;
+			for seed in 0..2 {
+				let mut ai = Ai::new(level, seed).with_iterations(300);
+				let mut random = Ai::new(Level::Random, seed);
+				wins += (play_out(&mut ai, &mut random) == Outcome::Win(Player::X)) as u32;
+				wins += (play_out(&mut random, &mut ai) == Outcome::Win(Player::O)) as u32;
+			}
+			assert!(wins >= 3, "{} won {} games out of 4", level, wins);
+		}
+	}
+}
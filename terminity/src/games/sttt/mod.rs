@@ -0,0 +1,679 @@
+//! A "super tic tac toe": a tic tac toe made of tic tac toes.
+//!
+//! Each of the nine zones of the board is a small tic tac toe, won by the first player aligning
+//! three of their marks in it. The game is won by the first player winning three aligned zones,
+//! and is a draw once every zone is won or full without that happening. The catch is that players
+//! don't choose where they play: the cell the opponent just played in sends them to the zone at
+//! the same place on the board, as the top right zone for the top right cell of a zone. Only on the
+//! first move, or when sent to a zone that is already won or full, can a player choose the zone.
+//!
+//! Variants of these rules are played with [`SuperTTT::with_rules`], as larger boards, whose
+//! layout is generated from their size.
+//!
+//! The rules are in [`rules`], this module only displays the game and lets the players play it,
+//! against each other or against the computer of [`ai`].
+
+#![allow(missing_docs)]
+
+use std::fmt::Formatter;
+use std::io;
+use std::ops::{Index, IndexMut, RangeInclusive};
+use std::time::Duration;
+
+use super::Game;
+use crate::backend::Terminal;
+use crate::events::{self, Event, EventHandler, Flow};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::style::{Color, ContentStyle, Stylize};
+use crossterm::{cursor, QueueableCommand};
+use terminity_widgets::render::{Buffer, Renderer};
+use terminity_widgets::widgets::frame::Frame;
+use terminity_widgets::widgets::text::{Align, Text};
+use terminity_widgets::{MouseEventWidget, Widget};
+
+pub mod ai;
+pub mod rules;
+
+use ai::{Ai, Level};
+use rules::{IllegalMove, Move, Outcome, Player, Pos, Rules};
+
+#[derive(Debug, Default)]
+pub struct SuperTTT {
+	rules: Rules,
+	/// The player played by the computer, its level and seed, if playing against it
+	computer: Option<(Player, Level, u64)>,
+}
+
+impl SuperTTT {
+	/// A game between two players on the same keyboard.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Plays the variant of the given rules.
+	///
+	/// Panics if the size of the board isn't in [`rules::SIZES`].
+	pub fn with_rules(mut self, rules: Rules) -> Self {
+		assert!(rules::SIZES.contains(&rules.size), "Invalid board size {}", rules.size);
+		self.rules = rules;
+		self
+	}
+
+	/// Plays against the computer, that plays the given player at the given level. The seed
+	/// decides the computer's random choices.
+	pub fn against_computer(mut self, player: Player, level: Level, seed: u64) -> Self {
+		self.computer = Some((player, level, seed));
+		self
+	}
+}
+
+impl Game for SuperTTT {
+	fn name(&self) -> &str {
+		"SuperTicTacToe"
+	}
+	fn description(&self) -> &str {
+		"A tic tac toe made of tic tac toes, against a friend or against the computer."
+	}
+	fn players(&self) -> RangeInclusive<u8> {
+		2..=2
+	}
+	fn min_size(&self) -> (u16, u16) {
+		let (width, height) = area_size(self.rules.size);
+		(width as u16, (height + TEXT_HEIGHT) as u16)
+	}
+	fn run(&self, term: &mut dyn Terminal) -> io::Result<()> {
+		let mut state = GameState::new(self.rules);
+		if let Some((player, level, seed)) = self.computer {
+			state.computer = Some((player, Ai::new(level, seed)));
+		}
+		events::run(&mut state, term)
+	}
+}
+
+struct GameState {
+	pub game: rules::SuperTTT,
+	/// The player played by the computer and the computer itself, if playing against it
+	pub computer: Option<(Player, Ai)>,
+	pub area: Frame<(u8, u8), Zone, GameArea>,
+	pub selected: Selection,
+	pub text: Text<TEXT_HEIGHT>,
+	/// Once the game is over, the index of the text displayed in `END_TEXTS`
+	pub end_step: Option<usize>,
+	renderer: Renderer,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Selection {
+	ty: SelectType,
+	x: u8,
+	y: u8,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum SelectType {
+	SelCell(u8, u8),
+	Zone,
+}
+
+/// The number of lines of the texts under the board.
+const TEXT_HEIGHT: usize = 7;
+
+/// The minimum width of the game.
+const MIN_WIDTH: usize = 70;
+
+/// The display of a zone of the game.
+#[derive(Debug)]
+struct Zone {
+	/// The number of cells on each side of the zone
+	side: u8,
+	pub values: Vec<Option<Player>>,
+	pub outcome: Option<Outcome>,
+	pub selected: bool,
+}
+
+/// The color of the marks of the given player.
+fn color(player: Player) -> Color {
+	match player {
+		Player::X => Color::Red,
+		Player::O => Color::Blue,
+	}
+}
+
+/// The number of the given player, as shown to the players.
+fn number(player: Player) -> u8 {
+	player as u8 + 1
+}
+
+impl Widget for Zone {
+	fn displ_line(&self, f: &mut Formatter<'_>, line: usize) -> std::fmt::Result {
+		let mut style = ContentStyle::new();
+		if let Some(outcome) = self.outcome {
+			style.background_color = Some(match outcome {
+				Outcome::Win(winner) => color(winner),
+				Outcome::Draw => Color::White,
+			});
+			style.foreground_color = Some(Color::Black);
+		}
+		if self.selected {
+			style.background_color = Some(Color::Grey);
+			style.foreground_color = style.background_color;
+		}
+		for cell_x in 0..self.side {
+			f.write_fmt(format_args!("{}", &style.apply(' ').to_string()))?;
+			let cell = self[(cell_x, line as u8)];
+			let mark = cell.map_or_else(|| " ".to_owned(), |player| player.to_string());
+			let mut styled_cell = style.apply(mark).bold();
+			if let (None, Some(player)) = (style.foreground_color, cell) {
+				styled_cell = styled_cell.with(color(player));
+			}
+			f.write_fmt(format_args!("{}", styled_cell))?;
+		}
+		f.write_fmt(format_args!("{}", &style.apply(' ').to_string()))?;
+		Ok(())
+	}
+	fn size(&self) -> (usize, usize) {
+		(2 * self.side as usize + 1, self.side as usize)
+	}
+}
+
+impl MouseEventWidget for Zone {
+	/// The cell under the pointer, the spaces around a cell being part of it.
+	type MouseHandlingResult = (u8, u8);
+	fn mouse_event(&mut self, event: MouseEvent) -> Self::MouseHandlingResult {
+		let last = self.side as u16 - 1;
+		((event.column / 2).min(last) as u8, event.row.min(last) as u8)
+	}
+}
+
+/// The zones of the game, indexed by their coordinates.
+#[derive(Debug)]
+struct GameArea {
+	/// The number of zones on each side of the board
+	side: u8,
+	zones: Vec<Zone>,
+}
+
+impl Index<Pos> for GameArea {
+	type Output = Zone;
+	fn index(&self, (x, y): Pos) -> &Self::Output {
+		&self.zones[x as usize + self.side as usize * y as usize]
+	}
+}
+impl IndexMut<Pos> for GameArea {
+	fn index_mut(&mut self, (x, y): Pos) -> &mut Self::Output {
+		&mut self.zones[x as usize + self.side as usize * y as usize]
+	}
+}
+
+impl GameArea {
+	/// The empty zones of a board of the given size.
+	fn new(side: u8) -> Self {
+		let zone = || Zone {
+			side,
+			values: vec![None; side as usize * side as usize],
+			outcome: None,
+			selected: false,
+		};
+		Self { side, zones: (0..side as usize * side as usize).map(|_| zone()).collect() }
+	}
+
+	/// Shows the marks and the outcomes of the zones of the given game.
+	fn update(&mut self, game: &rules::SuperTTT) {
+		for (pos, zone) in game.positions().zip(&mut self.zones) {
+			for (cell, value) in game.positions().zip(&mut zone.values) {
+				*value = game.cell(pos, cell);
+			}
+			zone.outcome = game.zone(pos);
+		}
+	}
+
+	/// Frames the zones in a grid, centered in the width of the game.
+	fn framed(self) -> Frame<Pos, Zone, GameArea> {
+		let side = self.side as usize;
+		let zone_width = 2 * side + 1;
+		let (width, _) = area_size(self.side);
+		let grid_width = side * (zone_width + 1) + 1;
+		let left = " ".repeat((width - grid_width) / 2);
+		let right = " ".repeat(width - grid_width - left.len());
+		let border =
+			format!("{}#{}{}", left, format!("{}#", "-".repeat(zone_width)).repeat(side), right);
+		let mut content = vec![(border.clone(), vec![])];
+		for y in 0..self.side {
+			for line in 0..side {
+				let zones = (0..self.side).map(|x| {
+					let suffix =
+						if x == self.side - 1 { format!("|{}", right) } else { "|".to_owned() };
+					(((x, y), line), suffix)
+				});
+				content.push((format!("{}|", left), zones.collect()));
+			}
+			content.push((border.clone(), vec![]));
+		}
+		Frame::new(content, self)
+	}
+}
+
+/// The size of the framed zones of a board of the given size.
+fn area_size(side: u8) -> (usize, usize) {
+	let side = side as usize;
+	let grid_width = side * (2 * side + 2) + 1;
+	(grid_width.max(MIN_WIDTH), side * (side + 1) + 1)
+}
+
+impl Index<Pos> for Zone {
+	type Output = Option<Player>;
+	fn index(&self, (x, y): Pos) -> &Self::Output {
+		&self.values[x as usize + self.side as usize * y as usize]
+	}
+}
+
+impl GameState {
+	fn new(rules: Rules) -> Self {
+		let center = rules.size / 2;
+		let mut area = GameArea::new(rules.size);
+		area[(center, center)].selected = true;
+		let area = area.framed();
+		let width = area.size().0;
+		Self {
+			game: rules::SuperTTT::with_rules(rules),
+			computer: None,
+			selected: Selection { ty: SelectType::Zone, x: center, y: center },
+			area,
+			text: Text {
+				content: [
+					"".to_owned(),
+					"".to_owned(),
+					"Welcome to Super tic tac toe!".to_owned(),
+					"Choose in which zone you will play first. You won't be able to cancel!"
+						.to_owned(),
+					"".to_owned(),
+					"".to_owned(),
+					"".to_owned(),
+				],
+				align: Align::Center,
+				padding: ' ',
+				width,
+			},
+			end_step: None,
+			renderer: Renderer::new(),
+		}
+	}
+
+	/// The texts displayed once the game is over, one after the other.
+	const END_TEXTS: [&'static str; 4] = [
+		"Press any key to exit   ",
+		"Press any key to exit.  ",
+		"Press any key to exit.. ",
+		"Press any key to exit...",
+	];
+
+	/// Highlights the selected zone if the player chooses the zone, or shows the end texts once
+	/// the game is over.
+	fn show_selection(&mut self) {
+		if self.end_step.is_some() {
+			self.text[6] = Self::END_TEXTS[0].to_owned();
+		} else if self.selected.ty == SelectType::Zone {
+			let coords = (self.selected.x, self.selected.y);
+			self.area[coords].selected = true;
+		}
+	}
+
+	/// Puts the cursor at the center of the zone the next player was sent to, or of the board if
+	/// they can choose the zone.
+	fn select_next(&mut self) {
+		self.selected.ty = match self.game.forced_zone() {
+			Some((x, y)) => SelectType::SelCell(x, y),
+			None => SelectType::Zone,
+		};
+		self.selected.x = self.game.rules().size / 2;
+		self.selected.y = self.game.rules().size / 2;
+	}
+
+	/// Whether the computer is to play.
+	fn computer_turn(&self) -> bool {
+		self.game.outcome().is_none()
+			&& self.computer.as_ref().is_some_and(|(player, _)| *player == self.game.player())
+	}
+
+	/// Moves the cursor or plays with the given key.
+	fn key(&mut self, code: KeyCode) {
+		use KeyCode::*;
+		let coords = (self.selected.x, self.selected.y);
+		let last = self.game.rules().size - 1;
+		match code {
+			Left if self.selected.x > 0 => self.selected.x -= 1,
+			Right if self.selected.x < last => self.selected.x += 1,
+			Up if self.selected.y > 0 => self.selected.y -= 1,
+			Down if self.selected.y < last => self.selected.y += 1,
+			Enter if !self.computer_turn() => match self.selected.ty {
+				SelectType::Zone if self.game.closed(coords) => self.refuse_zone(coords),
+				SelectType::Zone => {
+					self.text.clear();
+					self.selected.ty = SelectType::SelCell(coords.0, coords.1);
+					self.selected.x = self.game.rules().size / 2;
+					self.selected.y = self.game.rules().size / 2;
+					self.text[2] = "Right.".to_owned();
+					self.text[3] = "Which tile?".to_owned();
+				}
+				SelectType::SelCell(zone_x, zone_y) => {
+					self.play(Move { zone: (zone_x, zone_y), cell: coords })
+				}
+			},
+			_ => (),
+		}
+	}
+
+	/// Moves the cursor to the zone or cell under the pointer, and plays in the cell clicked.
+	fn mouse(&mut self, event: MouseEvent) {
+		let Some((zone, cell)) = self.area.mouse_event(event) else {
+			return;
+		};
+		match (event.kind, self.selected.ty) {
+			(MouseEventKind::Moved, SelectType::Zone) => (self.selected.x, self.selected.y) = zone,
+			(MouseEventKind::Moved, SelectType::SelCell(x, y)) if (x, y) == zone => {
+				(self.selected.x, self.selected.y) = cell
+			}
+			(MouseEventKind::Down(MouseButton::Left), ty) if !self.computer_turn() => match ty {
+				SelectType::Zone if self.game.closed(zone) => self.refuse_zone(zone),
+				_ => self.play(Move { zone, cell }),
+			},
+			_ => (),
+		}
+	}
+
+	/// Tells the player they can't play in the given closed zone.
+	fn refuse_zone(&mut self, zone: Pos) {
+		self.text.clear();
+		self.text[2] = match self.game.zone(zone) {
+			Some(Outcome::Win(winner)) if !self.game.rules().won_zones_open => {
+				format!("Nope, you can't! The zone is already won by {}.", winner)
+			}
+			_ => "Nope, no more free tile over here.".to_string(),
+		};
+		self.text[3] = "Choose in which zone you will play.".to_string();
+	}
+
+	/// Plays the given move, telling the players how it went.
+	fn play(&mut self, mv: Move) {
+		self.text.clear();
+		let player = self.game.player();
+		match self.game.play(mv) {
+			Ok(Some(Outcome::Draw)) => {
+				self.text[2] = "Really guys? That's a draw.".to_owned();
+				self.text[3] = "Well played though, that was intense!".to_owned();
+				self.end_step = Some(0);
+			}
+			Ok(Some(Outcome::Win(winner))) => {
+				self.text[2] = "WOOOOOHOOOOO!!!! Seems like we have a winner!".to_owned();
+				self.text[3] = format!("Well done player {}!", number(winner));
+				self.text[4] =
+					format!("Player {}, maybe you wanna ask a rematch?", number(winner.opponent()));
+				self.end_step = Some(0);
+			}
+			// Sent to a closed zone with the lose turn rule
+			Ok(None) if self.game.player() == player => {
+				self.text[2] = format!(
+					"Player {} is sent to a closed zone and loses their turn!",
+					number(player.opponent())
+				);
+				self.text[3] = "Where to play again?".to_owned();
+				self.select_next();
+			}
+			Ok(None) if self.computer.as_ref().is_some_and(|(computer, _)| *computer == player) => {
+				self.text[2] = "The computer played.".to_owned();
+				self.text[3] = "Where to play now?".to_owned();
+				self.select_next();
+			}
+			Ok(None) => {
+				self.text[2] = "Done.".to_owned();
+				self.text[3] = "Where to play now?".to_owned();
+				self.select_next();
+			}
+			Err(IllegalMove::Occupied) => {
+				self.text[2] =
+					"Sneaky one, but you can't play where someone already played!".to_owned();
+				self.text[3] = "Choose on which tile you'll play.".to_string();
+			}
+			Err(e) => {
+				self.text[2] = format!("{}.", e);
+				self.select_next();
+			}
+		}
+		self.area.update(&self.game);
+	}
+}
+
+impl EventHandler for GameState {
+	fn update(&mut self, event: Event) -> Flow {
+		if let Event::Resize(..) = event {
+			self.renderer.invalidate();
+		}
+		if let Some(step) = &mut self.end_step {
+			match event {
+				Event::Key(KeyEvent { kind: KeyEventKind::Press, .. }) => return Flow::Exit,
+				Event::Tick => *step = (*step + 1) % Self::END_TEXTS.len(),
+				_ => (),
+			}
+			self.text[6] = Self::END_TEXTS[*step].to_owned();
+			return Flow::Continue;
+		}
+		let coords = (self.selected.x, self.selected.y);
+		self.area[coords].selected = false;
+		match event {
+			// The computer plays on the first tick of its turn, once the last move is displayed
+			Event::Tick => {
+				if let (true, Some((_, ai))) = (self.computer_turn(), &mut self.computer) {
+					let mv = ai.best_move(&self.game).expect("The computer found no move");
+					self.play(mv);
+				}
+			}
+			Event::Key(KeyEvent { code, kind: KeyEventKind::Press, .. }) => self.key(code),
+			Event::Mouse(event) => self.mouse(event),
+			_ => (),
+		}
+		self.show_selection();
+		Flow::Continue
+	}
+
+	fn render(&mut self, out: &mut dyn io::Write) -> io::Result<()> {
+		let player = self.game.player();
+		self.text[0] = format!(
+			"Turn to player {} ({})",
+			number(player),
+			player.to_string().with(color(player)).bold()
+		);
+
+		let (width, height) = self.area.size();
+		let mut frame = Buffer::new((width.max(self.text.width), height + TEXT_HEIGHT));
+		frame.draw((0, 0), &self.area);
+		frame.draw((0, height), &self.text);
+		self.renderer.render(out, frame)?;
+
+		if let (None, Selection { ty: SelectType::SelCell(zx, zy), x, y }) =
+			(self.end_step, self.selected)
+		{
+			let (mut x_index, mut y_index) = self.area.find_pos(&(zx, zy)).unwrap();
+			y_index += y as usize;
+			x_index += 1 + 2 * x as usize;
+			out.queue(cursor::MoveTo(x_index as u16, y_index as u16))?.queue(cursor::Show)?;
+		} else {
+			out.queue(cursor::Hide)?;
+		}
+		Ok(())
+	}
+
+	fn tick_rate(&self) -> Option<Duration> {
+		if self.computer_turn() {
+			return Some(Duration::from_millis(10));
+		}
+		self.end_step.map(|_| Duration::from_millis(600))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::backend::VirtualTerminal;
+	use crossterm::event::{KeyCode, KeyModifiers};
+	use rules::SentToClosed;
+
+	/// Scripts the selection of the given zone or cell (indexed as in `GameArea` and `Zone`),
+	/// the cursor being at the center.
+	fn choose(term: &mut VirtualTerminal, index: u8) {
+		let (x, y) = (index % 3, index / 3);
+		let horizontal = if x > 1 { KeyCode::Right } else { KeyCode::Left };
+		let vertical = if y > 1 { KeyCode::Down } else { KeyCode::Up };
+		term.push_keys(std::iter::repeat_n(horizontal, x.abs_diff(1) as usize));
+		term.push_keys(std::iter::repeat_n(vertical, y.abs_diff(1) as usize));
+		term.push_keys([KeyCode::Enter]);
+	}
+
+	#[test]
+	fn full_game() {
+		// The zone to choose if the players can choose one, and the cell to play in
+		let moves = [
+			(Some(8), 1),
+			(None, 0),
+			(None, 6),
+			(None, 5),
+			(None, 7),
+			(None, 1),
+			(None, 1),
+			(None, 8),
+			(None, 4),
+			(None, 8),
+			(None, 7),
+			(None, 4),
+			(None, 1),
+			(None, 4),
+			(None, 5),
+			(None, 0),
+			(None, 1),
+			(Some(2), 8),
+			(Some(0), 4),
+			(None, 4),
+			(None, 0),
+			(None, 0),
+			(None, 2),
+			(None, 3),
+			(None, 5),
+			(None, 4),
+			(None, 2),
+		];
+		let mut term = VirtualTerminal::new((70, 20));
+		for (zone, cell) in moves {
+			if let Some(zone) = zone {
+				choose(&mut term, zone);
+			}
+			choose(&mut term, cell);
+		}
+		// Any key to exit
+		term.push_keys([KeyCode::Esc]);
+
+		SuperTTT::new().run(&mut term).unwrap();
+		let screen = term.screen();
+		assert!(screen.find("Seems like we have a winner!").is_some());
+		assert!(screen.find("Well done player 1!").is_some());
+		assert!(screen.find("Press any key to exit").is_some());
+		// The winning line of zones: top left to bottom right
+		for (x, y) in [(24, 1), (32, 5), (40, 9)] {
+			let cell = screen.cell(x, y);
+			assert_eq!(cell.style.background_color, Some(Color::Red), "zone at {}, {}", x, y);
+		}
+		assert_eq!(screen.line(0).trim(), "#-------#-------#-------#");
+	}
+
+	#[test]
+	fn mouse() {
+		let mut state = GameState::new(Rules::default());
+		// The screen coordinates of the given cell
+		let layout = GameState::new(Rules::default());
+		let at = |zone, (x, y): (u8, u8)| {
+			let (column, row) = layout.area.find_pos(&zone).unwrap();
+			(column as u16 + 1 + 2 * x as u16, row as u16 + y as u16)
+		};
+		let mouse = |state: &mut GameState, kind, (column, row)| {
+			let modifiers = KeyModifiers::NONE;
+			state.update(Event::Mouse(MouseEvent { kind, column, row, modifiers }));
+		};
+		let click = MouseEventKind::Down(MouseButton::Left);
+
+		// Hovering a zone selects it, and clicking a cell plays it
+		mouse(&mut state, MouseEventKind::Moved, at((2, 1), (0, 0)));
+		assert!(state.area[(2, 1)].selected && !state.area[(1, 1)].selected);
+		mouse(&mut state, click, at((2, 2), (0, 0)));
+		assert_eq!(state.game.moves(), [Move { zone: (2, 2), cell: (0, 0) }]);
+		assert_eq!(state.selected.ty, SelectType::SelCell(0, 0));
+
+		// Only the cells of the zone the player is sent to can be selected and played
+		mouse(&mut state, MouseEventKind::Moved, at((0, 0), (1, 2)));
+		assert_eq!((state.selected.x, state.selected.y), (1, 2));
+		mouse(&mut state, MouseEventKind::Moved, at((1, 0), (0, 0)));
+		assert_eq!((state.selected.x, state.selected.y), (1, 2));
+		mouse(&mut state, click, at((1, 0), (0, 0)));
+		assert_eq!(state.game.moves().len(), 1);
+		mouse(&mut state, click, at((0, 0), (2, 2)));
+		assert_eq!(state.game.moves()[1], Move { zone: (0, 0), cell: (2, 2) });
+		assert_eq!(state.game.cell((0, 0), (2, 2)), Some(Player::O));
+
+		// Clicking on the frame does nothing
+		mouse(&mut state, click, (0, 0));
+		assert_eq!(state.game.moves().len(), 2);
+	}
+
+	#[test]
+	fn against_computer() {
+		let mut state = GameState::new(Rules::default());
+		state.computer = Some((Player::O, Ai::new(Level::Random, 3)));
+		// The center zone, then its center cell
+		state.update(Event::Key(KeyCode::Enter.into()));
+		state.update(Event::Key(KeyCode::Enter.into()));
+		assert_eq!(state.game.moves().len(), 1);
+		// The human can't play for the computer
+		state.update(Event::Key(KeyCode::Enter.into()));
+		assert_eq!(state.game.moves().len(), 1);
+		assert!(state.tick_rate().is_some());
+
+		state.update(Event::Tick);
+		let answer = state.game.moves()[1];
+		assert_eq!(answer.zone, (1, 1));
+		assert_eq!(state.game.player(), Player::X);
+		assert_eq!(state.selected.ty, SelectType::SelCell(answer.cell.0, answer.cell.1));
+		assert_eq!(state.text[2], "The computer played.");
+		assert_eq!(state.tick_rate(), None);
+	}
+
+	#[test]
+	fn variants() {
+		// The layout of a 4×4 board is generated from its size
+		let rules = Rules { size: 4, sent_to_closed: SentToClosed::LoseTurn, ..Rules::default() };
+		assert_eq!(SuperTTT::new().with_rules(rules).min_size(), (70, 28));
+		let mut state = GameState::new(rules);
+		assert_eq!(state.area.size(), (70, 21));
+		let area = state.area.to_string();
+		let lines: Vec<_> = area.lines().collect();
+		assert_eq!(lines.len(), 21);
+		assert_eq!(
+			lines[0].split_whitespace().next(),
+			Some("#---------#---------#---------#---------#")
+		);
+		assert_eq!(state.area.find_pos(&(3, 3)), Some((45, 16)));
+		assert!(state.area[(2, 2)].selected);
+		for _ in 0..3 {
+			state.update(Event::Key(KeyCode::Right.into()));
+		}
+		assert!(state.area[(3, 2)].selected);
+
+		// The players are told when a player loses their turn
+		let rules = Rules { sent_to_closed: SentToClosed::LoseTurn, ..Rules::default() };
+		let mut state = GameState::new(rules);
+		let at = |index: u8| (index % 3, index / 3);
+		for (zone, cell) in [(4, 0), (0, 4), (4, 1), (1, 4), (4, 2), (2, 4)] {
+			state.play(Move { zone: at(zone), cell: at(cell) });
+		}
+		assert_eq!(state.text[2], "Player 1 is sent to a closed zone and loses their turn!");
+		assert_eq!(state.selected.ty, SelectType::Zone);
+		state.update(Event::Key(KeyCode::Enter.into()));
+		assert_eq!(state.text[2], "Nope, you can't! The zone is already won by x.");
+	}
+}
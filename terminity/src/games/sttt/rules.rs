@@ -0,0 +1,577 @@
+//! The rules of super tic tac toe, independent of how the game is displayed.
+//!
+//! The board is made of 3×3 zones, each being a tic tac toe of 3×3 cells. Zones and cells are
+//! indexed by `(x, y)` coordinates, `(0, 0)` being the top left one. The cell a player plays in
+//! sends the opponent to the zone at the same coordinates, unless that zone is already won or full.
+//!
+//! ```
+//! use terminity::games::sttt::rules::{Move, Player, SuperTTT};
+//!
+//! let mut game = SuperTTT::new();
+//! assert_eq!(game.legal_moves().len(), 81);
+//! game.play(Move { zone: (1, 1), cell: (2, 0) }).unwrap();
+//! // O is sent to the top right zone
+//! assert_eq!(game.player(), Player::O);
+//! assert_eq!(game.forced_zone(), Some((2, 0)));
+//! assert_eq!(game.legal_moves().len(), 9);
+//! game.undo();
+//! assert_eq!(game, SuperTTT::new());
+//! ```
+//!
+//! Variants of these rules are played through [`Rules`], as on larger boards:
+//!
+//! ```
+//! use terminity::games::sttt::rules::{Rules, SentToClosed, SuperTTT};
+//!
+//! let rules = Rules { size: 4, won_zones_open: true, sent_to_closed: SentToClosed::LoseTurn };
+//! let game = SuperTTT::with_rules(rules);
+//! assert_eq!(game.legal_moves().len(), 256);
+//! ```
+
+use std::fmt::{self, Display, Formatter};
+use std::ops::RangeInclusive;
+
+/// The coordinates of a zone on the board, or of a cell in a zone, as `(x, y)`.
+pub type Pos = (u8, u8);
+
+/// The sizes a board can have.
+pub const SIZES: RangeInclusive<u8> = 2..=5;
+
+/// A player, named after the mark they put in the cells. X plays first.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Player {
+	X = 0,
+	O = 1,
+}
+
+/// A move: the cell played in, and the zone it is in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Move {
+	pub zone: Pos,
+	pub cell: Pos,
+}
+
+/// How a zone or the whole game ended.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Outcome {
+	/// The player aligned a line of cells in the zone, or of zones on the board.
+	Win(Player),
+	/// The zone is full without anyone aligning a line of cells, or all the zones are closed
+	/// without anyone aligning a line of zones.
+	Draw,
+}
+
+/// Why a move can't be played.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum IllegalMove {
+	/// The zone or the cell is out of the board.
+	OffBoard,
+	/// The game is over.
+	GameOver,
+	/// The player was sent to another zone.
+	WrongZone,
+	/// The zone is closed: full, or won if won zones can't be played in.
+	ZoneClosed,
+	/// Someone already played in the cell.
+	Occupied,
+}
+
+/// What happens to a player sent to a closed zone.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum SentToClosed {
+	/// The player chooses the zone they play in.
+	#[default]
+	FreeChoice,
+	/// The player loses their turn, and the opponent plays again in the zone of their choice.
+	LoseTurn,
+}
+
+/// The rules of a game, to play variants of the classic game.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Rules {
+	/// The number of zones on each side of the board, and of cells on each side of a zone. A
+	/// full row, column or diagonal is needed to win. The classic game has a size of 3, and the
+	/// size must be in [`SIZES`].
+	pub size: u8,
+	/// Whether a won zone can still be played in until it is full. Its winner doesn't change.
+	pub won_zones_open: bool,
+	/// What happens to a player sent to a closed zone.
+	pub sent_to_closed: SentToClosed,
+}
+
+impl Default for Rules {
+	/// The classic rules.
+	fn default() -> Self {
+		Self { size: 3, won_zones_open: false, sent_to_closed: SentToClosed::FreeChoice }
+	}
+}
+
+/// A game of super tic tac toe: the marks on the board and the moves played so far.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuperTTT {
+	rules: Rules,
+	/// The lines to align to win a zone or the board: rows, columns then diagonals, as indexes
+	/// `x + size * y`
+	lines: Vec<Vec<usize>>,
+	/// The cells, indexed by zone then cell, as `x + size * y`.
+	cells: Vec<Vec<Option<Player>>>,
+	/// The outcome of each zone, indexed as `x + size * y`, with the index of the move that
+	/// decided it.
+	zones: Vec<Option<(Outcome, usize)>>,
+	/// The player who plays next
+	player: Player,
+	moves: Vec<Move>,
+	outcome: Option<Outcome>,
+}
+
+impl Player {
+	/// The other player.
+	pub fn opponent(self) -> Self {
+		match self {
+			Self::X => Self::O,
+			Self::O => Self::X,
+		}
+	}
+}
+
+impl Display for Player {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::X => "x",
+			Self::O => "o",
+		})
+	}
+}
+
+impl Display for IllegalMove {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::OffBoard => "The move is out of the board",
+			Self::GameOver => "The game is over",
+			Self::WrongZone => "The move must be played in the zone the last one sent to",
+			Self::ZoneClosed => "The zone can't be played in anymore",
+			Self::Occupied => "Someone already played in this cell",
+		})
+	}
+}
+
+impl Default for SuperTTT {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl SuperTTT {
+	/// A game of the classic rules where no one played yet.
+	pub fn new() -> Self {
+		Self::with_rules(Rules::default())
+	}
+
+	/// A game of the given rules where no one played yet.
+	///
+	/// Panics if the size of the board isn't in [`SIZES`].
+	pub fn with_rules(rules: Rules) -> Self {
+		assert!(SIZES.contains(&rules.size), "Invalid board size {}", rules.size);
+		let size = rules.size as usize;
+		let rows = (0..size).map(|y| (0..size).map(|x| x + size * y).collect());
+		let columns = (0..size).map(|x| (0..size).map(|y| x + size * y).collect());
+		let diagonals = [
+			(0..size).map(|i| i * (size + 1)).collect(),
+			(1..=size).map(|i| i * (size - 1)).collect(),
+		];
+		Self {
+			rules,
+			lines: rows.chain(columns).chain(diagonals).collect(),
+			cells: vec![vec![None; size * size]; size * size],
+			zones: vec![None; size * size],
+			player: Player::X,
+			moves: vec![],
+			outcome: None,
+		}
+	}
+
+	/// The rules of the game.
+	pub fn rules(&self) -> Rules {
+		self.rules
+	}
+
+	/// The coordinates of all the zones of the board, or of all the cells of a zone, row by row.
+	pub fn positions(&self) -> impl Iterator<Item = Pos> {
+		let size = self.rules.size;
+		(0..size).flat_map(move |y| (0..size).map(move |x| (x, y)))
+	}
+
+	/// The player who plays next.
+	pub fn player(&self) -> Player {
+		self.player
+	}
+
+	/// The mark in the given cell of the given zone. Panics if the zone or the cell is out of the
+	/// board.
+	pub fn cell(&self, zone: Pos, cell: Pos) -> Option<Player> {
+		self.cells[self.index(zone)][self.index(cell)]
+	}
+
+	/// How the given zone ended, if it did. A zone ends when a player wins it or when it is full,
+	/// and its outcome doesn't change afterwards even if it can still be played in. Panics if the
+	/// zone is out of the board.
+	pub fn zone(&self, zone: Pos) -> Option<Outcome> {
+		self.zones[self.index(zone)].map(|(outcome, _)| outcome)
+	}
+
+	/// Whether the given zone can't be played in anymore: it is full, or won and won zones can't
+	/// be played in. Panics if the zone is out of the board.
+	pub fn closed(&self, zone: Pos) -> bool {
+		let index = self.index(zone);
+		let won = matches!(self.zones[index], Some((Outcome::Win(_), _)));
+		(won && !self.rules.won_zones_open) || self.cells[index].iter().all(Option::is_some)
+	}
+
+	/// The moves played so far.
+	pub fn moves(&self) -> &[Move] {
+		&self.moves
+	}
+
+	/// How the game ended, if it did.
+	pub fn outcome(&self) -> Option<Outcome> {
+		self.outcome
+	}
+
+	/// The zone the next player was sent to, or `None` if they can choose any zone that isn't
+	/// closed: on the first move, or when sent to a closed zone.
+	pub fn forced_zone(&self) -> Option<Pos> {
+		let last = self.moves.last()?;
+		Some(last.cell).filter(|&zone| !self.closed(zone))
+	}
+
+	/// Checks whether the given move can be played, telling why it can't.
+	pub fn check(&self, mv: Move) -> Result<(), IllegalMove> {
+		if !self.on_board(mv.zone) || !self.on_board(mv.cell) {
+			Err(IllegalMove::OffBoard)
+		} else if self.outcome.is_some() {
+			Err(IllegalMove::GameOver)
+		} else if self.forced_zone().is_some_and(|zone| zone != mv.zone) {
+			Err(IllegalMove::WrongZone)
+		} else if self.closed(mv.zone) {
+			Err(IllegalMove::ZoneClosed)
+		} else if self.cell(mv.zone, mv.cell).is_some() {
+			Err(IllegalMove::Occupied)
+		} else {
+			Ok(())
+		}
+	}
+
+	/// The moves the next player can play, none if the game is over.
+	pub fn legal_moves(&self) -> Vec<Move> {
+		if self.outcome.is_some() {
+			return vec![];
+		}
+		let zones: Vec<_> = match self.forced_zone() {
+			Some(zone) => vec![zone],
+			None => self.positions().filter(|&zone| !self.closed(zone)).collect(),
+		};
+		zones
+			.into_iter()
+			.flat_map(|zone| {
+				self.positions()
+					.filter(move |&cell| self.cell(zone, cell).is_none())
+					.map(move |cell| Move { zone, cell })
+			})
+			.collect()
+	}
+
+	/// Plays the given move for the next player, deciding its zone and the game if it ends them.
+	pub fn play(&mut self, mv: Move) -> Result<Option<Outcome>, IllegalMove> {
+		self.check(mv)?;
+		let zone = self.index(mv.zone);
+		let cell = self.index(mv.cell);
+		self.cells[zone][cell] = Some(self.player);
+		if self.zones[zone].is_none() {
+			let cells = &self.cells[zone];
+			let outcome = match self.aligned(cells) {
+				Some(winner) => Some(Outcome::Win(winner)),
+				None if cells.iter().all(Option::is_some) => Some(Outcome::Draw),
+				None => None,
+			};
+			self.zones[zone] = outcome.map(|outcome| (outcome, self.moves.len()));
+		}
+		self.moves.push(mv);
+
+		let winners: Vec<_> = self
+			.zones
+			.iter()
+			.map(|zone| match zone {
+				Some((Outcome::Win(player), _)) => Some(*player),
+				_ => None,
+			})
+			.collect();
+		self.outcome = match self.aligned(&winners) {
+			Some(winner) => Some(Outcome::Win(winner)),
+			None if self.positions().all(|zone| self.closed(zone)) => Some(Outcome::Draw),
+			None => None,
+		};
+		if !self.skips_turn() {
+			self.player = self.player.opponent();
+		}
+		Ok(self.outcome)
+	}
+
+	/// Takes back the last move, returning it if any move was played.
+	pub fn undo(&mut self) -> Option<Move> {
+		let skipped = self.skips_turn();
+		let mv = self.moves.pop()?;
+		let zone = self.index(mv.zone);
+		let cell = self.index(mv.cell);
+		self.cells[zone][cell] = None;
+		if self.zones[zone].is_some_and(|(_, decided)| decided == self.moves.len()) {
+			self.zones[zone] = None;
+		}
+		if !skipped {
+			self.player = self.player.opponent();
+		}
+		// No move could have been played if the game was over before
+		self.outcome = None;
+		Some(mv)
+	}
+
+	/// Whether the last move made the opponent lose their turn, sending them to a closed zone.
+	fn skips_turn(&self) -> bool {
+		let Some(last) = self.moves.last() else {
+			return false;
+		};
+		self.rules.sent_to_closed == SentToClosed::LoseTurn && self.closed(last.cell)
+	}
+
+	/// The lines to align to win a zone or the board, as indexes in the arrays of zones or cells.
+	pub(super) fn lines(&self) -> &[Vec<usize>] {
+		&self.lines
+	}
+
+	/// The index of the given coordinates in the arrays of zones or cells.
+	pub(super) fn index(&self, (x, y): Pos) -> usize {
+		assert!(self.on_board((x, y)), "Position ({}, {}) out of the board", x, y);
+		x as usize + self.rules.size as usize * y as usize
+	}
+
+	/// Whether the coordinates of a zone or of a cell are on the board.
+	fn on_board(&self, (x, y): Pos) -> bool {
+		x < self.rules.size && y < self.rules.size
+	}
+
+	/// The player having aligned a line of the given values, if any.
+	fn aligned(&self, values: &[Option<Player>]) -> Option<Player> {
+		self.lines.iter().find_map(|line| {
+			let player = values[line[0]]?;
+			line.iter().all(|&i| values[i] == Some(player)).then_some(player)
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Plays the given moves, given as `(zone, cell)` indexes on a board of size 3.
+	fn play(game: &mut SuperTTT, moves: &[(u8, u8)]) -> Option<Outcome> {
+		let mut outcome = None;
+		for &(zone, cell) in moves {
+			let mv = Move { zone: (zone % 3, zone / 3), cell: (cell % 3, cell / 3) };
+			outcome = game.play(mv).unwrap_or_else(|e| panic!("{:?}: {}", mv, e));
+		}
+		outcome
+	}
+
+	/// The moves of X taking the zones of the first diagonal with their first, middle then last
+	/// row, O being sent away each time, but the last one.
+	const DIAGONAL: [(u8, u8); 16] = [
+		(0, 1),
+		(1, 0),
+		(0, 2),
+		(2, 0),
+		(0, 0),
+		(3, 4),
+		(4, 3),
+		(3, 0),
+		(4, 5),
+		(5, 4),
+		(4, 4),
+		(6, 8),
+		(8, 6),
+		(6, 0),
+		(8, 7),
+		(7, 8),
+	];
+
+	#[test]
+	fn forced_zone() {
+		let mut game = SuperTTT::new();
+		assert_eq!(game.forced_zone(), None);
+		assert_eq!(game.legal_moves().len(), 81);
+		play(&mut game, &[(4, 0)]);
+		assert_eq!(game.forced_zone(), Some((0, 0)));
+		assert!(game.legal_moves().iter().all(|mv| mv.zone == (0, 0)));
+		assert_eq!(game.legal_moves().len(), 9);
+		let elsewhere = Move { zone: (1, 0), cell: (0, 0) };
+		assert_eq!(game.play(elsewhere), Err(IllegalMove::WrongZone));
+		// Sent back to the center, where a cell is taken
+		play(&mut game, &[(0, 4)]);
+		assert_eq!(game.legal_moves().len(), 8);
+		assert_eq!(game.play(Move { zone: (1, 1), cell: (0, 0) }), Err(IllegalMove::Occupied));
+	}
+
+	#[test]
+	fn off_board() {
+		let mut game = SuperTTT::new();
+		// (3, 0) would be the index of the zone (0, 1)
+		assert_eq!(game.play(Move { zone: (3, 0), cell: (0, 0) }), Err(IllegalMove::OffBoard));
+		assert_eq!(game.play(Move { zone: (5, 5), cell: (5, 5) }), Err(IllegalMove::OffBoard));
+		assert_eq!(game.play(Move { zone: (0, 0), cell: (0, 3) }), Err(IllegalMove::OffBoard));
+		assert!(game.moves().is_empty());
+		assert!(std::panic::catch_unwind(|| game.zone((0, 3))).is_err());
+	}
+
+	#[test]
+	fn decided_zones() {
+		let mut game = SuperTTT::new();
+		// X wins the center zone with its first row, O playing in the center of other zones
+		play(&mut game, &[(4, 0), (0, 4), (4, 1), (1, 4), (4, 2)]);
+		assert_eq!(game.zone((1, 1)), Some(Outcome::Win(Player::X)));
+		assert!(game.closed((1, 1)));
+		assert_eq!(game.zone((0, 0)), None);
+		// O is sent to the top right zone, then X back to the decided center zone
+		assert_eq!(game.forced_zone(), Some((2, 0)));
+		play(&mut game, &[(2, 4)]);
+		assert_eq!(game.forced_zone(), None);
+		assert_eq!(game.play(Move { zone: (1, 1), cell: (0, 2) }), Err(IllegalMove::ZoneClosed));
+		// All the other zones can be played in
+		assert_eq!(game.legal_moves().len(), 8 * 9 - 3);
+	}
+
+	#[test]
+	fn win_and_draw() {
+		let mut game = SuperTTT::new();
+		assert_eq!(play(&mut game, &DIAGONAL), None);
+		assert_eq!(game.zone((0, 0)), Some(Outcome::Win(Player::X)));
+		assert_eq!(game.zone((1, 1)), Some(Outcome::Win(Player::X)));
+		assert_eq!(play(&mut game, &[(8, 8)]), Some(Outcome::Win(Player::X)));
+		assert_eq!(game.outcome(), Some(Outcome::Win(Player::X)));
+		assert!(game.legal_moves().is_empty());
+		assert_eq!(game.play(Move { zone: (0, 1), cell: (2, 2) }), Err(IllegalMove::GameOver));
+		game.undo();
+		assert_eq!(game.outcome(), None);
+		assert_eq!(game.zone((2, 2)), None);
+
+		// A full zone without a line is a draw, and so is the game once all the zones are closed
+		// without a line of zones
+		let mut game = SuperTTT::new();
+		let (x, o) = (Some(Player::X), Some(Player::O));
+		game.zones = vec![Some((Outcome::Draw, 0)); 9];
+		game.zones[0] = Some((Outcome::Win(Player::O), 0));
+		game.zones[8] = None;
+		for zone in 0..8 {
+			game.cells[zone] = vec![o; 9];
+		}
+		game.cells[8] = vec![x, o, x, x, o, o, o, x, None];
+		assert_eq!(play(&mut game, &[(8, 8)]), Some(Outcome::Draw));
+		assert_eq!(game.zone((2, 2)), Some(Outcome::Draw));
+	}
+
+	#[test]
+	fn won_zones_open() {
+		let rules = Rules { won_zones_open: true, ..Rules::default() };
+		let mut game = SuperTTT::with_rules(rules);
+		play(&mut game, &[(4, 0), (0, 4), (4, 1), (1, 4), (4, 2)]);
+		assert_eq!(game.zone((1, 1)), Some(Outcome::Win(Player::X)));
+		assert!(!game.closed((1, 1)));
+		// O is sent to the top right zone, then X back to the won center zone, where they must
+		// play until it is full
+		play(&mut game, &[(2, 4)]);
+		assert_eq!(game.forced_zone(), Some((1, 1)));
+		assert_eq!(game.legal_moves().len(), 6);
+		play(&mut game, &[(4, 6), (6, 4), (4, 7), (7, 4), (4, 8), (8, 4), (4, 3), (3, 4), (4, 5)]);
+		play(&mut game, &[(5, 4), (4, 4)]);
+		assert!(game.closed((1, 1)));
+		assert_eq!(game.zone((1, 1)), Some(Outcome::Win(Player::X)));
+		assert_eq!(game.forced_zone(), None);
+		game.undo();
+		assert!(!game.closed((1, 1)));
+		assert_eq!(game.zone((1, 1)), Some(Outcome::Win(Player::X)));
+	}
+
+	#[test]
+	fn lose_turn() {
+		let rules = Rules { sent_to_closed: SentToClosed::LoseTurn, ..Rules::default() };
+		let mut game = SuperTTT::with_rules(rules);
+		play(&mut game, &[(4, 0), (0, 4), (4, 1), (1, 4), (4, 2)]);
+		// O is sent to the top right zone, then X to the won center zone: X loses their turn and
+		// O plays again anywhere
+		play(&mut game, &[(2, 4)]);
+		assert_eq!(game.player(), Player::O);
+		assert_eq!(game.forced_zone(), None);
+		play(&mut game, &[(6, 0)]);
+		assert_eq!(game.player(), Player::X);
+		assert_eq!(game.undo(), Some(Move { zone: (0, 2), cell: (0, 0) }));
+		assert_eq!(game.player(), Player::O);
+		game.undo();
+		assert_eq!(game.player(), Player::O);
+		assert_eq!(game.forced_zone(), Some((2, 0)));
+	}
+
+	#[test]
+	fn sizes() {
+		let rules = |size| Rules { size, ..Rules::default() };
+		let game = SuperTTT::with_rules(rules(4));
+		assert_eq!(game.legal_moves().len(), 256);
+		assert_eq!(game.lines().len(), 10);
+		assert!(game.lines().contains(&vec![3, 6, 9, 12]));
+		assert_eq!(game.positions().last(), Some((3, 3)));
+
+		// The 4 cells of a row are needed to win a zone
+		let mut game = SuperTTT::with_rules(rules(4));
+		let moves = [
+			((1, 1), (0, 1)),
+			((0, 1), (1, 1)),
+			((1, 1), (1, 1)),
+			((1, 1), (0, 0)),
+			((0, 0), (0, 0)),
+			((0, 0), (1, 1)),
+			((1, 1), (2, 1)),
+			((2, 1), (1, 1)),
+		];
+		for (zone, cell) in moves {
+			game.play(Move { zone, cell }).unwrap();
+		}
+		assert_eq!(game.zone((1, 1)), None);
+		game.play(Move { zone: (1, 1), cell: (3, 1) }).unwrap();
+		assert_eq!(game.zone((1, 1)), Some(Outcome::Win(Player::X)));
+
+		assert!(std::panic::catch_unwind(|| SuperTTT::with_rules(rules(6))).is_err());
+	}
+
+	#[test]
+	fn undo() {
+		let variants = [
+			Rules::default(),
+			Rules { won_zones_open: true, ..Rules::default() },
+			Rules { sent_to_closed: SentToClosed::LoseTurn, size: 2, ..Rules::default() },
+			Rules { size: 4, ..Rules::default() },
+		];
+		for rules in variants {
+			let mut game = SuperTTT::with_rules(rules);
+			let mut states = vec![];
+			// Plays the first legal move until the game ends
+			while let Some(&mv) = game.legal_moves().first() {
+				states.push(game.clone());
+				game.play(mv).unwrap();
+			}
+			assert!(game.outcome().is_some());
+			while let Some(state) = states.pop() {
+				let mv = game.undo().unwrap();
+				assert_eq!(game, state, "{:?}", rules);
+				assert_eq!(game.check(mv), Ok(()));
+			}
+			assert_eq!(game.undo(), None);
+			assert_eq!(game, SuperTTT::with_rules(rules));
+		}
+	}
+}
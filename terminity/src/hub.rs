@@ -0,0 +1,263 @@
+//! A menu listing the games of a [`Registery`] and running the chosen ones.
+//!
+//! This is what the `hub` binary runs with the built-in games, but any registery can be given,
+//! allowing other crates to have their own games listed next to terminity's:
+//!
+//! ```no_run
+//! use terminity::games::Registery;
+//! # struct MyGame;
+//! # impl terminity::games::Game for MyGame {
+//! # 	fn name(&self) -> &str { "MyGame" }
+//! # 	fn run(&self, _: &mut dyn terminity::backend::Terminal) -> std::io::Result<()> { Ok(()) }
+//! # }
+//!
+//! let registery = Registery::builder().with_defaults().register(MyGame).build();
+//! terminity::hub::run(&registery).unwrap();
+//! ```
+
+use std::fmt::{Formatter, Write as _};
+use std::io::{self, Write};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::style::{ContentStyle, Stylize};
+use crossterm::{cursor, QueueableCommand};
+use terminity_widgets::render::{self, Buffer, Renderer};
+use terminity_widgets::widgets::auto_padder::AutoPadder;
+use terminity_widgets::{MouseEventWidget, ResizableWisget, Widget, WidgetDisplay};
+
+use crate::backend::Terminal;
+use crate::events::{self, Event, EventHandler, Flow};
+use crate::games::{self, GameWrapper, Registery};
+
+const TITLE: &str = "~ Terminity ~";
+const HELP: &str = "Up/Down or mouse: select | Enter or click: play | Esc: quit";
+
+/// What the menu asks the hub to do next.
+enum Choice<'a> {
+	Play(&'a GameWrapper),
+	Quit,
+}
+
+/// The list of all the registered games, one of them being selected.
+#[derive(WidgetDisplay)]
+struct Menu<'a> {
+	games: Vec<&'a GameWrapper>,
+	selected: usize,
+	width: usize,
+	/// A message to display to the player, like why a game couldn't be launched.
+	message: String,
+}
+
+impl<'a> Menu<'a> {
+	/// Index of the first line displaying a game.
+	const LIST_START: usize = 2;
+
+	fn new(registery: &'a Registery) -> Self {
+		let games: Vec<_> = registery.iter().collect();
+		let width = games
+			.iter()
+			.flat_map(|g| [render::width(g.name()) + 4, render::width(g.game().description())])
+			.chain([render::width(HELP)])
+			.max();
+		Self { games, selected: 0, width: width.unwrap_or(0), message: String::new() }
+	}
+
+	/// The details of the selected game, displayed under the list.
+	fn details(&self) -> [String; 3] {
+		let game = self.games[self.selected].game();
+		let players = game.players();
+		let players = if players.start() == players.end() {
+			format!("{} player{}", players.start(), if *players.start() > 1 { "s" } else { "" })
+		} else {
+			format!("{} to {} players", players.start(), players.end())
+		};
+		[game.description().to_owned(), players, self.message.clone()]
+	}
+}
+
+impl Widget for Menu<'_> {
+	fn displ_line(&self, f: &mut Formatter<'_>, line: usize) -> std::fmt::Result {
+		let list_end = Self::LIST_START + self.games.len();
+		// The content of the line, and its length once displayed
+		let (content, len) = match line {
+			0 => (TITLE.to_owned(), render::width(TITLE)),
+			l if (Self::LIST_START..list_end).contains(&l) => {
+				let i = l - Self::LIST_START;
+				let name = format!("  {}  ", self.games[i].name());
+				let len = render::width(&name);
+				if i == self.selected {
+					(ContentStyle::new().reverse().apply(name).to_string(), len)
+				} else {
+					(name, len)
+				}
+			}
+			l if (list_end + 1..list_end + 4).contains(&l) => {
+				let detail = self.details()[l - list_end - 1].clone();
+				let len = render::width(&detail);
+				(detail, len)
+			}
+			l if l == list_end + 5 => (HELP.to_owned(), render::width(HELP)),
+			_ => (String::new(), 0),
+		};
+		// The message may be wider than the menu
+		let (content, len) = if len > self.width {
+			(render::clip(&content, self.width), self.width)
+		} else {
+			(content, len)
+		};
+		let diff = self.width - len;
+		for _ in 0..diff / 2 {
+			f.write_char(' ')?;
+		}
+		f.write_str(&content)?;
+		for _ in 0..(diff - diff / 2) {
+			f.write_char(' ')?;
+		}
+		Ok(())
+	}
+	fn size(&self) -> (usize, usize) {
+		(self.width, self.games.len() + Self::LIST_START + 6)
+	}
+}
+
+impl MouseEventWidget for Menu<'_> {
+	/// Whether the game under the mouse was clicked.
+	type MouseHandlingResult = bool;
+	fn mouse_event(&mut self, event: MouseEvent) -> Self::MouseHandlingResult {
+		// NB: the event will be filtered and re-indexed by the wrapping Auto-Padder
+		let row = event.row as usize;
+		if !(Self::LIST_START..Self::LIST_START + self.games.len()).contains(&row) {
+			return false;
+		}
+		if self.selected != row - Self::LIST_START {
+			self.selected = row - Self::LIST_START;
+			self.message.clear();
+		}
+		matches!(event.kind, MouseEventKind::Down(MouseButton::Left))
+	}
+}
+
+/// The menu, displayed until a game that fits in the terminal is chosen or the player quits.
+struct Chooser<'m, 'a> {
+	menu: &'m mut AutoPadder<Menu<'a>>,
+	size: (u16, u16),
+	renderer: Renderer,
+	/// The choice of the player, `None` if the menu was interrupted
+	choice: Option<Choice<'a>>,
+}
+
+impl EventHandler for Chooser<'_, '_> {
+	fn update(&mut self, event: Event) -> Flow {
+		use KeyCode::*;
+		let menu = &mut self.menu;
+		let launch = match event {
+			Event::Key(KeyEvent { code, kind: KeyEventKind::Press, .. }) => match code {
+				Up | Down => {
+					let len = menu.games.len();
+					menu.selected = (menu.selected + if code == Up { len - 1 } else { 1 }) % len;
+					menu.message.clear();
+					false
+				}
+				Enter => true,
+				Esc | Char('q') => {
+					self.choice = Some(Choice::Quit);
+					return Flow::Exit;
+				}
+				_ => false,
+			},
+			Event::Mouse(e) => menu.mouse_event(e) == Some(true),
+			Event::Resize(w, h) => {
+				self.size = (w, h);
+				self.renderer.invalidate();
+				menu.resize((w as usize, h as usize));
+				false
+			}
+			_ => false,
+		};
+		if launch {
+			let game = menu.games[menu.selected];
+			let (min_w, min_h) = game.game().min_size();
+			if self.size.0 < min_w || self.size.1 < min_h {
+				menu.message = format!(
+					"The terminal is too small for {} (needs {}x{}, got {}x{})",
+					game.name(),
+					min_w,
+					min_h,
+					self.size.0,
+					self.size.1
+				);
+			} else {
+				self.choice = Some(Choice::Play(game));
+				return Flow::Exit;
+			}
+		}
+		Flow::Continue
+	}
+
+	fn render(&mut self, out: &mut dyn Write) -> io::Result<()> {
+		out.queue(cursor::Hide)?;
+		self.renderer.render(out, Buffer::from_widget(&*self.menu))
+	}
+}
+
+/// Displays the menu until a game that fits in the terminal is chosen or the player quits.
+fn choose<'a>(menu: &mut AutoPadder<Menu<'a>>, term: &mut dyn Terminal) -> io::Result<Choice<'a>> {
+	let mut chooser = Chooser { menu, size: (0, 0), renderer: Renderer::new(), choice: None };
+	events::run(&mut chooser, term)?;
+	Ok(chooser.choice.unwrap_or(Choice::Quit))
+}
+
+/// Runs the hub on the given registery: lists its games, runs the chosen one and comes back to
+/// the list once it exits, until the player quits.
+pub fn run(registery: &Registery) -> io::Result<()> {
+	if registery.is_empty() {
+		return Err(io::Error::new(io::ErrorKind::NotFound, "No game registered"));
+	}
+	let mut menu = AutoPadder(Menu::new(registery), (0, 0));
+	loop {
+		// The menu is displayed in the same environment as the games, and the environment is left
+		// before the chosen game sets it up again.
+		let choice = games::with_environment(|term| choose(&mut menu, term))?;
+		match choice {
+			Choice::Play(game) => game.run()?,
+			Choice::Quit => return Ok(()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use terminity_widgets::widgets::checked;
+
+	use super::*;
+	use crate::games::Game;
+
+	struct Tiny;
+
+	impl Game for Tiny {
+		fn name(&self) -> &str {
+			"Tiny Game"
+		}
+		fn description(&self) -> &str {
+			"Small"
+		}
+		fn run(&self, _: &mut dyn crate::backend::Terminal) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn long_message() {
+		let registery = Registery::builder().register(Tiny).build();
+		let mut menu = Menu::new(&registery);
+		menu.message =
+			"The terminal is too small for Tiny Game (needs 120x40, got 20x10)".to_owned();
+		let width = menu.size().0;
+		for line in 0..menu.size().1 {
+			let content = checked::check(&menu, line).unwrap();
+			assert_eq!(render::width(&content), width);
+		}
+		let message = checked::check(&menu, Menu::LIST_START + 1 + 3).unwrap();
+		assert!(message.starts_with("The terminal is too small"));
+	}
+}
@@ -1,12 +1,13 @@
 //! Crate to register multiple terminal games, allow to choose a game and set up an environment
 //! to run them. This is at an extremely early development stage.
 //!
-//! To try it, clone the project and run `cargo run Chess` or `cargo run SuperTicTacToe`.
+//! To try it, clone the project and run `cargo run --bin hub` to choose a game in a menu, or
+//! directly `cargo run Chess`, `cargo run SuperTicTacToe` or `cargo run Stratego`.
 //!
 //! The purposes and goals of this crate are to to:
 //! 1. Make it easier to build good UI in terminal
 //! 2. Make terminal games accessible to anyone and everyone. A windows `.exe` is to be expected,
-//! and I'd love to make a smartphone app.
+//!    and I'd love to make a smartphone app.
 //!
 //! I would also love to setup a P2P (peer to peer) system allowing to play the games online with
 //! anyone, and giving the programmers an API to setup an online game without a mandatory need for a
@@ -26,12 +27,16 @@
 //! * Enable raw mode
 //! * Save cursor position and move it to 0,0
 //! * When any unwind (and thus most panics) occurs, the terminal state is restored before the
-//! unwinding data is displayed (the display may be improved though). Without that, the terminal
-//! state keeps the configuration and the sh CLI becomes crappy.
+//!   unwinding data is displayed (the display may be improved though). Without that, the terminal
+//!   state keeps the configuration and the sh CLI becomes crappy.
 //!
 //! The result of doing all this can be seen for instance on the chess implementation, where
 //! dragging with the mouse is supported, and any keyboard input is captured immediately.
 
 #![warn(missing_docs)]
+#![allow(clippy::tabs_in_doc_comments)]
 
+pub mod backend;
+pub mod events;
 pub mod games;
+pub mod hub;
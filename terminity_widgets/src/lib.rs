@@ -1,9 +1,13 @@
 #![warn(missing_docs)]
+#![allow(clippy::tabs_in_doc_comments)]
 
 /*! Widgets for easier terminal UI building.
 
 	This crate revolves around the trait [`Widget`], and defines various [widgets] to help building
 	your own. It also defines various other traits for more transparent usage of the widgets.
+	Widgets can be displayed directly, or drawn into a [`Buffer`](render::Buffer) that is
+	[rendered](render) without redrawing what didn't change. They can be composed by drawing a
+	[`frame!`], or laid out in [containers](widgets::layout) reflowing them when resized.
 
 	This crate is currently at a very early development stage. The first changes it might have are
 	an api for un-resizeable widgets and more widgets.
@@ -15,6 +19,7 @@ use std::fmt::Formatter;
 pub use terminity_widgets_proc::frame;
 pub use terminity_widgets_proc::WidgetDisplay;
 
+pub mod render;
 pub mod widgets;
 
 // Re-export for internal use
@@ -24,7 +29,7 @@ pub mod _reexport {
 	pub use crossterm::terminal::ClearType::UntilNewLine;
 }
 
-/// An item displayable on multiple lines, assumed to be a rectangle (though not yet enforced).
+/// An item displayable on multiple lines, assumed to be a rectangle.
 ///
 /// This trait is mainly used for composition, like inside frame. The idea is that widgets can include
 /// other widgets, and this trait allows to display only one line of the widget, allowing the parent
@@ -32,10 +37,13 @@ pub mod _reexport {
 /// For the parent to be able to prepare for the display of a child widget, it probably needs to know
 /// the size of this one, explaining the existence of `size`.
 ///
-/// For the widget to behave correctly, it has to output lines of exactly the expected length.
+/// For the widget to behave correctly, it has to output lines of exactly the expected length,
+/// counted in terminal cells as [`render::width`] does (wide characters taking two cells).
 /// Multiple widgets here will break this rule if not used correctly (like by putting a Widget of
-/// size different from the one expected into a Frame or putting text longer than anticipated into
-/// a Text), `debug_assert`s might be added in the near future.
+/// size different from the one expected into a Frame). In debug builds, the widgets of this crate
+/// check the lines of their children and panic when this rule is broken, reporting the faulty
+/// widget. A [`Checked`](widgets::checked::Checked) widget can enforce it in release builds too, by
+/// truncating or padding the lines.
 ///
 /// Be careful, to avoid breaking this rule all the time, if the content of your widget is not long
 /// enough you should add padding.
@@ -58,6 +66,40 @@ pub trait Widget {
 	/// assert_eq!(formatted, "World");
 	/// ```
 	fn displ_line(&self, f: &mut Formatter<'_>, line: usize) -> std::fmt::Result;
+	/// Draws the given line of the widget into a row of styled cells, as wide as the widget (or
+	/// less, if the widget is clipped). The cells are blank when given.
+	///
+	/// Drawing cells spares parsing escape sequences to measure, clip or recolour the widget. By
+	/// default, the line written by [`displ_line`](Self::displ_line) is parsed into cells, but
+	/// widgets can instead draw directly into the cells, as with [`render::print`], and draw their
+	/// children with [`render::draw_at`].
+	///
+	/// ```
+	/// use crossterm::style::{Color, ContentStyle, Stylize};
+	/// use terminity_widgets::render::{self, Cell};
+	/// use terminity_widgets::Widget;
+	/// # use std::fmt::Formatter;
+	///
+	/// struct Warning;
+	/// impl Widget for Warning {
+	/// 	fn size(&self) -> (usize, usize) { (4, 1) }
+	/// 	fn draw_line(&self, cells: &mut [Cell], _: usize) {
+	/// 		render::print(cells, 0, "Oops", ContentStyle::new().red());
+	/// 	}
+	/// 	// ...
+	/// 	# fn displ_line(&self, f: &mut Formatter<'_>, _: usize) -> std::fmt::Result {
+	/// 		# write!(f, "{}", "Oops".red())
+	/// 	# }
+	/// }
+	///
+	/// let mut cells = vec![Cell::default(); 4];
+	/// Warning.draw_line(&mut cells, 0);
+	/// assert_eq!(cells[0].content, "O");
+	/// assert_eq!(cells[0].style.foreground_color, Some(Color::Red));
+	/// ```
+	fn draw_line(&self, cells: &mut [render::Cell], line: usize) {
+		render::print_ansi(cells, 0, &render::Formatted(self, line).to_string());
+	}
 	/// The current size of the widget, composed of first the width, then the height.
 	///
 	/// ```
@@ -0,0 +1,612 @@
+//! Rendering of widgets without redrawing the whole screen.
+//!
+//! Displaying a widget through its [`Display`] implementation writes all of its
+//! lines each time, which flickers and wastes bandwidth on slow terminals. Instead, widgets can be
+//! drawn into a [`Buffer`], an off-screen grid of styled [`Cell`]s, and the buffer given to a
+//! [`Renderer`]. The renderer compares it to the previously rendered one and only prints the cells
+//! that changed, with as few cursor moves and style changes as it can.
+//!
+//! ```
+//! use terminity_widgets::render::{Buffer, Renderer};
+//! use terminity_widgets::widgets::text::Text;
+//!
+//! let mut text = Text::new(["Hello".into(), "World".into()], 5);
+//! let mut renderer = Renderer::new();
+//! let mut out = vec![];
+//! renderer.render(&mut out, Buffer::from_widget(&text)).unwrap();
+//!
+//! // Only the changed cell is printed again
+//! text[0] = "Hallo".into();
+//! out.clear();
+//! renderer.render(&mut out, Buffer::from_widget(&text)).unwrap();
+//! assert_eq!(String::from_utf8(out).unwrap(), "\x1b[1;2H\x1b[0ma");
+//! ```
+//!
+//! # Widths
+//!
+//! Widths are counted in terminal cells, as measured by [`width`]: East-Asian wide characters and
+//! most emoji take two cells, combining characters and escape sequences none. A wide character
+//! that would straddle the right edge of a widget (or of the area it is clipped to) isn't drawn,
+//! its first cell is left blank instead, so that it never spills over what is next to it. In the
+//! same way, drawing a widget erases the wide characters of its neighbours that straddle its edges.
+
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Write};
+
+use crossterm::cursor::{MoveRight, MoveTo};
+use crossterm::style::{
+	Attribute, Color, ContentStyle, Print, SetAttribute, SetAttributes, SetBackgroundColor,
+	SetForegroundColor, SetUnderlineColor,
+};
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::QueueableCommand;
+use unicode_width::UnicodeWidthChar;
+
+use crate::widgets::checked;
+use crate::Widget;
+
+/// A cell of a [`Buffer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cell {
+	/// What is displayed in the cell. Usually a single character, but is empty for the second
+	/// half of a wide character, and may contain combining characters.
+	pub content: String,
+	/// The style the cell is displayed with.
+	pub style: ContentStyle,
+}
+
+impl Default for Cell {
+	fn default() -> Self {
+		Self { content: " ".to_owned(), style: ContentStyle::new() }
+	}
+}
+
+/// An off-screen grid of [`Cell`]s, widgets are drawn into before being [rendered](Renderer).
+///
+/// ```
+/// use crossterm::style::{Color, Stylize};
+/// use terminity_widgets::render::Buffer;
+///
+/// let mut buffer = Buffer::new((6, 2));
+/// buffer.print((1, 1), &format!("a{}", "b".red()));
+/// assert_eq!(buffer.line(1), " ab   ");
+/// assert_eq!(buffer.cell(2, 1).style.foreground_color, Some(Color::Red));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Buffer {
+	size: (usize, usize),
+	cells: Vec<Cell>,
+}
+
+impl Buffer {
+	/// Creates a buffer of the given size (columns, rows), filled with blank cells.
+	pub fn new(size: (usize, usize)) -> Self {
+		Self { size, cells: vec![Cell::default(); size.0 * size.1] }
+	}
+
+	/// Creates a buffer of the size of the given widget, and draws it.
+	pub fn from_widget<W: Widget + ?Sized>(widget: &W) -> Self {
+		let mut buffer = Self::new(widget.size());
+		buffer.draw((0, 0), widget);
+		buffer
+	}
+
+	/// The size (columns, rows) of the buffer.
+	pub fn size(&self) -> (usize, usize) {
+		self.size
+	}
+
+	/// The cell at the given position. Panics if the position is out of the buffer.
+	pub fn cell(&self, x: usize, y: usize) -> &Cell {
+		assert!(x < self.size.0 && y < self.size.1, "Cell ({}, {}) out of the buffer", x, y);
+		&self.cells[x + y * self.size.0]
+	}
+
+	/// The text of the given row, without any style.
+	pub fn line(&self, y: usize) -> String {
+		(0..self.size.0).map(|x| self.cell(x, y).content.as_str()).collect()
+	}
+
+	/// A row of the buffer, to draw into. Panics if the row is out of the buffer.
+	pub fn row_mut(&mut self, y: usize) -> &mut [Cell] {
+		assert!(y < self.size.1, "Row {} out of the buffer", y);
+		&mut self.cells[y * self.size.0..(y + 1) * self.size.0]
+	}
+
+	/// Draws the given widget, its top-left corner at the given position (column, row). What goes
+	/// out of the buffer is left out.
+	pub fn draw<W: Widget + ?Sized>(&mut self, (x, y): (usize, usize), widget: &W) {
+		let (width, height) = widget.size();
+		for line in 0..height.min(self.size.1.saturating_sub(y)) {
+			let row = self.row_mut(y + line);
+			let end = (x + width).min(row.len());
+			if x >= end {
+				return;
+			}
+			// The widget is given blank cells, erasing the wide characters it overlaps
+			if row[x].content.is_empty() && x > 0 {
+				row[x - 1].content = " ".to_owned();
+			}
+			if end < row.len() && row[end].content.is_empty() {
+				row[end].content = " ".to_owned();
+			}
+			row[x..end].fill(Cell::default());
+			checked::debug_check_draw(widget, line, &row[x..end]);
+			widget.draw_line(&mut row[x..end], line);
+		}
+	}
+
+	/// Prints the given text on a single row, starting at the given position (column, row), and
+	/// returns the column after the last printed character. See [`print_ansi`].
+	pub fn print(&mut self, (x, y): (usize, usize), text: &str) -> usize {
+		if y >= self.size.1 {
+			return x;
+		}
+		print_ansi(self.row_mut(y), x, text)
+	}
+}
+
+/// The width of the given text, in terminal cells. Escape sequences and control characters don't
+/// count.
+///
+/// ```
+/// use crossterm::style::Stylize;
+/// use terminity_widgets::render::width;
+///
+/// assert_eq!(width(&format!("a{}", "é".red())), 2);
+/// assert_eq!(width("世界!"), 5);
+/// ```
+pub fn width(text: &str) -> usize {
+	print_ansi(&mut [], 0, text)
+}
+
+/// Cuts the given text to the given width (in terminal cells), keeping all of its escape
+/// sequences. A wide character straddling the limit is replaced by a space.
+///
+/// ```
+/// use terminity_widgets::render::clip;
+///
+/// assert_eq!(clip("\x1b[1mab\x1b[0m", 1), "\x1b[1ma\x1b[0m");
+/// assert_eq!(clip("a世界", 2), "a ");
+/// ```
+pub fn clip(text: &str, width: usize) -> String {
+	let mut res = String::new();
+	let mut x = 0;
+	let mut chars = text.chars();
+	while let Some(c) = chars.next() {
+		if c == '\x1b' {
+			res.push(c);
+			match chars.next() {
+				Some('[') => {
+					res.push('[');
+					for c in chars.by_ref() {
+						res.push(c);
+						if ('\x40'..='\x7e').contains(&c) {
+							break;
+						}
+					}
+				}
+				Some(c) => res.push(c),
+				None => (),
+			}
+			continue;
+		}
+		let c_width = if c.is_control() { 0 } else { c.width().unwrap_or(0) };
+		if x + c_width <= width {
+			res.push(c);
+		} else if x < width {
+			res.push(' ');
+		}
+		x += c_width;
+	}
+	res
+}
+
+/// Prints the given text in the given style into a row of cells, starting at column `x`, and
+/// returns the column after the last printed character. Control characters are ignored, and what
+/// goes out of the cells is left out, still counting in the returned column.
+///
+/// ```
+/// use crossterm::style::{ContentStyle, Stylize};
+/// use terminity_widgets::render::{print, Cell};
+///
+/// let mut cells = vec![Cell::default(); 4];
+/// assert_eq!(print(&mut cells, 1, "世界", ContentStyle::new().bold()), 5);
+/// assert_eq!(cells[1].content, "世");
+/// assert_eq!(cells[2].content, "");
+/// assert_eq!(cells[3].content, " ");
+/// ```
+pub fn print(cells: &mut [Cell], x: usize, text: &str, style: ContentStyle) -> usize {
+	text.chars().fold(x, |x, c| print_char(cells, x, c, style))
+}
+
+/// Prints the given text into a row of cells as [`print()`] does, but applying the styles set by the
+/// SGR escape sequences of the text (as the ones of crossterm's
+/// [`Stylize`](crossterm::style::Stylize)). The text starts unstyled, and the other escape
+/// sequences are ignored.
+///
+/// ```
+/// use crossterm::style::{Color, Stylize};
+/// use terminity_widgets::render::{print_ansi, Cell};
+///
+/// let mut cells = vec![Cell::default(); 4];
+/// assert_eq!(print_ansi(&mut cells, 0, &format!("a{}", "b".red())), 2);
+/// assert_eq!(cells[1].style.foreground_color, Some(Color::Red));
+/// ```
+pub fn print_ansi(cells: &mut [Cell], mut x: usize, text: &str) -> usize {
+	let mut style = ContentStyle::new();
+	let mut chars = text.chars();
+	while let Some(c) = chars.next() {
+		if c != '\x1b' {
+			x = print_char(cells, x, c, style);
+			continue;
+		}
+		if chars.next() != Some('[') {
+			continue;
+		}
+		let mut params = String::new();
+		for c in chars.by_ref() {
+			if ('\x40'..='\x7e').contains(&c) {
+				// Only the SGR sequences are interpreted
+				if c == 'm' {
+					apply_sgr(&mut style, &params);
+				}
+				break;
+			}
+			params.push(c);
+		}
+	}
+	x
+}
+
+/// Draws the given line of a widget into a row of blank cells, starting at column `x`, and returns
+/// the column after the widget. What goes out of the cells is left out. This is how widgets draw
+/// their children in [`Widget::draw_line`].
+pub fn draw_at<W: Widget + ?Sized>(cells: &mut [Cell], x: usize, widget: &W, line: usize) -> usize {
+	let width = widget.size().0;
+	let end = (x + width).min(cells.len());
+	if x < end {
+		checked::debug_check_draw(widget, line, &cells[x..end]);
+		widget.draw_line(&mut cells[x..end], line);
+	}
+	x + width
+}
+
+/// Prints a character at column `x`, and returns the column after it.
+fn print_char(cells: &mut [Cell], x: usize, c: char, style: ContentStyle) -> usize {
+	if c.is_control() {
+		return x;
+	}
+	let width = c.width().unwrap_or(0);
+	if width == 0 {
+		// Combining character: goes with the previous one
+		if x > 0 && x <= cells.len() {
+			cells[x - 1].content.push(c);
+		}
+		return x;
+	}
+	if x + width <= cells.len() {
+		put(cells, x, Cell { content: c.to_string(), style });
+		if width == 2 {
+			put(cells, x + 1, Cell { content: String::new(), style });
+		}
+	}
+	x + width
+}
+
+/// Puts a cell, erasing any wide character it overlaps.
+fn put(cells: &mut [Cell], x: usize, cell: Cell) {
+	if cells[x].content.is_empty() && x > 0 {
+		cells[x - 1].content = " ".to_owned();
+	}
+	if x + 1 < cells.len() && cells[x + 1].content.is_empty() {
+		cells[x + 1].content = " ".to_owned();
+	}
+	cells[x] = cell;
+}
+
+/// A line of a widget, to format it.
+pub(crate) struct Formatted<'a, W: ?Sized>(pub &'a W, pub usize);
+
+impl<W: Widget + ?Sized> Display for Formatted<'_, W> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		self.0.displ_line(f, self.1)
+	}
+}
+
+/// Applies the parameters of a Select Graphic Rendition escape sequence (as `1;31` in
+/// `\x1b[1;31m`) to the given style.
+///
+/// ```
+/// use crossterm::style::{Attribute, Color, ContentStyle};
+/// use terminity_widgets::render::apply_sgr;
+///
+/// let mut style = ContentStyle::new();
+/// apply_sgr(&mut style, "1;38;5;9");
+/// assert!(style.attributes.has(Attribute::Bold));
+/// assert_eq!(style.foreground_color, Some(Color::Red));
+/// apply_sgr(&mut style, "0");
+/// assert_eq!(style, ContentStyle::new());
+/// ```
+pub fn apply_sgr(style: &mut ContentStyle, params: &str) {
+	const UNDERLINES: [Attribute; 5] = [
+		Attribute::Underlined,
+		Attribute::DoubleUnderlined,
+		Attribute::Undercurled,
+		Attribute::Underdotted,
+		Attribute::Underdashed,
+	];
+	let mut params = params.split(';');
+	while let Some(param) = params.next() {
+		// Sub-parameters (as in "4:3" for curly underline)
+		let mut sub = param.split(':');
+		let code: u16 = sub.next().unwrap_or("").parse().unwrap_or(0);
+		let attributes = &mut style.attributes;
+		match code {
+			0 => *style = ContentStyle::new(),
+			1 => attributes.set(Attribute::Bold),
+			2 => attributes.set(Attribute::Dim),
+			3 => attributes.set(Attribute::Italic),
+			4 => match sub.next() {
+				Some("0") => UNDERLINES.into_iter().for_each(|a| attributes.unset(a)),
+				Some("2") => attributes.set(Attribute::DoubleUnderlined),
+				Some("3") => attributes.set(Attribute::Undercurled),
+				Some("4") => attributes.set(Attribute::Underdotted),
+				Some("5") => attributes.set(Attribute::Underdashed),
+				_ => attributes.set(Attribute::Underlined),
+			},
+			5 => attributes.set(Attribute::SlowBlink),
+			6 => attributes.set(Attribute::RapidBlink),
+			7 => attributes.set(Attribute::Reverse),
+			8 => attributes.set(Attribute::Hidden),
+			9 => attributes.set(Attribute::CrossedOut),
+			21 | 22 => {
+				attributes.unset(Attribute::Bold);
+				attributes.unset(Attribute::Dim);
+			}
+			23 => attributes.unset(Attribute::Italic),
+			24 => UNDERLINES.into_iter().for_each(|a| attributes.unset(a)),
+			25 => {
+				attributes.unset(Attribute::SlowBlink);
+				attributes.unset(Attribute::RapidBlink);
+			}
+			27 => attributes.unset(Attribute::Reverse),
+			28 => attributes.unset(Attribute::Hidden),
+			29 => attributes.unset(Attribute::CrossedOut),
+			30..=37 => style.foreground_color = ansi_color(code - 30),
+			90..=97 => style.foreground_color = ansi_color(code - 90 + 8),
+			40..=47 => style.background_color = ansi_color(code - 40),
+			100..=107 => style.background_color = ansi_color(code - 100 + 8),
+			38 | 48 | 58 => {
+				let color = match params.next() {
+					Some("5") => params.next().map(|n| format!("5;{}", n)),
+					Some("2") => {
+						let rgb: Vec<_> = params.by_ref().take(3).collect();
+						Some(format!("2;{}", rgb.join(";")))
+					}
+					_ => None,
+				}
+				.and_then(|c| Color::parse_ansi(&c));
+				match code {
+					38 => style.foreground_color = color,
+					48 => style.background_color = color,
+					_ => style.underline_color = color,
+				}
+			}
+			39 => style.foreground_color = None,
+			49 => style.background_color = None,
+			59 => style.underline_color = None,
+			_ => (),
+		}
+	}
+}
+
+/// The color of the given index in the 16 colors palette.
+fn ansi_color(index: u16) -> Option<Color> {
+	Color::parse_ansi(&format!("5;{}", index))
+}
+
+/// Renders [`Buffer`]s on a terminal, only printing what changed since the previous one.
+///
+/// The renderer assumes that the terminal still shows the previously rendered buffer, at the
+/// top-left corner of the screen. When that's not the case anymore (as when the screen was cleared
+/// or the terminal resized), it has to be [invalidated](Self::invalidate), for the next buffer to
+/// be drawn entirely. It is also drawn entirely when its size differs from the previous one.
+///
+/// The cursor may be moved between two renders, its position is never assumed at the start of a
+/// render. The style is reset at the end of each render.
+#[derive(Debug, Default)]
+pub struct Renderer {
+	/// The buffer shown on the terminal, if known.
+	previous: Option<Buffer>,
+}
+
+impl Renderer {
+	/// Creates a renderer, that draws the first buffer entirely.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Forgets what the terminal shows: the next buffer is drawn entirely, after clearing the
+	/// screen.
+	pub fn invalidate(&mut self) {
+		self.previous = None;
+	}
+
+	/// Renders the given buffer, at the top-left corner of the screen. The output isn't flushed.
+	pub fn render<O: Write + ?Sized>(&mut self, out: &mut O, buffer: Buffer) -> io::Result<()> {
+		let previous = match self.previous.take() {
+			Some(previous) if previous.size == buffer.size => previous,
+			_ => {
+				out.queue(Clear(ClearType::All))?;
+				Buffer::new(buffer.size)
+			}
+		};
+		let mut cursor = None;
+		let mut pen = None;
+		for y in 0..buffer.size.1 {
+			for x in 0..buffer.size.0 {
+				let cell = buffer.cell(x, y);
+				// The second half of a wide character is printed with the first one
+				if cell == previous.cell(x, y) || cell.content.is_empty() {
+					continue;
+				}
+				match cursor {
+					Some((cx, cy)) if cy == y && cx == x => (),
+					Some((cx, cy)) if cy == y && cx < x => {
+						out.queue(MoveRight((x - cx) as u16))?;
+					}
+					_ => {
+						out.queue(MoveTo(x as u16, y as u16))?;
+					}
+				}
+				set_style(out, &mut pen, cell.style)?;
+				out.queue(Print(&cell.content))?;
+				let wide = x + 1 < buffer.size.0 && buffer.cell(x + 1, y).content.is_empty();
+				cursor = Some((x + if wide { 2 } else { 1 }, y));
+			}
+		}
+		if pen.is_some_and(|pen| pen != ContentStyle::new()) {
+			out.queue(SetAttribute(Attribute::Reset))?;
+		}
+		self.previous = Some(buffer);
+		Ok(())
+	}
+}
+
+/// Changes the style of the terminal from `pen` (unknown if `None`) to `style`.
+fn set_style<O: Write + ?Sized>(
+	out: &mut O,
+	pen: &mut Option<ContentStyle>,
+	style: ContentStyle,
+) -> io::Result<()> {
+	let mut current = match *pen {
+		Some(current) if current == style => return Ok(()),
+		Some(current) => current,
+		None => {
+			out.queue(SetAttribute(Attribute::Reset))?;
+			ContentStyle::new()
+		}
+	};
+	// Attributes can only be removed one by one through their own codes, resetting instead
+	let changed = current.attributes ^ style.attributes;
+	if !(changed & current.attributes).is_empty() {
+		out.queue(SetAttribute(Attribute::Reset))?;
+		current = ContentStyle::new();
+	}
+	if current.foreground_color != style.foreground_color {
+		out.queue(SetForegroundColor(style.foreground_color.unwrap_or(Color::Reset)))?;
+	}
+	if current.background_color != style.background_color {
+		out.queue(SetBackgroundColor(style.background_color.unwrap_or(Color::Reset)))?;
+	}
+	if current.underline_color != style.underline_color {
+		out.queue(SetUnderlineColor(style.underline_color.unwrap_or(Color::Reset)))?;
+	}
+	let added = (current.attributes ^ style.attributes) & style.attributes;
+	if !added.is_empty() {
+		out.queue(SetAttributes(added))?;
+	}
+	*pen = Some(style);
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use crossterm::style::Stylize;
+
+	use super::*;
+
+	/// Renders the buffer, returning what was written.
+	fn render(renderer: &mut Renderer, buffer: &Buffer) -> String {
+		let mut out = vec![];
+		renderer.render(&mut out, buffer.clone()).unwrap();
+		String::from_utf8(out).unwrap()
+	}
+
+	#[test]
+	fn print() {
+		let mut buffer = Buffer::new((6, 2));
+		let text = format!("{}e\u{301}\x1b[2Kb{}", "a".bold(), "世界".on_blue());
+		assert_eq!(buffer.print((0, 0), &text), 7);
+		assert_eq!(buffer.line(0), "ae\u{301}b世 ");
+		assert!(buffer.cell(0, 0).style.attributes.has(Attribute::Bold));
+		assert_eq!(buffer.cell(1, 0).style, ContentStyle::new());
+		assert_eq!(buffer.cell(3, 0).style.background_color, Some(Color::Blue));
+		assert_eq!(buffer.cell(4, 0).content, "");
+		// Overwriting half of a wide character erases it
+		buffer.print((4, 0), "c");
+		assert_eq!(buffer.line(0), "ae\u{301}b c ");
+		buffer.print((4, 1), "d");
+		assert_eq!(buffer.line(1), "    d ");
+	}
+
+	#[test]
+	fn straddling() {
+		use crate::widgets::text::Text;
+
+		let mut buffer = Buffer::new((6, 1));
+		buffer.print((0, 0), "a世界b");
+		// The wide characters straddling the edges of a widget are erased
+		buffer.draw((2, 0), &Text::new(["xy".into()], 2));
+		assert_eq!(buffer.line(0), "a xy b");
+		// A wide character of a widget straddling its edge isn't drawn
+		buffer.draw((0, 0), &Text::new(["ab世".into()], 3));
+		assert_eq!(buffer.line(0), "ab y b");
+		assert_eq!(width("ab世"), 4);
+	}
+
+	#[test]
+	fn diff() {
+		let mut renderer = Renderer::new();
+		let mut buffer = Buffer::new((8, 2));
+		buffer.print((0, 0), "ab  cd");
+		// Drawn entirely, but the blank cells
+		assert_eq!(render(&mut renderer, &buffer), "\x1b[2J\x1b[1;1H\x1b[0mab\x1b[2Ccd");
+		assert_eq!(render(&mut renderer, &buffer), "");
+		// Only the changed cells, moving forward on the same row
+		buffer.print((1, 0), "x");
+		buffer.print((5, 0), "y");
+		buffer.print((0, 1), "z");
+		let out = render(&mut renderer, &buffer);
+		assert_eq!(out, "\x1b[1;2H\x1b[0mx\x1b[3Cy\x1b[2;1Hz");
+		// A new size or an invalidation draws everything again
+		renderer.invalidate();
+		assert!(render(&mut renderer, &buffer).starts_with("\x1b[2J"));
+		assert!(render(&mut renderer, &Buffer::new((3, 3))).starts_with("\x1b[2J"));
+	}
+
+	#[test]
+	fn styles() {
+		let mut renderer = Renderer::new();
+		let mut buffer = Buffer::new((4, 1));
+		buffer.print((0, 0), &format!("{}{}{}", "a".red(), "b".red().bold(), "c".bold()));
+		let out = render(&mut renderer, &buffer);
+		assert_eq!(out, "\x1b[2J\x1b[1;1H\x1b[0m\x1b[38;5;9ma\x1b[1mb\x1b[39mc\x1b[0m");
+		// The style is only reset when it isn't the default one
+		let out = render(&mut renderer, &Buffer::new((4, 1)));
+		assert_eq!(out, "\x1b[1;1H\x1b[0m   ");
+		// Attributes are added without resetting, removed by resetting
+		let mut out = vec![];
+		let mut pen = Some(ContentStyle::new().red());
+		set_style(&mut out, &mut pen, ContentStyle::new().red().bold()).unwrap();
+		set_style(&mut out, &mut pen, ContentStyle::new().blue().bold()).unwrap();
+		set_style(&mut out, &mut pen, ContentStyle::new().blue()).unwrap();
+		set_style(&mut out, &mut pen, ContentStyle::new().blue()).unwrap();
+		let out = String::from_utf8(out).unwrap();
+		assert_eq!(out, "\x1b[1m\x1b[38;5;12m\x1b[0m\x1b[38;5;12m");
+	}
+
+	#[test]
+	fn wide_characters() {
+		let mut renderer = Renderer::new();
+		let mut buffer = Buffer::new((4, 1));
+		buffer.print((0, 0), "世界");
+		render(&mut renderer, &buffer);
+		// The cursor moves by two after a wide character
+		buffer.print((0, 0), "界世");
+		assert_eq!(render(&mut renderer, &buffer), "\x1b[1;1H\x1b[0m界世");
+		buffer.print((1, 0), "ab");
+		assert_eq!(render(&mut renderer, &buffer), "\x1b[1;1H\x1b[0m ab ");
+	}
+}
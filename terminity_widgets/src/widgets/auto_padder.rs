@@ -2,6 +2,9 @@
 use crossterm::event::MouseEvent;
 
 use crate as terminity_widgets;
+use crate::render::{self, Cell};
+use crate::widgets::checked;
+use crate::widgets::layout::LayoutChild;
 use crate::MouseEventWidget;
 use crate::ResizableWisget;
 use crate::Widget;
@@ -20,6 +23,7 @@ use std::ops::DerefMut;
 /// you might forget to deref the `AutoPadder`.
 ///
 /// if there's an odd quantity of padding to add, the extra space will be on the right and/or bottom.
+/// If the child is larger than the target size, it is clipped on the right and/or bottom.
 ///
 /// TODO: Describe behavior on traits.
 ///
@@ -58,21 +62,35 @@ impl<W: Widget> Widget for AutoPadder<W> {
 			for _ in 0..left_padding {
 				f.write_char(' ')?;
 			}
-			self.0.displ_line(f, line - top_padding)?;
+			if content_size.0 <= total_size.0 {
+				checked::write_line(f, &self.0, line - top_padding)?;
+			} else {
+				// The child is wider than the target size: it is clipped
+				let (content, _) = checked::line(&self.0, line - top_padding);
+				f.write_str(&render::clip(&content, total_size.0))?;
+			}
 			for _ in (left_padding + content_size.0)..(total_size.0) {
 				f.write_char(' ')?;
 			}
 		}
 		Ok(())
 	}
+	fn draw_line(&self, cells: &mut [Cell], line: usize) {
+		let content_size = self.0.size();
+		let top_padding = (self.1 .1.saturating_sub(content_size.1)) / 2;
+		let left_padding = (self.1 .0.saturating_sub(content_size.0)) / 2;
+		if (top_padding..top_padding + content_size.1).contains(&line) {
+			render::draw_at(cells, left_padding, &self.0, line - top_padding);
+		}
+	}
 	fn size(&self) -> (usize, usize) {
 		self.1
 	}
 }
 
-impl<W: MouseEventWidget> MouseEventWidget for AutoPadder<W> {
-	type MouseHandlingResult = Option<W::MouseHandlingResult>;
-	fn mouse_event(&mut self, event: MouseEvent) -> Self::MouseHandlingResult {
+impl<W: Widget> AutoPadder<W> {
+	/// The given mouse event with coordinates relative to the child, if it is on the child.
+	fn child_event(&self, event: MouseEvent) -> Option<MouseEvent> {
 		let MouseEvent { column, row, kind, modifiers } = event;
 		let mut column = column as i32;
 		let mut row = row as i32;
@@ -85,20 +103,36 @@ impl<W: MouseEventWidget> MouseEventWidget for AutoPadder<W> {
 
 		if column >= 0
 			&& (column as usize) < content_size.0
-			&& row >= 0 && (row as usize) < content_size.1
+			&& row >= 0
+			&& (row as usize) < content_size.1
 		{
-			Some(self.0.mouse_event(MouseEvent {
-				kind,
-				column: column as u16,
-				row: row as u16,
-				modifiers,
-			}))
+			Some(MouseEvent { kind, column: column as u16, row: row as u16, modifiers })
 		} else {
 			None
 		}
 	}
 }
 
+impl<W: MouseEventWidget> MouseEventWidget for AutoPadder<W> {
+	type MouseHandlingResult = Option<W::MouseHandlingResult>;
+	fn mouse_event(&mut self, event: MouseEvent) -> Self::MouseHandlingResult {
+		let event = self.child_event(event)?;
+		Some(self.0.mouse_event(event))
+	}
+}
+
+/// In a layout, the padder takes the size of its area and gives the mouse events on the child to
+/// it.
+impl<R, W: LayoutChild<R>> LayoutChild<R> for AutoPadder<W> {
+	fn fit(&mut self, size: (usize, usize)) {
+		self.resize(size)
+	}
+	fn mouse(&mut self, event: MouseEvent) -> Option<R> {
+		let event = self.child_event(event)?;
+		self.0.mouse(event)
+	}
+}
+
 impl<W: Widget> ResizableWisget for AutoPadder<W> {
 	fn resize(&mut self, size: (usize, usize)) {
 		self.1 = size;
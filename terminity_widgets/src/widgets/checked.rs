@@ -0,0 +1,443 @@
+//! Defines the [Checked] widget, and the checks of the size contract of the widgets.
+//!
+//! A widget has to display lines of exactly its width, and only the lines within its height (see
+//! [`Widget`]). A widget breaking this contract corrupts the layout of whatever contains it, so in
+//! debug builds, the widgets of this crate check their children against it, panicking with the name
+//! of the faulty widget. The lines displayed are measured as they are written, while the lines
+//! [drawn](crate::render::draw_at) are only checked to be within the widget, the cells given to
+//! draw them never being wider than it.
+//!
+//! In release builds, these checks are skipped. A widget that can't be trusted can instead be
+//! wrapped in a [`Checked`] widget, always checking it and following a [`Policy`] when it fails.
+
+use std::any::type_name;
+use std::fmt::{self, Display, Formatter, Write};
+use std::ops::{Deref, DerefMut};
+
+use crossterm::event::MouseEvent;
+use unicode_width::UnicodeWidthChar;
+
+use crate as terminity_widgets;
+use crate::render::{self, Cell};
+use crate::{MouseEventWidget, ResizableWisget, Widget, WidgetDisplay};
+
+/// A breach of the size contract of a widget, found by [`check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+	/// A line out of the widget was asked.
+	OutOfBounds {
+		/// The line asked.
+		line: usize,
+		/// The height of the widget.
+		height: usize,
+	},
+	/// A line of the widget isn't as wide as the widget.
+	Width {
+		/// The line displayed.
+		line: usize,
+		/// The width of the line, in terminal cells.
+		width: usize,
+		/// The width of the widget.
+		expected: usize,
+	},
+}
+
+impl Display for Violation {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::OutOfBounds { line, height } => {
+				write!(f, "line {} asked, but its height is {}", line, height)
+			}
+			Self::Width { line, width, expected } => {
+				write!(f, "line {} is {} cells wide, but its width is {}", line, width, expected)
+			}
+		}
+	}
+}
+
+impl std::error::Error for Violation {}
+
+/// Displays the given line of a widget, and checks it against the size of the widget.
+///
+/// ```
+/// use terminity_widgets::widgets::checked::{check, Violation};
+/// use terminity_widgets::widgets::text::Text;
+///
+/// let text = Text::new(["Hello".into()], 5);
+/// assert_eq!(check(&text, 0), Ok("Hello".to_owned()));
+/// assert_eq!(check(&text, 1), Err(Violation::OutOfBounds { line: 1, height: 1 }));
+/// ```
+pub fn check<W: Widget + ?Sized>(widget: &W, line: usize) -> Result<String, Violation> {
+	let (content, width) = displayed(widget, line)?;
+	match widget.size().0 {
+		expected if width == expected => Ok(content),
+		expected => Err(Violation::Width { line, width, expected }),
+	}
+}
+
+/// Displays the given line of a widget if it is within the widget, with its width.
+fn displayed<W: Widget + ?Sized>(widget: &W, line: usize) -> Result<(String, usize), Violation> {
+	let height = widget.size().1;
+	if line >= height {
+		return Err(Violation::OutOfBounds { line, height });
+	}
+	let content = render::Formatted(widget, line).to_string();
+	let width = render::width(&content);
+	Ok((content, width))
+}
+
+/// Panics, reporting the given violation of the size contract by the given widget.
+fn violated(widget: &str, violation: Violation) -> ! {
+	panic!("The widget `{}` broke its size contract: {}", widget, violation)
+}
+
+/// Checks that the given line is within the widget, in debug builds.
+fn debug_check_line<W: Widget + ?Sized>(widget: &W, line: usize) {
+	let height = widget.size().1;
+	if cfg!(debug_assertions) && line >= height {
+		violated(type_name::<W>(), Violation::OutOfBounds { line, height })
+	}
+}
+
+/// Checks that the given line of the widget was displayed with the given width, in debug builds.
+fn debug_check_width<W: Widget + ?Sized>(widget: &W, line: usize, width: usize) {
+	let expected = widget.size().0;
+	if cfg!(debug_assertions) && width != expected {
+		violated(type_name::<W>(), Violation::Width { line, width, expected })
+	}
+}
+
+/// Writes the given line of a child widget. Debug builds check it, measuring it as it is written.
+pub(crate) fn write_line<W: Widget + ?Sized>(
+	f: &mut Formatter<'_>,
+	widget: &W,
+	line: usize,
+) -> fmt::Result {
+	if !cfg!(debug_assertions) {
+		return widget.displ_line(f, line);
+	}
+	debug_check_line(widget, line);
+	let mut measure = Measure { f, width: 0, escape: Escape::None };
+	write!(measure, "{}", render::Formatted(widget, line))?;
+	debug_check_width(widget, line, measure.width);
+	Ok(())
+}
+
+/// Displays the given line of a child widget, with its width. Debug builds check it.
+pub(crate) fn line<W: Widget + ?Sized>(widget: &W, line: usize) -> (String, usize) {
+	debug_check_line(widget, line);
+	let content = render::Formatted(widget, line).to_string();
+	let width = render::width(&content);
+	debug_check_width(widget, line, width);
+	(content, width)
+}
+
+/// Checks, in debug builds, that the given line is within the widget and that the cells it is
+/// about to be drawn into aren't wider than the widget.
+pub(crate) fn debug_check_draw<W: Widget + ?Sized>(widget: &W, line: usize, cells: &[Cell]) {
+	debug_check_line(widget, line);
+	debug_assert!(
+		cells.len() <= widget.size().0,
+		"The widget `{}` is given {} cells to draw, but its width is {}",
+		type_name::<W>(),
+		cells.len(),
+		widget.size().0
+	);
+}
+
+/// Where [`Measure`] is in an escape sequence.
+enum Escape {
+	None,
+	Started,
+	Csi,
+}
+
+/// Forwards what is written to a formatter, measuring its width in terminal cells as
+/// [`render::width`] does.
+struct Measure<'a, 'b> {
+	f: &'a mut Formatter<'b>,
+	width: usize,
+	escape: Escape,
+}
+
+impl fmt::Write for Measure<'_, '_> {
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		for c in s.chars() {
+			self.escape = match (&self.escape, c) {
+				(Escape::None, '\x1b') => Escape::Started,
+				(Escape::None, c) => {
+					self.width += if c.is_control() { 0 } else { c.width().unwrap_or(0) };
+					Escape::None
+				}
+				(Escape::Started, '[') => Escape::Csi,
+				(Escape::Csi, '\x40'..='\x7e') | (Escape::Started, _) => Escape::None,
+				(Escape::Csi, _) => Escape::Csi,
+			};
+		}
+		self.f.write_str(s)
+	}
+}
+
+/// What a [`Checked`] widget does when its content breaks the size contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+	/// Panics, reporting the violation and the name of the widget.
+	Panic,
+	/// Fits the content to the size of the widget: the lines too long are truncated, the ones too
+	/// short padded with spaces, and the lines out of the widget are blank.
+	Fit,
+}
+
+impl Default for Policy {
+	/// Panics in debug builds, and fits in release builds.
+	fn default() -> Self {
+		if cfg!(debug_assertions) {
+			Self::Panic
+		} else {
+			Self::Fit
+		}
+	}
+}
+
+/// A widget checking that its content respects the size contract of widgets, following a [`Policy`]
+/// when it doesn't.
+///
+/// The content is reported by its type name, unless it is [named](Checked::named). This struct
+/// aims to be used transparently by implementing [`Deref`] and [`DerefMut`], and the other traits
+/// are given to the content.
+///
+/// ```
+/// use format::lazy_format;
+/// use terminity_widgets::widgets::checked::{Checked, Policy};
+/// use terminity_widgets::Widget;
+/// # use std::fmt::Formatter;
+///
+/// // A widget lying about its size
+/// struct Liar;
+/// impl Widget for Liar {
+/// 	fn displ_line(&self, f: &mut Formatter<'_>, line: usize) -> std::fmt::Result {
+/// 		f.write_str(["Hello world!", "Hi"][line])
+/// 	}
+/// 	fn size(&self) -> (usize, usize) {
+/// 		(5, 2)
+/// 	}
+/// }
+///
+/// let checked = Checked::new(Liar).named("greeting").with_policy(Policy::Fit);
+/// assert_eq!(lazy_format!(|f| checked.displ_line(f, 0)).to_string(), "Hello");
+/// assert_eq!(lazy_format!(|f| checked.displ_line(f, 1)).to_string(), "Hi   ");
+/// assert_eq!(lazy_format!(|f| checked.displ_line(f, 2)).to_string(), "     ");
+/// ```
+///
+/// ```should_panic
+/// use terminity_widgets::render::Cell;
+/// use terminity_widgets::widgets::checked::{Checked, Policy};
+/// use terminity_widgets::widgets::text::Text;
+/// use terminity_widgets::Widget;
+///
+/// let checked = Checked::new(Text::new(["Hello".into()], 5)).named("greeting");
+/// let checked = checked.with_policy(Policy::Panic);
+/// let mut cells = vec![Cell::default(); 5];
+/// // Panics: "The widget `greeting` broke its size contract: line 1 asked, but its height is 1"
+/// checked.draw_line(&mut cells, 1);
+/// ```
+#[derive(WidgetDisplay)]
+pub struct Checked<W: Widget> {
+	widget: W,
+	name: &'static str,
+	policy: Policy,
+}
+
+impl<W: Widget> Checked<W> {
+	/// Checks the given widget, with the default policy.
+	pub fn new(widget: W) -> Self {
+		Self { widget, name: type_name::<W>(), policy: Policy::default() }
+	}
+
+	/// Reports the widget by the given name rather than by its type name.
+	pub fn named(mut self, name: &'static str) -> Self {
+		self.name = name;
+		self
+	}
+
+	/// Follows the given policy when the widget breaks the size contract.
+	pub fn with_policy(mut self, policy: Policy) -> Self {
+		self.policy = policy;
+		self
+	}
+
+	/// Gives back the checked widget.
+	pub fn into_inner(self) -> W {
+		self.widget
+	}
+
+	/// The given line of the widget if it breaks the size contract, fitted according to the policy.
+	fn fitted(&self, line: usize) -> Option<String> {
+		let width = self.widget.size().0;
+		let violation = match displayed(&self.widget, line) {
+			Ok((_, actual)) if actual == width => return None,
+			Ok((content, actual)) => {
+				if self.policy == Policy::Fit {
+					return Some(
+						render::clip(&content, width) + &" ".repeat(width.saturating_sub(actual)),
+					);
+				}
+				Violation::Width { line, width: actual, expected: width }
+			}
+			Err(violation) => violation,
+		};
+		match self.policy {
+			Policy::Panic => violated(self.name, violation),
+			Policy::Fit => Some(" ".repeat(width)),
+		}
+	}
+}
+
+impl<W: Widget> Widget for Checked<W> {
+	fn displ_line(&self, f: &mut Formatter<'_>, line: usize) -> fmt::Result {
+		match self.fitted(line) {
+			Some(content) => f.write_str(&content),
+			None => self.widget.displ_line(f, line),
+		}
+	}
+	fn draw_line(&self, cells: &mut [Cell], line: usize) {
+		match self.fitted(line) {
+			Some(content) => {
+				render::print_ansi(cells, 0, &content);
+			}
+			None => self.widget.draw_line(cells, line),
+		}
+	}
+	fn size(&self) -> (usize, usize) {
+		self.widget.size()
+	}
+}
+
+impl<W: MouseEventWidget> MouseEventWidget for Checked<W> {
+	type MouseHandlingResult = W::MouseHandlingResult;
+	fn mouse_event(&mut self, event: MouseEvent) -> Self::MouseHandlingResult {
+		self.widget.mouse_event(event)
+	}
+}
+
+impl<W: Widget + ResizableWisget> ResizableWisget for Checked<W> {
+	fn resize(&mut self, size: (usize, usize)) {
+		self.widget.resize(size)
+	}
+}
+
+impl<W: Widget> Deref for Checked<W> {
+	type Target = W;
+	fn deref(&self) -> &Self::Target {
+		&self.widget
+	}
+}
+
+impl<W: Widget> DerefMut for Checked<W> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.widget
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use format::lazy_format;
+
+	use super::*;
+	use crate::render::Buffer;
+	use crate::widgets::auto_padder::AutoPadder;
+	use crate::widgets::text::Text;
+
+	/// A widget displaying the given lines whatever its size.
+	struct Lines(Vec<&'static str>, (usize, usize));
+
+	impl Widget for Lines {
+		fn displ_line(&self, f: &mut Formatter<'_>, line: usize) -> fmt::Result {
+			f.write_str(self.0[line])
+		}
+		fn size(&self) -> (usize, usize) {
+			self.1
+		}
+	}
+
+	#[test]
+	fn violations() {
+		let lines = Lines(vec!["abc", "a世界", "\x1b[1mab\x1b[0m"], (4, 3));
+		assert_eq!(check(&lines, 0), Err(Violation::Width { line: 0, width: 3, expected: 4 }));
+		assert_eq!(check(&lines, 1), Err(Violation::Width { line: 1, width: 5, expected: 4 }));
+		assert_eq!(check(&lines, 2), Err(Violation::Width { line: 2, width: 2, expected: 4 }));
+		assert_eq!(check(&lines, 3), Err(Violation::OutOfBounds { line: 3, height: 3 }));
+		let lines = Lines(vec!["ab世"], (4, 1));
+		assert_eq!(check(&lines, 0), Ok("ab世".to_owned()));
+	}
+
+	#[test]
+	fn fit() {
+		let checked =
+			Checked::new(Lines(vec!["abc", "a世界", "abcd"], (4, 3))).with_policy(Policy::Fit);
+		let line = |i| lazy_format!(|f| checked.displ_line(f, i)).to_string();
+		assert_eq!([line(0), line(1), line(2), line(3)], ["abc ", "a世 ", "abcd", "    "]);
+		let mut buffer = Buffer::new((5, 3));
+		buffer.draw((0, 0), &checked);
+		assert_eq!(buffer.line(1), "a世  ");
+	}
+
+	#[test]
+	#[should_panic(expected = "The widget `lines` broke its size contract: line 0 is 3 cells wide")]
+	fn panic() {
+		let checked = Checked::new(Lines(vec!["abc"], (4, 1))).named("lines");
+		let _ = checked.with_policy(Policy::Panic).to_string();
+	}
+
+	#[test]
+	#[cfg(debug_assertions)]
+	#[should_panic(expected = "`terminity_widgets::widgets::checked::tests::Lines` broke its size")]
+	fn debug_checks() {
+		let padder = AutoPadder(Lines(vec!["abc"], (4, 1)), (6, 3));
+		let _ = padder.to_string();
+	}
+
+	#[test]
+	fn styled_lines() {
+		let lines = Lines(vec!["\x1b[31mab\x1b[39m世"], (4, 1));
+		let padder = AutoPadder(lines, (6, 1));
+		assert_eq!(render::width(&padder.to_string()), 6);
+	}
+
+	/// A widget drawing its cells directly, its display being left out.
+	struct Drawn;
+
+	impl Widget for Drawn {
+		fn displ_line(&self, _f: &mut Formatter<'_>, _line: usize) -> fmt::Result {
+			Ok(())
+		}
+		fn draw_line(&self, cells: &mut [Cell], _line: usize) {
+			render::print(cells, 0, "ab", Default::default());
+		}
+		fn size(&self) -> (usize, usize) {
+			(2, 1)
+		}
+	}
+
+	#[test]
+	fn drawn_lines() {
+		// Only the drawing is checked when drawing
+		let padder = AutoPadder(Drawn, (4, 1));
+		assert_eq!(Buffer::from_widget(&padder).line(0), " ab ");
+	}
+
+	#[test]
+	#[cfg(debug_assertions)]
+	#[should_panic(expected = "`terminity_widgets::widgets::checked::tests::Drawn` broke its size")]
+	fn drawn_out_of_bounds() {
+		let mut cells = vec![Cell::default(); 2];
+		render::draw_at(&mut cells, 0, &Drawn, 1);
+	}
+
+	#[test]
+	fn clipped_padder() {
+		let padder = AutoPadder(Text::new(["Hello".into(), "World".into()], 5), (3, 1));
+		assert_eq!(padder.to_string(), "Hel");
+		assert_eq!(Buffer::from_widget(&padder).line(0), "Hel");
+	}
+}
@@ -1,15 +1,24 @@
 //! Defines the [Frame] widget.
 use crate as terminity_widgets; // For the macros
+use crate::render::{self, Cell};
+use crate::widgets::checked;
+use crate::MouseEventWidget;
 use crate::Widget;
 use crate::WidgetDisplay;
 
+use crossterm::event::MouseEvent;
+
 use std::collections::HashMap;
 use std::fmt::Formatter;
 use std::hash::Hash;
 use std::ops::Deref;
 use std::ops::DerefMut;
 use std::ops::Index;
-use unicode_segmentation::UnicodeSegmentation;
+use std::ops::IndexMut;
+
+/// The content of a frame: a list of lines, each made of a prefix and a list of
+/// `((widget index, widget line), suffix)`. See [`Frame::new`].
+pub type FrameContent<Idx> = Vec<(String, Vec<((Idx, usize), String)>)>;
 
 /// A Frame[^coll] is a widget containing a collection of widgets that it is able to display.
 ///
@@ -20,6 +29,9 @@ use unicode_segmentation::UnicodeSegmentation;
 /// Once a collection is framed, it can actually still be used as the original collection since
 /// frames implements [`Deref`] and [`DerefMut`].
 ///
+/// When the children handle mouse events, the frame gives each event to the child under the
+/// pointer, with coordinates relative to that child (see [`MouseEventWidget`]).
+///
 /// The generics arguments of Frame are:
 /// * `Idx`: the type of the indexes to access the collection's content
 /// * `Item`: the type of the children widgets
@@ -55,7 +67,7 @@ use unicode_segmentation::UnicodeSegmentation;
 /// macro, allowing more flexibility in the types of the frame's children.
 #[derive(WidgetDisplay)]
 pub struct Frame<Idx: ToOwned<Owned = Idx>, Item: Widget, Coll: Index<Idx, Output = Item>> {
-	content: Vec<(String, Vec<((Idx, usize), String)>)>,
+	content: FrameContent<Idx>,
 	widgets: Coll,
 	size: (usize, usize),
 	positions: HashMap<Idx, (usize, usize)>,
@@ -80,31 +92,27 @@ impl<
 	///
 	/// If this function seems too complicated to use, consider using the [`frame!`](crate::frame)
 	/// macro, that actually just compiles to an assignation and a `Frame::new` invocation.
-	pub fn new(content: Vec<(String, Vec<((Idx, usize), String)>)>, widgets: Coll) -> Self {
-		macro_rules! str_len {
-			($str:expr) => {
-				String::from_utf8(strip_ansi_escapes::strip($str).unwrap())
-					.unwrap()
-					.graphemes(true)
-					.count()
-			};
-		}
-
-		let size = (content[0].0.len(), content.len());
+	pub fn new(content: FrameContent<Idx>, widgets: Coll) -> Self {
+		let mut width = 0;
 		let mut positions = HashMap::new();
 		// TODO: cleanup/adapt. This is code from when I tried to implement un-resizable widgets.
 		for (y_pos, (prefix, line)) in content.iter().enumerate() {
 			let mut x_pos = 0;
 			let mut previous = prefix;
 			for (item, suffix) in line {
-				x_pos += str_len!(previous);
+				x_pos += render::width(previous);
 				if item.1 == 0 {
 					positions.insert(item.0.clone(), (x_pos, y_pos));
 				}
 				x_pos += widgets[item.0.to_owned()].size().0;
 				previous = suffix;
 			}
+			// The width is the one of the first line
+			if y_pos == 0 {
+				width = x_pos + render::width(previous);
+			}
 		}
+		let size = (width, content.len());
 		Self { content, widgets, size, positions }
 	}
 }
@@ -122,10 +130,7 @@ impl<
 	pub fn find_x(&self, line: usize, element_index: Idx) -> Option<usize> {
 		macro_rules! str_len {
 			($str:expr) => {
-				String::from_utf8(strip_ansi_escapes::strip($str).unwrap())
-					.unwrap()
-					.graphemes(true)
-					.count()
+				render::width($str)
 			};
 		}
 		self.content[line].1.iter().enumerate().find(|(_, (el, _))| el.0 == element_index).map(
@@ -155,16 +160,48 @@ impl<Idx: ToOwned<Owned = Idx>, Item: Widget, Coll: Index<Idx, Output = Item>> W
 	for Frame<Idx, Item, Coll>
 {
 	fn displ_line(&self, f: &mut Formatter<'_>, line: usize) -> std::fmt::Result {
-		let (begin, widgets_line) = &self.content[line as usize];
-		f.write_str(&begin)?;
+		let (begin, widgets_line) = &self.content[line];
+		f.write_str(begin)?;
 		for ((widget_i, w_line), postfix) in widgets_line {
-			self.widgets[widget_i.to_owned()].displ_line(f, *w_line)?;
-			f.write_str(&postfix)?;
+			checked::write_line(f, &self.widgets[widget_i.to_owned()], *w_line)?;
+			f.write_str(postfix)?;
 		}
 		Ok(())
 	}
+	fn draw_line(&self, cells: &mut [Cell], line: usize) {
+		let (begin, widgets_line) = &self.content[line];
+		let mut x = render::print_ansi(cells, 0, begin);
+		for ((widget_i, w_line), postfix) in widgets_line {
+			x = render::draw_at(cells, x, &self.widgets[widget_i.to_owned()], *w_line);
+			x = render::print_ansi(cells, x, postfix);
+		}
+	}
 	fn size(&self) -> (usize, usize) {
-		self.size.clone()
+		self.size
+	}
+}
+
+impl<
+		Idx: ToOwned<Owned = Idx> + Eq + Hash,
+		Item: MouseEventWidget,
+		Coll: Index<Idx, Output = Item> + IndexMut<Idx>,
+	> MouseEventWidget for Frame<Idx, Item, Coll>
+{
+	/// The index of the child under the pointer and the result of its handling of the event, or
+	/// `None` if the event isn't on any child.
+	type MouseHandlingResult = Option<(Idx, Item::MouseHandlingResult)>;
+	/// Gives the event to the child under the pointer, found using the position of its first
+	/// occurrence in the frame. The children are thus assumed to be displayed as rectangles.
+	fn mouse_event(&mut self, event: MouseEvent) -> Self::MouseHandlingResult {
+		let (column, row) = (event.column as usize, event.row as usize);
+		let (index, &(x, y)) = self.positions.iter().find(|(index, &(x, y))| {
+			let (width, height) = self.widgets[(*index).to_owned()].size();
+			(x..x + width).contains(&column) && (y..y + height).contains(&row)
+		})?;
+		let index = index.to_owned();
+		let event = MouseEvent { column: (column - x) as u16, row: (row - y) as u16, ..event };
+		let result = self.widgets[index.to_owned()].mouse_event(event);
+		Some((index, result))
 	}
 }
 
@@ -198,10 +235,10 @@ mod tests {
 
 	impl Widget for Img {
 		fn displ_line(&self, f: &mut Formatter<'_>, line: usize) -> std::fmt::Result {
-			f.write_str(&self.content[line as usize])
+			f.write_str(&self.content[line])
 		}
 		fn size(&self) -> (usize, usize) {
-			self.size.clone()
+			self.size
 		}
 	}
 
@@ -284,6 +321,37 @@ mod tests {
 		)
 	}
 
+	impl MouseEventWidget for Img {
+		type MouseHandlingResult = (u16, u16);
+		fn mouse_event(&mut self, event: MouseEvent) -> Self::MouseHandlingResult {
+			(event.column, event.row)
+		}
+	}
+
+	#[test]
+	fn mouse_routing() {
+		use crossterm::event::{KeyModifiers, MouseEventKind};
+
+		let img = |size| Img { content: vec![], size };
+		let mut frame0 = frame!(
+			['a': img((2, 2)), 'b': img((3, 1))]
+			"+-------+"
+			"| aa bbb|"
+			"| aa    |"
+			"+-------+"
+		);
+		let mut click = |column, row| {
+			let kind = MouseEventKind::Down(crossterm::event::MouseButton::Left);
+			frame0.mouse_event(MouseEvent { kind, column, row, modifiers: KeyModifiers::NONE })
+		};
+		assert_eq!(click(2, 1), Some((0, (0, 0))));
+		assert_eq!(click(3, 2), Some((0, (1, 1))));
+		assert_eq!(click(7, 1), Some((1, (2, 0))));
+		for (column, row) in [(0, 0), (4, 1), (5, 2), (8, 1), (2, 3), (20, 20)] {
+			assert_eq!(click(column, row), None, "{}, {}", column, row);
+		}
+	}
+
 	#[derive(StructFrame)]
 	#[layout {
 		"*-------------*",
@@ -325,4 +393,70 @@ mod tests {
 		assert_eq!("| lmnopqrstuv |", &lazy_format!(|f| s_frame.displ_line(f, 5)).to_string());
 		assert_eq!("*-------------*", &lazy_format!(|f| s_frame.displ_line(f, 6)).to_string());
 	}
+	#[test]
+	fn draw() {
+		use crate::render::Buffer;
+		use crossterm::style::{Color, Stylize};
+
+		let img = |content: [&str; 2]| Img {
+			content: content.iter().map(|line| format!("{}", line.red())).collect(),
+			size: (content[0].len(), 2),
+		};
+		let frame0 = frame!(
+			['a': img(["abc", "def"]), 'b': img(["ghi", "jkl"])]
+			"|aaa bbb|"
+			"|aaa bbb|"
+		);
+		let buffer = Buffer::from_widget(&frame0);
+		assert_eq!(buffer.line(0), "|abc ghi|");
+		assert_eq!(buffer.line(1), "|def jkl|");
+		assert_eq!(buffer.cell(4, 0).style, Default::default());
+		assert_eq!(buffer.cell(5, 0).style.foreground_color, Some(Color::Red));
+
+		let s_frame = SideBySide { left: img(["ab", "cd"]), right: img(["123", "456"]) };
+		let mut buffer = Buffer::new((8, 3));
+		buffer.draw((4, 1), &s_frame);
+		assert_eq!(buffer.line(1), "    ab 1");
+		assert_eq!(buffer.line(2), "    cd 4");
+		assert_eq!(buffer.cell(7, 2).style.foreground_color, Some(Color::Red));
+	}
+
+	#[test]
+	fn wide_characters() {
+		use crate::render::Buffer;
+
+		let img = |content: &str| Img { content: vec![content.to_owned()], size: (4, 1) };
+		let frame0 = frame!(
+			['a': img("世界"), 'b': img("ab  ")]
+			"│囲aaaa│bbbb│"
+		);
+		assert_eq!(frame0.size(), (13, 1));
+		assert_eq!(frame0.find_pos(&1), Some((8, 0)));
+		let buffer = Buffer::from_widget(&frame0);
+		assert_eq!(buffer.line(0), "│囲世界│ab  │");
+		assert_eq!(buffer.cell(8, 0).content, "a");
+	}
+
+	#[derive(StructFrame)]
+	#[layout {
+		"ll rrr",
+		"ll rrr",
+	}]
+	struct SideBySide {
+		#[layout(name = 'l')]
+		left: Img,
+		#[layout(name = 'r')]
+		right: Img,
+	}
+
+	#[test]
+	fn struct_frame_up_to_the_edge() {
+		let s_frame = SideBySide {
+			left: Img { content: vec!["ab".into(), "cd".into()], size: (2, 2) },
+			right: Img { content: vec!["123".into(), "456".into()], size: (3, 2) },
+		};
+		assert_eq!(s_frame.size(), (6, 2));
+		assert_eq!("ab 123", &lazy_format!(|f| s_frame.displ_line(f, 0)).to_string());
+		assert_eq!("cd 456", &lazy_format!(|f| s_frame.displ_line(f, 1)).to_string());
+	}
 }
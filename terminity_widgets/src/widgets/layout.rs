@@ -0,0 +1,731 @@
+//! Defines the [HStack], [VStack] and [Grid] widgets, laying out their children along
+//! [Constraint]s.
+//!
+//! Unlike a [Frame](super::frame::Frame), whose layout is drawn once and for all, these widgets
+//! compute the area of each child from their own size: when resized (through
+//! [`ResizableWisget`]), they share the new size between their children according to the
+//! constraints, resize the children to their area and route the mouse events to the child under
+//! the pointer. A game can thus reflow its whole UI when the terminal is resized.
+//!
+//! The children are resized along with their container, so they have to implement
+//! [`ResizableWisget`] for the container to be resized. Other widgets can be wrapped in an
+//! [`AutoPadder`](super::auto_padder::AutoPadder), centring them in their area. A child smaller
+//! than its area is padded with blanks, a larger one is clipped.
+//!
+//! Children of different types can be laid out together by boxing them as [`LayoutChild`] trait
+//! objects, each of them telling what the mouse events on it mean. Widgets of a fixed size can
+//! be such children, and so can the layout widgets of boxed children, to nest them.
+//!
+//! ```
+//! use format::lazy_format;
+//! use terminity_widgets::widgets::auto_padder::AutoPadder;
+//! use terminity_widgets::widgets::layout::{Constraint, HStack};
+//! use terminity_widgets::widgets::text::Text;
+//! use terminity_widgets::{ResizableWisget, Widget};
+//!
+//! let text = |s: &str| AutoPadder(Text::new([s.into()], s.len()), (0, 0));
+//! let mut stack = HStack::new()
+//! 	.with(Constraint::Fixed(4), text("<"))
+//! 	.with(Constraint::Flex(1), text("Hi!"))
+//! 	.with(Constraint::Fixed(4), text(">"));
+//! stack.resize((13, 1));
+//! assert_eq!(lazy_format!(|f| stack.displ_line(f, 0)).to_string(), " <   Hi!  >  ");
+//! stack.resize((11, 1));
+//! assert_eq!(lazy_format!(|f| stack.displ_line(f, 0)).to_string(), " <  Hi! >  ");
+//! ```
+//!
+//! ```
+//! use crossterm::event::{KeyModifiers, MouseEvent, MouseEventKind};
+//! use terminity_widgets::render::Buffer;
+//! use terminity_widgets::widgets::layout::{Constraint::*, LayoutChild, VStack};
+//! use terminity_widgets::widgets::text::Text;
+//! use terminity_widgets::{MouseEventWidget, ResizableWisget, Widget};
+//! # use std::fmt::Formatter;
+//!
+//! // A button, telling which one it is when clicked
+//! struct Button(u8);
+//! impl Widget for Button {
+//! 	fn size(&self) -> (usize, usize) { (3, 1) }
+//! 	// ...
+//! 	# fn displ_line(&self, f: &mut Formatter<'_>, _: usize) -> std::fmt::Result {
+//! 		# write!(f, "[{}]", self.0)
+//! 	# }
+//! }
+//! impl LayoutChild<u8> for Button {
+//! 	fn mouse(&mut self, _: MouseEvent) -> Option<u8> {
+//! 		Some(self.0)
+//! 	}
+//! }
+//!
+//! let mut stack = VStack::<Box<dyn LayoutChild<u8>>>::new()
+//! 	.with(Fixed(1), Box::new(Text::new(["Title".into()], 5)))
+//! 	.with(Fixed(1), Box::new(Button(1)));
+//! stack.resize((5, 2));
+//! assert_eq!(Buffer::from_widget(&stack).line(1), "[1]  ");
+//! let click = |row| MouseEvent {
+//! 	kind: MouseEventKind::Moved,
+//! 	column: 1,
+//! 	row,
+//! 	modifiers: KeyModifiers::NONE,
+//! };
+//! assert_eq!(stack.mouse_event(click(1)), Some((1, Some(1))));
+//! assert_eq!(stack.mouse_event(click(0)), Some((0, None)));
+//! stack[0].downcast_mut::<Text<1>>().unwrap()[0] = "Bye".into();
+//! ```
+
+use std::any::Any;
+use std::fmt::Formatter;
+use std::ops::{Deref, DerefMut, Range};
+
+use crossterm::event::MouseEvent;
+
+use crate as terminity_widgets;
+use crate::render::{self, Cell};
+use crate::widgets::checked;
+use crate::{MouseEventWidget, ResizableWisget, Widget, WidgetDisplay};
+
+/// How much room a child of a layout widget takes along an axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Constraint {
+	/// Exactly the given number of cells.
+	Fixed(usize),
+	/// The given percentage of the size of the container.
+	Percentage(u16),
+	/// At least the given number of cells, growing as `Flex(1)` with the room left.
+	Min(usize),
+	/// At most the given number of cells, growing as `Flex(1)` with the room left.
+	Max(usize),
+	/// A share of the room left by the other constraints, proportional to the given weight.
+	Flex(u16),
+}
+
+impl Constraint {
+	/// The weight with which the constraint grows with the room left.
+	fn weight(self) -> usize {
+		match self {
+			Self::Flex(weight) => weight as usize,
+			Self::Min(_) | Self::Max(_) => 1,
+			Self::Fixed(_) | Self::Percentage(_) => 0,
+		}
+	}
+}
+
+/// Splits the given number of cells along the given constraints, giving the range of cells of
+/// each of them.
+///
+/// The fixed, percentage and minimal sizes are given first. If they don't fit, the last
+/// constraints are the ones shrunk. The room left is then shared between the flexible, minimal and
+/// maximal constraints according to their weight, the maximal ones not growing past their maximum.
+/// The cells left by the rounding go to the first growing constraints. If nothing grows, the last
+/// cells are left out.
+///
+/// ```
+/// use terminity_widgets::widgets::layout::{split, Constraint::*};
+///
+/// assert_eq!(split(10, &[Fixed(2), Flex(1), Max(2)]), [0..2, 2..8, 8..10]);
+/// assert_eq!(split(10, &[Percentage(50), Flex(1), Flex(2)]), [0..5, 5..7, 7..10]);
+/// assert_eq!(split(3, &[Fixed(2), Min(2)]), [0..2, 2..3]);
+/// ```
+pub fn split(total: usize, constraints: &[Constraint]) -> Vec<Range<usize>> {
+	use Constraint::*;
+	let mut sizes: Vec<usize> = constraints
+		.iter()
+		.map(|constraint| match *constraint {
+			Fixed(n) | Min(n) => n,
+			Percentage(p) => total * p as usize / 100,
+			Max(_) | Flex(_) => 0,
+		})
+		.collect();
+	let mut left = total;
+	for size in &mut sizes {
+		*size = (*size).min(left);
+		left -= *size;
+	}
+	let mut growing: Vec<_> =
+		(0..constraints.len()).filter(|&i| constraints[i].weight() > 0).collect();
+	while left > 0 && !growing.is_empty() {
+		let weights: usize = growing.iter().map(|&i| constraints[i].weight()).sum();
+		let share = |i: usize| left * constraints[i].weight() / weights;
+		// The maximal constraints that would grow past their maximum are capped first
+		let capped = growing.iter().position(|&i| match constraints[i] {
+			Max(max) => sizes[i] + share(i) >= max,
+			_ => false,
+		});
+		if let Some(position) = capped {
+			let i = growing.remove(position);
+			if let Max(max) = constraints[i] {
+				let grown = max.saturating_sub(sizes[i]).min(left);
+				sizes[i] += grown;
+				left -= grown;
+			}
+			continue;
+		}
+		let shares: Vec<_> = growing.iter().map(|&i| share(i)).collect();
+		for (&i, share) in growing.iter().zip(shares) {
+			sizes[i] += share;
+			left -= share;
+		}
+		for &i in growing.iter().take(left) {
+			sizes[i] += 1;
+		}
+		left = 0;
+	}
+	let mut start = 0;
+	sizes
+		.into_iter()
+		.map(|size| {
+			start += size;
+			start - size..start
+		})
+		.collect()
+}
+
+/// A child of a layout widget, that can be boxed for a layout widget to have children of different
+/// types: the layout widgets of `Box<dyn LayoutChild<R>>` resize their children through
+/// [`fit`](Self::fit) and give the `R` their child under the pointer gives through
+/// [`mouse`](Self::mouse). Boxed children can be [downcast](Self::downcast_mut) back to their type.
+///
+/// Both methods do nothing by default, for widgets of a fixed size ignoring the mouse. The layout
+/// widgets of boxed children are children themselves.
+pub trait LayoutChild<R = ()>: Widget + Any {
+	/// Resizes the child to the size of its area. Widgets of a fixed size keep theirs, being padded
+	/// or clipped by the layout widget.
+	fn fit(&mut self, _size: (usize, usize)) {}
+	/// Handles a mouse event on the child, with coordinates relative to it. Gives `None` if the
+	/// event means nothing to the child.
+	fn mouse(&mut self, _event: MouseEvent) -> Option<R> {
+		None
+	}
+}
+
+impl<R: 'static> dyn LayoutChild<R> {
+	/// The child as its actual type, if it is of the given type.
+	pub fn downcast_ref<W: LayoutChild<R>>(&self) -> Option<&W> {
+		(self as &dyn Any).downcast_ref()
+	}
+
+	/// The child as its actual type, if it is of the given type.
+	pub fn downcast_mut<W: LayoutChild<R>>(&mut self) -> Option<&mut W> {
+		(self as &mut dyn Any).downcast_mut()
+	}
+}
+
+impl<R: 'static> Widget for Box<dyn LayoutChild<R>> {
+	fn displ_line(&self, f: &mut Formatter<'_>, line: usize) -> std::fmt::Result {
+		(**self).displ_line(f, line)
+	}
+	fn draw_line(&self, cells: &mut [Cell], line: usize) {
+		(**self).draw_line(cells, line)
+	}
+	fn size(&self) -> (usize, usize) {
+		(**self).size()
+	}
+}
+
+impl<R: 'static> MouseEventWidget for Box<dyn LayoutChild<R>> {
+	type MouseHandlingResult = Option<R>;
+	fn mouse_event(&mut self, event: MouseEvent) -> Self::MouseHandlingResult {
+		(**self).mouse(event)
+	}
+}
+
+impl<R: 'static> ResizableWisget for Box<dyn LayoutChild<R>> {
+	fn resize(&mut self, size: (usize, usize)) {
+		(**self).fit(size)
+	}
+}
+
+/// A widget laying out its children in a grid, the columns and the rows being sized along
+/// [`Constraint`]s. See the [module-level documentation](self) for more details.
+///
+/// The children are given in reading order: the first row from left to right, then the second one,
+/// and so on. The grid can have less children than cells, the last ones being left blank.
+///
+/// Once built, the grid can be used as the slice of its children, since it implements [`Deref`]
+/// and [`DerefMut`]. Mouse events are given to the child under the pointer, with coordinates
+/// relative to that child, as [`Frame`](super::frame::Frame) does.
+///
+/// ```
+/// use terminity_widgets::widgets::auto_padder::AutoPadder;
+/// use terminity_widgets::widgets::layout::{Constraint::*, Grid};
+/// use terminity_widgets::widgets::text::Text;
+/// use terminity_widgets::render::Buffer;
+/// use terminity_widgets::ResizableWisget;
+///
+/// let cell = |s: &str| AutoPadder(Text::new([s.into()], 1), (0, 0));
+/// let mut grid = Grid::new(vec![Fixed(3), Flex(1)], vec![Flex(1), Fixed(1)])
+/// 	.with(cell("a"))
+/// 	.with(cell("b"))
+/// 	.with(cell("c"));
+/// grid.resize((6, 4));
+/// assert_eq!(grid.area(1), Some((3..6, 0..3)));
+/// let buffer = Buffer::from_widget(&grid);
+/// assert_eq!(buffer.line(1), " a  b ");
+/// assert_eq!(buffer.line(3), " c    ");
+/// ```
+#[derive(WidgetDisplay)]
+pub struct Grid<W: Widget> {
+	columns: Vec<Constraint>,
+	rows: Vec<Constraint>,
+	children: Vec<W>,
+	size: (usize, usize),
+	/// The computed cells of each column
+	xs: Vec<Range<usize>>,
+	/// The computed cells of each row
+	ys: Vec<Range<usize>>,
+}
+
+impl<W: Widget> Grid<W> {
+	/// Creates an empty grid, with the given constraints on its columns and rows. It is of size
+	/// `(0, 0)` until it is [resized](ResizableWisget::resize).
+	pub fn new(columns: Vec<Constraint>, rows: Vec<Constraint>) -> Self {
+		let mut grid =
+			Self { columns, rows, children: vec![], size: (0, 0), xs: vec![], ys: vec![] };
+		grid.layout();
+		grid
+	}
+
+	/// Adds a child in the next cell of the grid. If every cell already has a child, the given one
+	/// is dropped (and debug builds panic).
+	pub fn with(mut self, child: W) -> Self {
+		let full = self.children.len() >= self.columns.len() * self.rows.len();
+		debug_assert!(!full, "The grid is full");
+		if !full {
+			self.children.push(child);
+		}
+		self
+	}
+
+	/// The columns and rows of cells of the area of the child of the given index, if there is such
+	/// a child. The child may be smaller than its area.
+	pub fn area(&self, index: usize) -> Option<(Range<usize>, Range<usize>)> {
+		let columns = self.columns.len();
+		if index >= self.children.len() || columns == 0 {
+			return None;
+		}
+		Some((self.xs[index % columns].clone(), self.ys[index / columns].clone()))
+	}
+
+	/// Computes the cells of each column and row.
+	fn layout(&mut self) {
+		self.xs = split(self.size.0, &self.columns);
+		self.ys = split(self.size.1, &self.rows);
+	}
+
+	/// The children on the given line, with their area and the line of theirs that is on it.
+	fn line_children(&self, line: usize) -> impl Iterator<Item = (&W, Range<usize>, usize)> {
+		let row = self.ys.iter().position(|ys| ys.contains(&line));
+		let columns = self.columns.len();
+		row.into_iter().flat_map(move |row| {
+			let line = line - self.ys[row].start;
+			self.children
+				.iter()
+				.skip(row * columns)
+				.take(columns)
+				.zip(&self.xs)
+				.filter(move |(child, _)| line < child.size().1)
+				.map(move |(child, xs)| (child, xs.clone(), line))
+		})
+	}
+}
+
+impl<W: Widget> Widget for Grid<W> {
+	fn displ_line(&self, f: &mut Formatter<'_>, line: usize) -> std::fmt::Result {
+		let mut x = 0;
+		for (child, xs, child_line) in self.line_children(line) {
+			let width = child.size().0.min(xs.len());
+			let (content, content_width) = checked::line(child, child_line);
+			write!(f, "{:1$}", "", xs.start - x)?;
+			if content_width > width {
+				f.write_str(&render::clip(&content, width))?;
+			} else {
+				// A child line narrower than the child is padded, not to shift the next children
+				f.write_str(&content)?;
+				write!(f, "{:1$}", "", width - content_width)?;
+			}
+			x = xs.start + width;
+		}
+		write!(f, "{:1$}", "", self.size.0 - x)
+	}
+	fn draw_line(&self, cells: &mut [Cell], line: usize) {
+		for (child, xs, child_line) in self.line_children(line) {
+			let end = (xs.start + child.size().0.min(xs.len())).min(cells.len());
+			if xs.start < end {
+				checked::debug_check_draw(child, child_line, &cells[xs.start..end]);
+				child.draw_line(&mut cells[xs.start..end], child_line);
+			}
+		}
+	}
+	fn size(&self) -> (usize, usize) {
+		self.size
+	}
+}
+
+impl<W: MouseEventWidget> MouseEventWidget for Grid<W> {
+	/// The index of the child under the pointer and the result of its handling of the event, or
+	/// `None` if the event isn't on any child.
+	type MouseHandlingResult = Option<(usize, W::MouseHandlingResult)>;
+	fn mouse_event(&mut self, event: MouseEvent) -> Self::MouseHandlingResult {
+		let (column, row) = (event.column as usize, event.row as usize);
+		let index = (0..self.children.len()).find(|&i| {
+			let (xs, ys) = self.area(i).expect("Child out of the grid");
+			let (width, height) = self.children[i].size();
+			(xs.start..xs.start + width.min(xs.len())).contains(&column)
+				&& (ys.start..ys.start + height.min(ys.len())).contains(&row)
+		})?;
+		let (xs, ys) = self.area(index).expect("Child out of the grid");
+		let event = MouseEvent {
+			column: (column - xs.start) as u16,
+			row: (row - ys.start) as u16,
+			..event
+		};
+		Some((index, self.children[index].mouse_event(event)))
+	}
+}
+
+impl<W: Widget + ResizableWisget> ResizableWisget for Grid<W> {
+	/// Lays out the children in the given size, resizing them to their area.
+	fn resize(&mut self, size: (usize, usize)) {
+		self.size = size;
+		self.layout();
+		for i in 0..self.children.len() {
+			let (xs, ys) = self.area(i).expect("Child out of the grid");
+			self.children[i].resize((xs.len(), ys.len()));
+		}
+	}
+}
+
+impl<R: 'static> LayoutChild<R> for Grid<Box<dyn LayoutChild<R>>> {
+	fn fit(&mut self, size: (usize, usize)) {
+		self.resize(size)
+	}
+	fn mouse(&mut self, event: MouseEvent) -> Option<R> {
+		self.mouse_event(event)?.1
+	}
+}
+
+impl<W: Widget> Deref for Grid<W> {
+	type Target = [W];
+	fn deref(&self) -> &Self::Target {
+		&self.children
+	}
+}
+
+impl<W: Widget> DerefMut for Grid<W> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.children
+	}
+}
+
+/// Implements the traits of the grid on a stack wrapping it.
+macro_rules! stack_impls {
+	($stack:ident) => {
+		impl<W: Widget> Widget for $stack<W> {
+			fn displ_line(&self, f: &mut Formatter<'_>, line: usize) -> std::fmt::Result {
+				self.0.displ_line(f, line)
+			}
+			fn draw_line(&self, cells: &mut [Cell], line: usize) {
+				self.0.draw_line(cells, line)
+			}
+			fn size(&self) -> (usize, usize) {
+				self.0.size()
+			}
+		}
+
+		impl<W: MouseEventWidget> MouseEventWidget for $stack<W> {
+			/// The index of the child under the pointer and the result of its handling of the
+			/// event, or `None` if the event isn't on any child.
+			type MouseHandlingResult = Option<(usize, W::MouseHandlingResult)>;
+			fn mouse_event(&mut self, event: MouseEvent) -> Self::MouseHandlingResult {
+				self.0.mouse_event(event)
+			}
+		}
+
+		impl<W: Widget + ResizableWisget> ResizableWisget for $stack<W> {
+			fn resize(&mut self, size: (usize, usize)) {
+				self.0.resize(size)
+			}
+		}
+
+		impl<R: 'static> LayoutChild<R> for $stack<Box<dyn LayoutChild<R>>> {
+			fn fit(&mut self, size: (usize, usize)) {
+				self.0.fit(size)
+			}
+			fn mouse(&mut self, event: MouseEvent) -> Option<R> {
+				self.0.mouse(event)
+			}
+		}
+
+		impl<W: Widget> Default for $stack<W> {
+			fn default() -> Self {
+				Self::new()
+			}
+		}
+
+		impl<W: Widget> Deref for $stack<W> {
+			type Target = Grid<W>;
+			fn deref(&self) -> &Self::Target {
+				&self.0
+			}
+		}
+
+		impl<W: Widget> DerefMut for $stack<W> {
+			fn deref_mut(&mut self) -> &mut Self::Target {
+				&mut self.0
+			}
+		}
+	};
+}
+
+/// A widget laying out its children from left to right, each taking the whole height of the stack.
+/// It is a [`Grid`] of a single row, see the [module-level documentation](self) for an example.
+#[derive(WidgetDisplay)]
+pub struct HStack<W: Widget>(Grid<W>);
+
+impl<W: Widget> HStack<W> {
+	/// Creates an empty stack, of size `(0, 0)` until it is [resized](ResizableWisget::resize).
+	pub fn new() -> Self {
+		Self(Grid::new(vec![], vec![Constraint::Flex(1)]))
+	}
+
+	/// Adds a child on the right, its width following the given constraint.
+	pub fn with(mut self, constraint: Constraint, child: W) -> Self {
+		self.0.columns.push(constraint);
+		self.0.children.push(child);
+		self.0.layout();
+		self
+	}
+}
+
+stack_impls!(HStack);
+
+/// A widget laying out its children from top to bottom, each taking the whole width of the stack.
+/// It is a [`Grid`] of a single column.
+///
+/// ```
+/// use terminity_widgets::widgets::auto_padder::AutoPadder;
+/// use terminity_widgets::widgets::layout::{Constraint, VStack};
+/// use terminity_widgets::widgets::text::Text;
+/// use terminity_widgets::render::Buffer;
+/// use terminity_widgets::ResizableWisget;
+///
+/// let text = |s: &str| AutoPadder(Text::new([s.into()], s.len()), (0, 0));
+/// let mut stack = VStack::new()
+/// 	.with(Constraint::Min(1), text("Title"))
+/// 	.with(Constraint::Percentage(50), text("Body"));
+/// stack.resize((7, 4));
+/// assert_eq!(stack[1].1, (7, 2));
+/// let buffer = Buffer::from_widget(&stack);
+/// assert_eq!(buffer.line(0), " Title ");
+/// assert_eq!(buffer.line(1), "       ");
+/// assert_eq!(buffer.line(2), " Body  ");
+/// ```
+#[derive(WidgetDisplay)]
+pub struct VStack<W: Widget>(Grid<W>);
+
+impl<W: Widget> VStack<W> {
+	/// Creates an empty stack, of size `(0, 0)` until it is [resized](ResizableWisget::resize).
+	pub fn new() -> Self {
+		Self(Grid::new(vec![Constraint::Flex(1)], vec![]))
+	}
+
+	/// Adds a child at the bottom, its height following the given constraint.
+	pub fn with(mut self, constraint: Constraint, child: W) -> Self {
+		self.0.rows.push(constraint);
+		self.0.children.push(child);
+		self.0.layout();
+		self
+	}
+}
+
+stack_impls!(VStack);
+
+#[cfg(test)]
+mod tests {
+	use format::lazy_format;
+	use Constraint::*;
+
+	use super::*;
+	use crate::render::Buffer;
+
+	/// A widget filled with its character, giving back where it's clicked.
+	struct Fill(char, (usize, usize));
+
+	impl Widget for Fill {
+		fn displ_line(&self, f: &mut Formatter<'_>, _line: usize) -> std::fmt::Result {
+			write!(f, "{}", self.0.to_string().repeat(self.1 .0))
+		}
+		fn size(&self) -> (usize, usize) {
+			self.1
+		}
+	}
+
+	impl MouseEventWidget for Fill {
+		type MouseHandlingResult = (u16, u16);
+		fn mouse_event(&mut self, event: MouseEvent) -> Self::MouseHandlingResult {
+			(event.column, event.row)
+		}
+	}
+
+	impl ResizableWisget for Fill {
+		fn resize(&mut self, size: (usize, usize)) {
+			self.1 = size;
+		}
+	}
+
+	impl LayoutChild<(u16, u16)> for Fill {
+		fn fit(&mut self, size: (usize, usize)) {
+			self.resize(size)
+		}
+		fn mouse(&mut self, event: MouseEvent) -> Option<(u16, u16)> {
+			Some(self.mouse_event(event))
+		}
+	}
+
+	/// A widget of a fixed size, ignoring the mouse.
+	struct Label(&'static str);
+
+	impl Widget for Label {
+		fn displ_line(&self, f: &mut Formatter<'_>, _line: usize) -> std::fmt::Result {
+			f.write_str(self.0)
+		}
+		fn size(&self) -> (usize, usize) {
+			(self.0.len(), 1)
+		}
+	}
+
+	impl LayoutChild<(u16, u16)> for Label {}
+
+	fn click(column: u16, row: u16) -> MouseEvent {
+		use crossterm::event::{KeyModifiers, MouseButton, MouseEventKind};
+		MouseEvent {
+			kind: MouseEventKind::Down(MouseButton::Left),
+			column,
+			row,
+			modifiers: KeyModifiers::NONE,
+		}
+	}
+
+	#[test]
+	fn splitting() {
+		assert_eq!(split(5, &[]), []);
+		assert_eq!(split(5, &[Fixed(2), Fixed(1)]), [0..2, 2..3]);
+		assert_eq!(split(5, &[Fixed(4), Percentage(50), Min(3)]), [0..4, 4..5, 5..5]);
+		assert_eq!(split(10, &[Max(3), Flex(1), Max(8)]), [0..3, 3..7, 7..10]);
+		assert_eq!(split(10, &[Min(4), Flex(0), Flex(2)]), [0..6, 6..6, 6..10]);
+		assert_eq!(split(7, &[Flex(1), Flex(1), Flex(1)]), [0..3, 3..5, 5..7]);
+		assert_eq!(split(100, &[Percentage(150), Fixed(2)]), [0..100, 100..100]);
+	}
+
+	#[test]
+	fn resize() {
+		let mut stack =
+			HStack::new().with(Fixed(2), Fill('a', (0, 0))).with(Flex(1), Fill('b', (0, 0)));
+		assert_eq!(stack.size(), (0, 0));
+		stack.resize((5, 2));
+		assert_eq!((stack[0].1, stack[1].1), ((2, 2), (3, 2)));
+		assert_eq!(lazy_format!(|f| stack.displ_line(f, 1)).to_string(), "aabbb");
+		stack.resize((1, 1));
+		assert_eq!((stack[0].1, stack[1].1), ((1, 1), (0, 1)));
+		assert_eq!(lazy_format!(|f| stack.displ_line(f, 0)).to_string(), "a");
+	}
+
+	#[test]
+	fn sizes_mismatch() {
+		let mut grid = Grid::new(vec![Fixed(3), Fixed(3)], vec![Fixed(2)])
+			.with(Fill('a', (1, 1)))
+			.with(Fill('b', (5, 3)));
+		grid.size = (7, 3);
+		grid.layout();
+		for (line, expected) in ["a  bbb ", "   bbb ", "       "].into_iter().enumerate() {
+			assert_eq!(lazy_format!(|f| grid.displ_line(f, line)).to_string(), expected);
+		}
+		let buffer = Buffer::from_widget(&grid);
+		for (line, expected) in ["a  bbb ", "   bbb ", "       "].into_iter().enumerate() {
+			assert_eq!(buffer.line(line), expected);
+		}
+	}
+
+	#[test]
+	#[cfg(not(debug_assertions))]
+	fn short_lines() {
+		struct Short;
+		impl Widget for Short {
+			fn displ_line(&self, f: &mut Formatter<'_>, _line: usize) -> std::fmt::Result {
+				f.write_str("a")
+			}
+			fn size(&self) -> (usize, usize) {
+				(3, 1)
+			}
+		}
+		let mut grid = Grid::new(vec![Fixed(4), Fixed(2)], vec![Fixed(1)]).with(Short).with(Short);
+		grid.size = (7, 1);
+		grid.layout();
+		assert_eq!(lazy_format!(|f| grid.displ_line(f, 0)).to_string(), "a   a  ");
+	}
+
+	#[test]
+	fn full_grid() {
+		let grid = Grid::<Fill>::new(vec![], vec![Flex(1)]);
+		assert_eq!(grid.area(0), None);
+		let grid = Grid::new(vec![Flex(1)], vec![Flex(1)]).with(Fill('a', (0, 0)));
+		let extra =
+			std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| grid.with(Fill('b', (0, 0)))));
+		// Debug builds panic, release ones drop the child
+		assert_eq!(extra.is_err(), cfg!(debug_assertions));
+		if let Ok(mut grid) = extra {
+			assert_eq!(grid.len(), 1);
+			grid.resize((2, 1));
+			assert_eq!(grid.area(1), None);
+			assert_eq!(lazy_format!(|f| grid.displ_line(f, 0)).to_string(), "aa");
+		}
+	}
+
+	#[test]
+	fn mouse() {
+		let mut stack =
+			VStack::new().with(Fixed(1), HStack::new().with(Flex(1), Fill('a', (0, 0)))).with(
+				Flex(1),
+				HStack::new().with(Fixed(2), Fill('b', (0, 0))).with(Flex(1), Fill('c', (0, 0))),
+			);
+		stack.resize((6, 3));
+		let buffer = Buffer::from_widget(&stack);
+		assert_eq!(
+			[buffer.line(0), buffer.line(1), buffer.line(2)],
+			["aaaaaa", "bbcccc", "bbcccc"]
+		);
+		assert_eq!(stack.mouse_event(click(4, 0)), Some((0, Some((0, (4, 0))))));
+		assert_eq!(stack.mouse_event(click(1, 2)), Some((1, Some((0, (1, 1))))));
+		assert_eq!(stack.mouse_event(click(3, 1)), Some((1, Some((1, (1, 0))))));
+		assert_eq!(stack.mouse_event(click(6, 1)), None);
+	}
+
+	#[test]
+	fn boxed_children() {
+		type Child = Box<dyn LayoutChild<(u16, u16)>>;
+		let row = HStack::<Child>::new()
+			.with(Fixed(3), Box::new(Label("ab")))
+			.with(Flex(1), Box::new(Fill('c', (0, 0))));
+		let mut stack = VStack::<Child>::new()
+			.with(Fixed(1), Box::new(Label("title")))
+			.with(Flex(1), Box::new(row));
+		stack.resize((6, 3));
+		let buffer = Buffer::from_widget(&stack);
+		assert_eq!(
+			[buffer.line(0), buffer.line(1), buffer.line(2)],
+			["title ", "ab ccc", "   ccc"]
+		);
+		assert_eq!(stack.mouse_event(click(4, 2)), Some((1, Some((1, 1)))));
+		// On the label, and between the label and the filling
+		assert_eq!(stack.mouse_event(click(1, 1)), Some((1, None)));
+		assert_eq!(stack.mouse_event(click(2, 1)), Some((1, None)));
+		assert_eq!(stack.mouse_event(click(1, 0)), Some((0, None)));
+		assert_eq!(stack.mouse_event(click(6, 1)), None);
+
+		let row = stack[1].downcast_mut::<HStack<Child>>().unwrap();
+		assert!(row[0].downcast_ref::<Fill>().is_none());
+		assert_eq!(row[1].downcast_ref::<Fill>().unwrap().1, (3, 2));
+		row[0].downcast_mut::<Label>().unwrap().0 = "xy";
+		assert_eq!(Buffer::from_widget(&stack).line(1), "xy ccc");
+	}
+}
@@ -2,5 +2,7 @@
 //! [crate's root](crate). Check their respective docs for more detail.
 
 pub mod auto_padder;
+pub mod checked;
 pub mod frame;
+pub mod layout;
 pub mod text;
@@ -1,13 +1,13 @@
 //! Defines the [Text] widget.
 use crate as terminity_widgets;
+use crate::render::{self, Cell};
+use crate::widgets::layout::LayoutChild;
 use crate::Widget;
 use crate::WidgetDisplay;
-use std::fmt;
 use std::fmt::Formatter;
 use std::fmt::Write;
 use std::ops::Index;
 use std::ops::IndexMut;
-use unicode_segmentation::UnicodeSegmentation;
 
 /// Enum used in [`Text`]. Indicates where the text aligns
 pub enum Align {
@@ -57,22 +57,25 @@ impl<const H: usize> Text<H> {
 			s.clear();
 		}
 	}
-}
 
-impl<const H: usize> Widget for Text<H> {
-	fn displ_line(&self, f: &mut Formatter<'_>, line: usize) -> std::fmt::Result {
-		let width = String::from_utf8(
-			strip_ansi_escapes::strip(&self.content[line]).map_err(|_| fmt::Error)?,
-		)
-		.unwrap()
-		.graphemes(true)
-		.count();
+	/// The padding to add on the left and on the right of a line of the given width.
+	fn padding(&self, width: usize) -> (usize, usize) {
 		let diff = self.width.saturating_sub(width);
-		let (left, right) = match self.align {
+		match self.align {
 			Align::Left => (0, diff),
 			Align::Right => (diff, 0),
 			Align::Center => (diff / 2, diff - (diff / 2)),
-		};
+		}
+	}
+}
+
+impl<const H: usize> Widget for Text<H> {
+	fn displ_line(&self, f: &mut Formatter<'_>, line: usize) -> std::fmt::Result {
+		let width = render::width(&self.content[line]);
+		if width > self.width {
+			return f.write_str(&render::clip(&self.content[line], self.width));
+		}
+		let (left, right) = self.padding(width);
 		for _ in 0..left {
 			f.write_char(self.padding)?;
 		}
@@ -82,11 +85,41 @@ impl<const H: usize> Widget for Text<H> {
 		}
 		Ok(())
 	}
+	/// Draws the line without measuring it beforehand: it is drawn on the left, and then moved
+	/// according to its alignment.
+	///
+	/// ```
+	/// use crossterm::style::{Color, Stylize};
+	/// use terminity_widgets::render::Buffer;
+	/// use terminity_widgets::widgets::text::Text;
+	///
+	/// let text = Text::centered([format!("{}!", "Hi".green())], 6);
+	/// let buffer = Buffer::from_widget(&text);
+	/// assert_eq!(buffer.line(0), " Hi!  ");
+	/// assert_eq!(buffer.cell(1, 0).style.foreground_color, Some(Color::Green));
+	/// ```
+	fn draw_line(&self, cells: &mut [Cell], line: usize) {
+		let mut row = vec![Cell::default(); self.width];
+		let width = render::print_ansi(&mut row, 0, &self.content[line]);
+		let (left, right) = self.padding(width);
+		row.rotate_right(left);
+		if self.padding != ' ' {
+			for i in (0..left).chain(self.width - right..self.width) {
+				row[i].content = self.padding.to_string();
+			}
+		}
+		for (cell, drawn) in cells.iter_mut().zip(row) {
+			*cell = drawn;
+		}
+	}
 	fn size(&self) -> (usize, usize) {
 		(self.width, H)
 	}
 }
 
+/// A text keeps its size in a layout, and ignores the mouse.
+impl<R, const H: usize> LayoutChild<R> for Text<H> {}
+
 impl<const H: usize> Index<usize> for Text<H> {
 	type Output = String;
 	fn index(&self, i: usize) -> &Self::Output {
@@ -98,3 +131,26 @@ impl<const H: usize> IndexMut<usize> for Text<H> {
 		&mut self.content[i]
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use format::lazy_format;
+
+	use super::*;
+	use crate::render::Buffer;
+
+	#[test]
+	fn wide_characters() {
+		let text = Text::centered(["世界".into(), "ab世界".into(), "👍".into()], 5);
+		let line = |i| lazy_format!(|f| text.displ_line(f, i)).to_string();
+		assert_eq!(line(0), "世界 ");
+		// Too long: cut, the straddling half replaced by a space
+		assert_eq!(line(1), "ab世 ");
+		assert_eq!(line(2), " 👍  ");
+
+		let buffer = Buffer::from_widget(&text);
+		assert_eq!(buffer.line(0), "世界 ");
+		assert_eq!(buffer.line(1), "ab世 ");
+		assert_eq!(buffer.line(2), " 👍  ");
+	}
+}
@@ -7,6 +7,8 @@ use std::{
 	collections::HashMap,
 	iter,
 };
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
 use syn::{
 	braced, bracketed,
 	parse::{Parse, ParseStream},
@@ -29,7 +31,7 @@ struct FrameWidgetIndex {
 	col: Token![:],
 	index: Expr,
 }*/
-#[allow(dead_code)]
+#[allow(dead_code, clippy::large_enum_variant)]
 enum FrameWidgetIndex {
 	Simple {
 		name: LitChar,
@@ -46,7 +48,7 @@ enum FrameWidgetIndex {
 	},
 }
 
-#[allow(dead_code)]
+#[allow(dead_code, clippy::large_enum_variant)]
 enum FrameColl {
 	Array {
 		brackets: Bracket,
@@ -60,6 +62,7 @@ enum FrameColl {
 		values: Punctuated<FrameWidgetIndex, Token![,]>,
 	},
 }
+#[allow(clippy::large_enum_variant)]
 pub enum IndexKind<'a> {
 	Expr(Expr),
 	Range((usize, Option<LitInt>, &'a Cell<usize>)),
@@ -136,7 +139,7 @@ impl Parse for FrameWidgetIndex {
 	fn parse(input: ParseStream) -> syn::Result<Self> {
 		if input.peek(Ident) {
 			let repeat: Ident = input.parse()?;
-			if repeat.to_string() != "repeat" {
+			if repeat != "repeat" {
 				return Err(syn::Error::new(repeat.span(), "Expected a char or 'repreat'"));
 			}
 			let name = input.parse()?;
@@ -170,14 +173,14 @@ impl Parse for FrameMacro {
 			let value = input.parse()?;
 			let size = if input.peek(Ident) {
 				let of: Ident = input.parse()?;
-				if of.to_string() != "of" {
+				if of != "of" {
 					return Err(syn::Error::new(
 						of.span(),
 						"Expecting a size description ('of size<w, h>') or the token '=>'",
 					));
 				}
 				let size: Ident = input.parse()?;
-				if size.to_string() != "size" {
+				if size != "size" {
 					return Err(syn::Error::new(
 						size.span(),
 						"Expecting a size description ('of size<w, h>')",
@@ -208,35 +211,41 @@ impl Parse for FrameMacro {
 	}
 }
 
-pub fn parse_frame_lines<'a>(
+/// The known sizes of the widgets, associated to the char representing them in the layout.
+pub type WidgetsSizes<'a> = Vec<(char, &'a RefCell<Option<(usize, usize)>>)>;
+
+/// A parsed frame line: a prefix, then a list of `(((widget char, uid), widget line), suffix)`.
+pub type FrameLine = (LitStr, Vec<(((char, usize), usize), LitStr)>);
+
+pub fn parse_frame_lines(
 	frame_width: &mut Option<usize>,
 	errors: &mut Vec<Diagnostic>,
 	content: &[LitStr],
-	widgets_names: Vec<(char, &RefCell<Option<(usize, usize)>>)>,
-) -> Vec<(LitStr, Vec<(((char, usize), usize), LitStr)>)> {
+	widgets_names: WidgetsSizes,
+) -> Vec<FrameLine> {
 	let mut res_lines = vec![];
 
 	let mut next_uid = 0;
-	// char to match, uid, start, end, current line
+	// char to match, uid, start and end columns, current line
 	let mut last_indexes: Vec<(char, usize, (usize, usize), usize)> = vec![];
 	for line in content {
 		let line_content = line.value();
-		// Check full width of frame
+		// Check full width of frame, in terminal cells
 		match frame_width {
 			Some(w) => {
-				if *w != line_content.chars().count() {
+				if *w != line_content.width() {
 					errors.push(Diagnostic::spanned(
 						line.span(),
 						Level::Error,
 						format!(
 							"Frame width is inconsistant. Got {} earlier, found {} here.",
 							w,
-							line_content.chars().count()
+							line_content.width()
 						),
 					));
 				}
-			} // TODO: check width in graphemes
-			None => *frame_width = Some(line_content.chars().count()),
+			}
+			None => *frame_width = Some(line_content.width()),
 		}
 		// Get the list of index of widget on current line
 		let mut indexes = widgets_names
@@ -256,10 +265,12 @@ pub fn parse_frame_lines<'a>(
 					let mut end_index = match *widgets_size.borrow_mut() {
 						// If current widget has pre-defined width, then use it
 						Some((w, _)) => {
-							let widget_section = &substr[start_index..(start_index + w)];
-							if widget_section.chars().count() != w
-								|| !widget_section.chars().all(|c| c == name)
-							{
+							// The number of placeholders covering the widget's width
+							let name_width = name.width().unwrap_or(0).max(1);
+							let count = w / name_width;
+							let placeholders =
+								substr[start_index..].chars().take_while(|c| *c == name).count();
+							if placeholders < count || count * name_width != w {
 								// Create error and skip until a char is different
 								errors.push(Diagnostic::spanned(
 									line.span(),
@@ -276,12 +287,12 @@ pub fn parse_frame_lines<'a>(
 								substr = &line_content[substr_index..];
 								continue;
 							} else {
-								w
+								count * name.len_utf8()
 							}
 						}
 						None => substr[start_index..]
 							.find(|ch| ch != name)
-							.unwrap_or(line_content.len()),
+							.unwrap_or(substr.len() - start_index),
 					};
 					// Relatively to substr
 					end_index += start_index;
@@ -289,18 +300,21 @@ pub fn parse_frame_lines<'a>(
 					// Relatively to line_content
 					start_index += substr_index;
 					end_index += substr_index;
+					// In columns, to compare with the other lines
+					let start_column = line_content[..start_index].width();
+					let end_column = line_content[..end_index].width();
 
 					// Get details of this same span on the line above
 					let above = last_indexes
 						.binary_search_by(|(_, _, (start, end), _)| {
-							if (start_index..end_index).contains(start)
-								|| (start_index..end_index).contains(end)
-								|| (*start..*end).contains(&start_index)
-								|| (*start..*end).contains(&end_index)
+							if (start_column..end_column).contains(start)
+								|| (start_column..end_column).contains(end)
+								|| (*start..*end).contains(&start_column)
+								|| (*start..*end).contains(&end_column)
 							{
 								Ordering::Equal
 							} else {
-								start.cmp(&start_index)
+								start.cmp(&start_column)
 							}
 						})
 						.ok()
@@ -313,9 +327,9 @@ pub fn parse_frame_lines<'a>(
 						}
 						Some((last_name, last_uid, (start, end), y_index)) => {
 							// if there's some kind of issue, then we start a brand new display
-							if widgets_size.borrow().map_or(false, |(_, h)| h == y_index + 1)
-								|| *last_name != name || *start != start_index
-								|| *end != end_index
+							if widgets_size.borrow().is_some_and(|(_, h)| h == y_index + 1)
+								|| *last_name != name || *start != start_column
+								|| *end != end_column
 							{
 								next_uid += 1;
 								(0, next_uid - 1)
@@ -328,25 +342,26 @@ pub fn parse_frame_lines<'a>(
 					// Prepare next iteration
 					substr_index = end_index;
 					substr = &line_content[substr_index..];
-					res.push((name, uid, (start_index, end_index), widget_y_index));
+					res.push((name, uid, (start_column, end_column), widget_y_index));
 				}
 				res
 			})
 			.collect::<Vec<_>>();
 		indexes.sort_unstable_by_key(|(_, _, i, _)| *i);
 
-		//let _ = check_heights(&last_indexes, &indexes, &mut content_height, &last_line.unwrap());
+		check_heights(errors, &widgets_names, &last_indexes, &indexes, line);
 
 		// Make (widget, suffix) pairs from the end of the line
 		let mut last_index = line_content.len();
 		let mut line_res = vec![];
-		for (widget, uid, (line_index, line_end), line_height) in indexes.iter().rev() {
-			//let width = content_width.unwrap();
+		for (widget, uid, (start, end), line_height) in indexes.iter().rev() {
+			let (line_index, line_end) =
+				(byte_index(&line_content, *start), byte_index(&line_content, *end));
 			line_res.push((
-				((widget.clone(), *uid), line_height.clone()),
-				LitStr::new(&line_content[*line_end..last_index], line.span()),
+				((*widget, *uid), *line_height),
+				LitStr::new(&line_content[line_end..last_index], line.span()),
 			));
-			last_index = *line_index;
+			last_index = line_index;
 		}
 		// Reorder line to have it in appropriate order
 		line_res.reverse();
@@ -355,30 +370,49 @@ pub fn parse_frame_lines<'a>(
 
 		// Prepare next iteration
 		last_indexes = indexes;
-		//last_line = Some(line);
 	}
-	// TODO: check height of last_indexes
-	//let _ = check_heights(&last_indexes, &[], &mut content_height, &last_line.unwrap());
-	/*match widgets_size {
-		None => (),
-		Some(s) => {
-			for i in last_indexes {
-				if i.2 + 1 != s.1 {
-					errors.push(Diagnostic::spanned(
-						content.last().unwrap().span(),
-						Level::Error,
-						format!(
-							"Lines of {:?} missing (at {} out of {})",
-							i.0 .0.value(),
-							i.2,
-							s.1
-						),
-					))
-				}
+	if let Some(last_line) = content.last() {
+		check_heights(errors, &widgets_names, &last_indexes, &[], last_line);
+	}
+	res_lines
+}
+
+/// The index of the byte at which the given column (in terminal cells) starts in the line.
+fn byte_index(line: &str, column: usize) -> usize {
+	let mut width = 0;
+	for (i, c) in line.char_indices() {
+		if width >= column {
+			return i;
+		}
+		width += c.width().unwrap_or(0);
+	}
+	line.len()
+}
+
+/// Checks that every widget display of the previous line that doesn't continue on the current line
+/// has reached the expected height (when the widgets' size is known).
+fn check_heights(
+	errors: &mut Vec<Diagnostic>,
+	widgets_names: &WidgetsSizes,
+	previous: &[(char, usize, (usize, usize), usize)],
+	current: &[(char, usize, (usize, usize), usize)],
+	line: &LitStr,
+) {
+	for (name, uid, _, y_index) in previous {
+		if current.iter().any(|(_, other_uid, _, _)| other_uid == uid) {
+			continue;
+		}
+		let size = widgets_names.iter().find(|(n, _)| n == name).and_then(|(_, s)| *s.borrow());
+		if let Some((_, height)) = size {
+			if y_index + 1 != height {
+				errors.push(Diagnostic::spanned(
+					line.span(),
+					Level::Error,
+					format!("Lines of {:?} missing (got {} out of {})", name, y_index + 1, height),
+				));
 			}
 		}
-	}*/
-	res_lines
+	}
 }
 
 pub fn run(input: FrameMacro) -> (TokenStream, Vec<Diagnostic>) {
@@ -400,7 +434,9 @@ pub fn run(input: FrameMacro) -> (TokenStream, Vec<Diagnostic>) {
 					Pair::End(w) => (w, None),
 				};
 				res.push_value(&wi.expr);
-				punct.map(|p| res.push_punct(p));
+				if let Some(p) = punct {
+					res.push_punct(p)
+				}
 			}
 			quote!([#res])
 		}
@@ -488,8 +524,7 @@ mod tests {
 			r"| * HHHHHH WWWWWW * |"
 			r"| * HHHHHH WWWWWW * |"
 			r"\===================/"
-		)
-		.into();
+		);
 		let res = run(syn::parse2(frame_def).unwrap());
 		#[rustfmt::skip]
 		let expected: proc_macro2::TokenStream = quote!({
@@ -521,6 +556,38 @@ mod tests {
 		assert_eq!(res.0.to_string(), expected.to_string());
 	}
 
+	#[test]
+	fn wide_characters() {
+		let frame_def: proc_macro2::TokenStream = quote!(
+			['H': img]
+			"│世界HH│"
+			"│----HH│"
+		);
+		let res = run(syn::parse2(frame_def).unwrap());
+		#[rustfmt::skip]
+		let expected: proc_macro2::TokenStream = quote!({
+			let widgets = [img];
+			terminity_widgets::widgets::frame::Frame::new(
+				vec![
+					("│世界".to_owned(), vec![((0usize, 0usize), "│".to_owned())]),
+					("│----".to_owned(), vec![((0usize, 1usize), "│".to_owned())])
+				],
+				widgets
+			)
+		});
+		assert_eq!(res.1.len(), 0);
+		assert_eq!(res.0.to_string(), expected.to_string());
+
+		// Widths are counted in cells, not in characters
+		let frame_def: proc_macro2::TokenStream = quote!(
+			['H': img]
+			"│世界HH│"
+			"│--HH│"
+		);
+		let res = run(syn::parse2(frame_def).unwrap());
+		assert_eq!(res.1.len(), 1);
+	}
+
 	#[test]
 	fn coll_frame() {
 		let frame_def: proc_macro2::TokenStream = quote!(
@@ -534,8 +601,7 @@ mod tests {
 			r"| ccccc ddddd |"
 			r"| ccccc ddddd |"
 			r"\=============/"
-		)
-		.into();
+		);
 		let res = run(syn::parse2(frame_def).unwrap());
 		#[rustfmt::skip]
 		let expected: proc_macro2::TokenStream = quote!({
@@ -609,8 +675,7 @@ mod tests {
 			r"| aaaaa aaaaa |"
 			r"| aaaaa aaaaa |"
 			r"\=============/"
-		)
-		.into();
+		);
 		let res = run(syn::parse2(frame_def).unwrap());
 		#[rustfmt::skip]
 		let expected: proc_macro2::TokenStream = quote!({
@@ -684,8 +749,7 @@ mod tests {
 			r"| aaaaa aaaaa |"
 			r"| aaaaa aaaaa |"
 			r"\=============/"
-		)
-		.into();
+		);
 		let res = run(syn::parse2(frame_def).unwrap());
 		#[rustfmt::skip]
 		let expected: proc_macro2::TokenStream = quote!({
@@ -759,8 +823,7 @@ mod tests {
 			r"| aaaaa aaaaa |"
 			r"| aaaaa aaaaa |"
 			r"\=============/"
-		)
-		.into();
+		);
 		let res = run(syn::parse2(frame_def).unwrap());
 		println!("{:?}", res.1);
 		assert_eq!(res.1.len(), 1);
@@ -779,8 +842,7 @@ mod tests {
 			r"| aaaaa aaaaa |"
 			r"| aaaaa aaaaa |"
 			r"\=============/"
-		)
-		.into();
+		);
 		let res = run(syn::parse2(frame_def).unwrap());
 		println!("{:?}", res.1);
 		assert_eq!(res.1.len(), 1);
@@ -794,8 +856,7 @@ mod tests {
 			r"| aaaa |"
 			r"| aaaa |"
 			r"\======/"
-		)
-		.into();
+		);
 		let res = run(syn::parse2(frame_def).unwrap());
 		println!("{:?}", res.1);
 		assert_ne!(res.1.len(), 0);
@@ -808,8 +869,7 @@ mod tests {
 			r"/====\"
 			r"| aa |"
 			r"| aa |"
-		)
-		.into();
+		);
 		let res = run(syn::parse2(frame_def).unwrap());
 		println!("{:?}", res.1);
 		assert_eq!(res.1.len(), 1);
@@ -823,8 +883,7 @@ mod tests {
 			r"| aa |"
 			r"| aa |"
 			r"\====/"
-		)
-		.into();
+		);
 		let res = run(syn::parse2(frame_def).unwrap());
 		println!("{:?}", res.1);
 		assert_eq!(res.1.len(), 1);
@@ -839,8 +898,7 @@ mod tests {
 			r"| xxxx |"
 			r"| xxxx |"
 			r"\======/"
-		)
-		.into();
+		);
 		let res = run(syn::parse2(frame_def).unwrap());
 		#[rustfmt::skip]
 		let expected: proc_macro2::TokenStream = quote!({
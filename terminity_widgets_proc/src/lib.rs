@@ -1,4 +1,5 @@
 #![warn(missing_docs)]
+#![allow(clippy::tabs_in_doc_comments)]
 
 /*! Crate defining the procedural macros for
 	[terminity_widgets](https://docs.rs/terminity_widgets/latest/terminity_widgets/index.html).
@@ -15,16 +16,16 @@ use std::{cell::RefCell, collections::HashMap};
 
 use proc_macro::TokenStream;
 use proc_macro2::Span;
-use proc_macro_error::{abort, emit_call_site_error, emit_error, proc_macro_error};
+use proc_macro_error::{abort, proc_macro_error};
 use quote::quote;
 
 use syn::{
 	braced,
-	parse::{Nothing, Parse, ParseStream},
+	parse::{Parse, ParseStream},
 	parse2, parse_macro_input,
 	punctuated::Punctuated,
 	spanned::Spanned,
-	DeriveInput, LitStr, Meta, NestedMeta, PathArguments, Token,
+	DeriveInput, LitStr, Meta, NestedMeta, Token,
 };
 
 use crate::frame::parse_frame_lines;
@@ -149,24 +150,25 @@ fn parse_attr_content(content: Meta) -> Result<Vec<(syn::Path, syn::Lit)>, syn::
 	match content {
 		Meta::Path(_) => Ok(vec![]),
 		Meta::NameValue(nv) => Ok(vec![(nv.path, nv.lit)]),
-		Meta::List(l) => {
-			let r = l
-				.nested
-				.into_iter()
-				.map(|meta| match meta {
-					NestedMeta::Meta(m) => match m {
-						Meta::NameValue(nv) => Ok((nv.path, nv.lit)),
-						_ => Err(syn::Error::new(m.span(), "Expected 'key = value'")),
-					},
-					NestedMeta::Lit(_) => todo!(),
-				})
-				.collect();
-			r
-		}
+		Meta::List(l) => l
+			.nested
+			.into_iter()
+			.map(|meta| match meta {
+				NestedMeta::Meta(m) => match m {
+					Meta::NameValue(nv) => Ok((nv.path, nv.lit)),
+					_ => Err(syn::Error::new(m.span(), "Expected 'key = value'")),
+				},
+				NestedMeta::Lit(_) => todo!(),
+			})
+			.collect(),
 	}
 }
 
+/// Derive macro to build a frame out of a structure, aka. a Structure Frame.
 ///
+/// The layout of the frame is given through a `#[layout {...}]` attribute on the structure, and
+/// each field is given the char representing it in the layout through `#[layout(name = '.')]`.
+/// Every field has to be a widget, and the structure then implements `Widget`.
 #[proc_macro_error]
 #[proc_macro_derive(StructFrame, attributes(layout))]
 pub fn struct_frame(tokens: TokenStream) -> TokenStream {
@@ -198,7 +200,7 @@ pub fn struct_frame(tokens: TokenStream) -> TokenStream {
 				}
 				let attr_details = match attr_details.pop() {
 					None => Ok(vec![]),
-					Some(d) => d.parse_meta().map(parse_attr_content).unwrap_or_else(|e| Err(e)),
+					Some(d) => d.parse_meta().map(parse_attr_content).unwrap_or_else(Err),
 				};
 				// Extract attr_details
 				let name = attr_details
@@ -221,7 +223,7 @@ pub fn struct_frame(tokens: TokenStream) -> TokenStream {
 							}
 						})
 					})
-					.unwrap_or_else(|e| Err(e));
+					.unwrap_or_else(Err);
 				let name = match name {
 					Ok(Some(d)) => d,
 					Ok(None) => abort!(f.span(), "Missing name for frame layout"),
@@ -234,7 +236,7 @@ pub fn struct_frame(tokens: TokenStream) -> TokenStream {
 					// Size
 					RefCell::new(None),
 				);
-				if let Some(_) = res.insert(name.value(), details) {
+				if res.insert(name.value(), details).is_some() {
 					abort!(
 						name.span(),
 						"There are multiple fields of frame name {:?}.",
@@ -271,6 +273,7 @@ pub fn struct_frame(tokens: TokenStream) -> TokenStream {
 
 	let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
+	let layout_body_draw = layout_body.clone();
 	let disp_content = layout_body.into_iter().enumerate().map(|(line, (prefix, line_parts))| {
 		let line_parts = line_parts.into_iter().map(|(((name, _), line_i), suffix)| {
 			let (field, _size) = &widget_indexes[&name];
@@ -285,6 +288,22 @@ pub fn struct_frame(tokens: TokenStream) -> TokenStream {
 		})
 	});
 
+	let draw_content =
+		layout_body_draw.into_iter().enumerate().map(|(line, (prefix, line_parts))| {
+			let line_parts = line_parts.into_iter().map(|(((name, _), line_i), suffix)| {
+				let (field, _size) = &widget_indexes[&name];
+				quote! {
+					let x = terminity_widgets::render::draw_at(cells, x, &self.#field, #line_i);
+					let x = terminity_widgets::render::print_ansi(cells, x, #suffix);
+				}
+			});
+			quote!(#line => {
+				let x = terminity_widgets::render::print_ansi(cells, 0, #prefix);
+				#(#line_parts)*
+				x
+			})
+		});
+
 	let expanded = quote! {
 		#(#errors)* // Give the errors
 		impl #impl_generics Widget for #ident #ty_generics #where_clause {
@@ -295,6 +314,12 @@ pub fn struct_frame(tokens: TokenStream) -> TokenStream {
 				}
 				Ok(())
 			}
+			fn draw_line(&self, cells: &mut [terminity_widgets::render::Cell], line: usize) {
+				match line {
+					#(#draw_content,)*
+					_ => panic!("Drawing line out of struct frame"),
+				};
+			}
 			fn size(&self) -> (usize, usize) {
 				(#frame_width, #frame_height)
 			}